@@ -0,0 +1,74 @@
+//! Global allocator that keeps count, for the allocation numbers in bench mode
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering::Relaxed};
+
+pub struct Counting;
+
+static ALLOCS: AtomicUsize = AtomicUsize::new(0);
+static LIVE: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+fn grow(size: usize) {
+    let live = LIVE.fetch_add(size, Relaxed) + size;
+    PEAK.fetch_max(live, Relaxed);
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let p = System.alloc(layout);
+        if !p.is_null() {
+            ALLOCS.fetch_add(1, Relaxed);
+            grow(layout.size());
+        }
+        p
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let p = System.alloc_zeroed(layout);
+        if !p.is_null() {
+            ALLOCS.fetch_add(1, Relaxed);
+            grow(layout.size());
+        }
+        p
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        LIVE.fetch_sub(layout.size(), Relaxed);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let p = System.realloc(ptr, layout, new_size);
+        if !p.is_null() {
+            // Counts as an allocation, the block usually moves
+            ALLOCS.fetch_add(1, Relaxed);
+            LIVE.fetch_sub(layout.size(), Relaxed);
+            grow(new_size);
+        }
+        p
+    }
+}
+
+/// Allocations made while running something and the most bytes it had at once
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Usage {
+    pub allocs: usize,
+    pub peak: usize,
+}
+
+/// Run `f` and see how much it allocated, only makes sense on one thread
+pub fn measure<T>(f: impl FnOnce() -> T) -> (T, Usage) {
+    let base = LIVE.load(Relaxed);
+    PEAK.store(base, Relaxed);
+    let allocs = ALLOCS.load(Relaxed);
+
+    let out = f();
+
+    let usage = Usage {
+        allocs: ALLOCS.load(Relaxed) - allocs,
+        peak: PEAK.load(Relaxed).saturating_sub(base),
+    };
+
+    (out, usage)
+}
@@ -0,0 +1,133 @@
+//! `aoc bench`, runs parts over and over while keeping an eye on time and memory
+
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::alloc::{self, Usage};
+use crate::days::Day;
+
+pub struct Bench {
+    pub day: usize,
+    pub part: usize,
+    /// Sorted, fastest first
+    pub times: Vec<Duration>,
+    /// Worst of all the runs, they should all be the same anyway
+    pub usage: Usage,
+}
+
+impl Bench {
+    pub fn min(&self) -> Duration {
+        self.times[0]
+    }
+
+    pub fn median(&self) -> Duration {
+        self.times[self.times.len() / 2]
+    }
+
+    pub fn max(&self) -> Duration {
+        self.times[self.times.len() - 1]
+    }
+}
+
+/// Run a part `runs` times, None if there's no such part
+pub fn bench_part(
+    day: &Day,
+    part: usize,
+    input: &str,
+    runs: usize,
+) -> Option<Result<Bench, String>> {
+    day.part(part)?;
+
+    let mut times = vec![];
+    let mut usage = Usage::default();
+
+    for _ in 0..runs.max(1) {
+        let start = Instant::now();
+        let (answer, u) = alloc::measure(|| day.solve(part, input).unwrap());
+        times.push(start.elapsed());
+
+        if let Err(e) = answer {
+            return Some(Err(e));
+        }
+
+        usage.allocs = usage.allocs.max(u.allocs);
+        usage.peak = usage.peak.max(u.peak);
+    }
+
+    times.sort();
+
+    Some(Ok(Bench {
+        day: day.day,
+        part,
+        times,
+        usage,
+    }))
+}
+
+fn bytes(n: usize) -> String {
+    match n {
+        _ if n >= 1 << 20 => format!("{:.1} MiB", n as f64 / (1 << 20) as f64),
+        _ if n >= 1 << 10 => format!("{:.1} KiB", n as f64 / (1 << 10) as f64),
+        _ => format!("{} B", n),
+    }
+}
+
+pub fn print_table(benches: &[Bench]) {
+    println!(
+        "{:>3}  {:>4}  {:>4}  {:>12}  {:>12}  {:>12}  {:>10}  {:>10}",
+        "Day", "Part", "Runs", "Min", "Median", "Max", "Allocs", "Peak"
+    );
+
+    for b in benches {
+        println!(
+            "{:>3}  {:>4}  {:>4}  {:>12}  {:>12}  {:>12}  {:>10}  {:>10}",
+            b.day,
+            b.part,
+            b.times.len(),
+            format!("{:.3?}", b.min()),
+            format!("{:.3?}", b.median()),
+            format!("{:.3?}", b.max()),
+            b.usage.allocs,
+            bytes(b.usage.peak),
+        );
+    }
+}
+
+/// Append the results as TSV so runs from different changes can be compared, times
+/// are in nanoseconds and the first column is when the bench ran in unix seconds
+pub fn write(path: &Path, benches: &[Bench]) -> io::Result<()> {
+    let new = !path.exists();
+    let mut f = OpenOptions::new().create(true).append(true).open(path)?;
+
+    if new {
+        writeln!(
+            f,
+            "timestamp\tday\tpart\truns\tmin_ns\tmedian_ns\tmax_ns\tallocs\tpeak_bytes"
+        )?;
+    }
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    for b in benches {
+        writeln!(
+            f,
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            now,
+            b.day,
+            b.part,
+            b.times.len(),
+            b.min().as_nanos(),
+            b.median().as_nanos(),
+            b.max().as_nanos(),
+            b.usage.allocs,
+            b.usage.peak
+        )?;
+    }
+
+    Ok(())
+}
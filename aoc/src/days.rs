@@ -0,0 +1,96 @@
+use common::input::Example;
+use common::output::Record;
+use common::SolveError;
+
+pub type Solver = fn(&str) -> Result<Record, SolveError>;
+
+pub struct Day {
+    pub day: usize,
+    pub part1: Solver,
+    pub part2: Option<Solver>,
+    pub examples: &'static [Example],
+}
+
+impl Day {
+    pub fn part(&self, part: usize) -> Option<Solver> {
+        match part {
+            1 => Some(self.part1),
+            2 => self.part2,
+            _ => None,
+        }
+    }
+
+    /// Run a part, errors come back ready to print. None if there's no such part
+    pub fn solve(&self, part: usize, input: &str) -> Option<Result<Record, String>> {
+        let solver = self.part(part)?;
+
+        Some(solver(input).map_err(|e| {
+            let msg = format!("day {} part {}: {}", self.day, part, e);
+            match e.snippet(input) {
+                Some(s) => format!("{}\n{}", msg, s),
+                None => msg,
+            }
+        }))
+    }
+}
+
+macro_rules! day {
+    ($n:expr, $d:ident) => {
+        Day {
+            day: $n,
+            // Days that can fail after parsing have their own errors
+            part1: |i| {
+                $d::part1(i)
+                    .map(|a| Record::new($n, 1, &a))
+                    .map_err(SolveError::from)
+            },
+            part2: Some(|i| {
+                $d::part2(i)
+                    .map(|a| Record::new($n, 2, &a))
+                    .map_err(SolveError::from)
+            }),
+            examples: $d::EXAMPLES,
+        }
+    };
+}
+
+pub const DAYS: [Day; 25] = [
+    day!(1, day1),
+    day!(2, day2),
+    day!(3, day3),
+    day!(4, day4),
+    day!(5, day5),
+    day!(6, day6),
+    day!(7, day7),
+    day!(8, day8),
+    day!(9, day9),
+    day!(10, day10),
+    day!(11, day11),
+    day!(12, day12),
+    day!(13, day13),
+    day!(14, day14),
+    day!(15, day15),
+    day!(16, day16),
+    day!(17, day17),
+    day!(18, day18),
+    day!(19, day19),
+    day!(20, day20),
+    day!(21, day21),
+    day!(22, day22),
+    day!(23, day23),
+    day!(24, day24),
+    Day {
+        day: 25,
+        part1: |i| {
+            day25::part1(i)
+                .map(|a| Record::new(25, 1, &a))
+                .map_err(SolveError::from)
+        },
+        part2: None,
+        examples: day25::EXAMPLES,
+    },
+];
+
+pub fn get(day: usize) -> Option<&'static Day> {
+    DAYS.get(day.wrapping_sub(1))
+}
@@ -0,0 +1,94 @@
+//! Runs every day on its real input and compares with answers.txt at the top of the
+//! workspace. It's optional, nothing gets checked if the file isn't there.
+//!
+//! Slow in debug builds so it's ignored by default, run it with
+//! `cargo test --release -p aoc -- --ignored`. Set AOC_ANSWERS to use another file and
+//! AOC_DAYS=12,15 to only check some days.
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use common::input::Source;
+
+use crate::days;
+
+fn root() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("..")
+}
+
+/// (day, part, answer) from lines like "10 2 10595"
+fn parse_answers(text: &str) -> Vec<(usize, usize, String)> {
+    text.lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .map(|l| {
+            let mut it = l.splitn(3, ' ');
+            let mut num = || it.next().and_then(|n| n.parse().ok());
+            let (day, part) = (num(), num());
+            let answer = it.next().unwrap_or("").replace("\\n", "\n");
+
+            match (day, part) {
+                (Some(d), Some(p)) => (d, p, answer),
+                _ => panic!("bad line in the answers file: '{}'", l),
+            }
+        })
+        .collect()
+}
+
+#[test]
+#[ignore]
+fn golden() {
+    let path = env::var_os("AOC_ANSWERS")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| root().join("answers.txt"));
+
+    let text = match fs::read_to_string(&path) {
+        Ok(t) => t,
+        Err(_) => {
+            eprintln!("no answers at {}, skipping", path.display());
+            return;
+        }
+    };
+
+    let only: Option<Vec<usize>> = env::var("AOC_DAYS")
+        .ok()
+        .map(|s| s.split(',').filter_map(|d| d.trim().parse().ok()).collect());
+
+    let mut wrong = vec![];
+
+    for (d, p, expected) in parse_answers(&text) {
+        if only.as_ref().is_some_and(|o| !o.contains(&d)) {
+            continue;
+        }
+
+        let day = days::get(d).unwrap_or_else(|| panic!("there's no day {}", d));
+        let solver = day
+            .part(p)
+            .unwrap_or_else(|| panic!("day {} has no part {}", d, p));
+
+        let input = Source::File(root().join(format!("day{}/input", d)))
+            .read(day.examples)
+            .unwrap_or_else(|e| panic!("{}", e));
+
+        match solver(&input) {
+            Ok(r) if r.answer == expected => (),
+            Ok(r) => wrong.push(format!(
+                "day {} part {}: got {}, want {}",
+                d, p, r.answer, expected
+            )),
+            Err(e) => wrong.push(format!("day {} part {}: {}", d, p, e)),
+        }
+    }
+
+    assert!(wrong.is_empty(), "\n{}", wrong.join("\n"));
+}
+
+#[test]
+fn answers_file() {
+    let a = parse_answers("# comment\n\n10 1 .#\\n#.\n25 1 407\n");
+    assert_eq!(
+        a,
+        vec![(10, 1, ".#\n#.".to_owned()), (25, 1, "407".to_owned())]
+    );
+}
@@ -0,0 +1,297 @@
+use std::env;
+use std::path::PathBuf;
+use std::process;
+use std::time::{Duration, Instant};
+
+use common::input::Source;
+use common::output::{Format, Record, TSV_HEADER};
+
+mod alloc;
+mod bench;
+mod days;
+#[cfg(test)]
+mod golden;
+
+use crate::days::Day;
+
+#[global_allocator]
+static ALLOC: alloc::Counting = alloc::Counting;
+
+const USAGE: &str = "\
+Usage: aoc run --day <N> [--part <1|2>] [--input <PATH> | --example <NAME>] [--format <F>]
+       aoc run --all [--format <F>]
+       aoc bench [--runs <N>] [--out <PATH>] <same as run>
+
+Inputs default to dayN/input, relative to where you run it from.
+Use --input - to read from stdin. Formats are text, json or tsv, the last two give
+one {day, part, answer, extra} record per part.
+
+bench runs each part 10 times by default and appends the results to bench.tsv.";
+
+const RUNS: usize = 10;
+const BENCH_OUT: &str = "bench.tsv";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Cmd {
+    Run,
+    Bench,
+}
+
+#[derive(Debug)]
+struct Opts {
+    cmd: Cmd,
+    day: Option<usize>,
+    part: Option<usize>,
+    source: Option<Source>,
+    all: bool,
+    runs: Option<usize>,
+    out: Option<PathBuf>,
+    format: Format,
+}
+
+fn parse_args(args: &[String]) -> Result<Opts, String> {
+    let mut it = args.iter();
+
+    let cmd = match it.next().map(|s| s.as_str()) {
+        Some("run") => Cmd::Run,
+        Some("bench") => Cmd::Bench,
+        Some(cmd) => return Err(format!("unknown command '{}'", cmd)),
+        None => return Err("missing command".to_owned()),
+    };
+
+    let mut opts = Opts {
+        cmd,
+        day: None,
+        part: None,
+        source: None,
+        all: false,
+        runs: None,
+        out: None,
+        format: Format::Text,
+    };
+
+    while let Some(arg) = it.next() {
+        let mut value = |name: &str| {
+            it.next()
+                .cloned()
+                .ok_or_else(|| format!("{} needs a value", name))
+        };
+
+        match arg.as_str() {
+            "--day" | "-d" => {
+                let v = value(arg)?;
+                opts.day = Some(v.parse().map_err(|_| format!("bad day '{}'", v))?);
+            }
+            "--part" | "-p" => {
+                let v = value(arg)?;
+                opts.part = Some(v.parse().map_err(|_| format!("bad part '{}'", v))?);
+            }
+            "--input" | "-i" => opts.source = Some(Source::from_arg(&value(arg)?)),
+            "--example" | "-e" => opts.source = Some(Source::Example(value(arg)?)),
+            "--all" | "-a" => opts.all = true,
+            "--runs" | "-n" => {
+                let v = value(arg)?;
+                match v.parse() {
+                    Ok(n) if n > 0 => opts.runs = Some(n),
+                    _ => return Err(format!("bad run count '{}'", v)),
+                }
+            }
+            "--out" | "-o" => opts.out = Some(PathBuf::from(value(arg)?)),
+            "--format" | "-f" => opts.format = value(arg)?.parse()?,
+            x => return Err(format!("unknown option '{}'", x)),
+        }
+    }
+
+    if opts.all && (opts.day.is_some() || opts.part.is_some() || opts.source.is_some()) {
+        return Err(
+            "--all runs everything, it doesn't take --day, --part, --input or --example".to_owned(),
+        );
+    }
+
+    if cmd != Cmd::Bench && (opts.runs.is_some() || opts.out.is_some()) {
+        return Err("--runs and --out are only for bench".to_owned());
+    }
+
+    if cmd != Cmd::Run && opts.format != Format::Text {
+        return Err("--format is only for run".to_owned());
+    }
+
+    if !opts.all && opts.day.is_none() {
+        return Err("either --day or --all is needed".to_owned());
+    }
+
+    Ok(opts)
+}
+
+struct Run {
+    rec: Record,
+    time: Duration,
+}
+
+fn read_input(day: &Day, source: Option<&Source>) -> Result<String, String> {
+    let default = Source::File(PathBuf::from(format!("day{}/input", day.day)));
+
+    source
+        .unwrap_or(&default)
+        .read(day.examples)
+        .map_err(|e| e.to_string())
+}
+
+fn run_part(day: &Day, part: usize, input: &str) -> Option<Result<Run, String>> {
+    let start = Instant::now();
+    let rec = day.solve(part, input)?;
+    let time = start.elapsed();
+
+    Some(rec.map(|rec| Run { rec, time }))
+}
+
+fn print_table(runs: &[Run]) {
+    let width = runs
+        .iter()
+        .flat_map(|r| r.rec.answer.lines())
+        .map(|l| l.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("Answer".len());
+
+    println!(
+        "{:>3}  {:>4}  {:<w$}  {:>12}",
+        "Day",
+        "Part",
+        "Answer",
+        "Time",
+        w = width
+    );
+
+    for r in runs {
+        let mut lines = r.rec.answer.lines();
+        let first = lines.next().unwrap_or("");
+        let time = format!("{:.3?}", r.time);
+
+        println!(
+            "{:>3}  {:>4}  {:<w$}  {:>12}",
+            r.rec.day,
+            r.rec.part,
+            first,
+            time,
+            w = width
+        );
+
+        // Day 10 draws its answer
+        for l in lines {
+            println!("{:>3}  {:>4}  {:<w$}", "", "", l, w = width);
+        }
+    }
+
+    let total: Duration = runs.iter().map(|r| r.time).sum();
+    println!(
+        "{:>3}  {:>4}  {:<w$}  {:>12}",
+        "",
+        "",
+        "Total",
+        format!("{:.3?}", total),
+        w = width
+    );
+}
+
+/// A day to run with its input and parts
+struct Job {
+    day: &'static Day,
+    input: String,
+    parts: Vec<usize>,
+}
+
+fn jobs(opts: &Opts) -> Result<Vec<Job>, String> {
+    if opts.all {
+        return days::DAYS
+            .iter()
+            .map(|day| {
+                let parts = (1..=2).filter(|&p| day.part(p).is_some()).collect();
+                let input = read_input(day, None)?;
+                Ok(Job { day, input, parts })
+            })
+            .collect();
+    }
+
+    let n = opts.day.unwrap();
+    let day = days::get(n).ok_or_else(|| format!("there's no day {}", n))?;
+
+    let parts = match opts.part {
+        Some(p) if day.part(p).is_none() => return Err(format!("day {} has no part {}", n, p)),
+        Some(p) => vec![p],
+        None => (1..=2).filter(|&p| day.part(p).is_some()).collect(),
+    };
+
+    let input = read_input(day, opts.source.as_ref())?;
+    Ok(vec![Job { day, input, parts }])
+}
+
+fn run(opts: &Opts) -> Result<(), String> {
+    let jobs = jobs(opts)?;
+
+    let mut runs = vec![];
+
+    for j in &jobs {
+        for &p in &j.parts {
+            let r = run_part(j.day, p, &j.input).unwrap()?;
+
+            // Print as we go unless it's going in the table
+            match opts.format {
+                Format::Text if opts.all => (),
+                Format::Text => println!("{}", r.rec.text()),
+                Format::Json => println!("{}", r.rec.json()),
+                Format::Tsv => {
+                    if runs.is_empty() {
+                        println!("{}", TSV_HEADER);
+                    }
+                    println!("{}", r.rec.tsv());
+                }
+            }
+
+            runs.push(r);
+        }
+    }
+
+    if opts.all && opts.format == Format::Text {
+        print_table(&runs);
+    }
+
+    Ok(())
+}
+
+fn bench(opts: &Opts) -> Result<(), String> {
+    let runs = opts.runs.unwrap_or(RUNS);
+    let out = opts.out.clone().unwrap_or_else(|| PathBuf::from(BENCH_OUT));
+    let mut benches = vec![];
+
+    for j in &jobs(opts)? {
+        for &p in &j.parts {
+            benches.push(bench::bench_part(j.day, p, &j.input, runs).unwrap()?);
+        }
+    }
+
+    bench::print_table(&benches);
+    bench::write(&out, &benches).map_err(|e| format!("can't write {}: {}", out.display(), e))
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let opts = match parse_args(&args) {
+        Ok(o) => o,
+        Err(e) => {
+            eprintln!("aoc: {}\n\n{}", e, USAGE);
+            process::exit(2);
+        }
+    };
+
+    let res = match opts.cmd {
+        Cmd::Run => run(&opts),
+        Cmd::Bench => bench(&opts),
+    };
+
+    if let Err(e) = res {
+        eprintln!("aoc: {}", e);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,248 @@
+//! Bare bones property and fuzz testing, there are no crates around to do it for us.
+//!
+//! The fuzz tests run a few thousand cases by default, set FUZZ_ITERS for a longer go
+//! and FUZZ_SEED to try different inputs:
+//! `FUZZ_ITERS=1000000 cargo test --release fuzz`
+
+use std::env;
+use std::panic::{self, AssertUnwindSafe};
+
+use crate::input::Example;
+
+/// xorshift64*, plenty random for making up test cases
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        // Zero would get stuck
+        Rng(seed.max(1))
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// Something in `0..n`
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    /// Something in `lo..=hi`
+    pub fn range(&mut self, lo: i64, hi: i64) -> i64 {
+        let span = (hi as i128 - lo as i128 + 1) as u128;
+        (lo as i128 + (self.next_u64() as u128 % span) as i128) as i64
+    }
+
+    pub fn pick<'a, T>(&mut self, xs: &'a [T]) -> &'a T {
+        &xs[self.below(xs.len())]
+    }
+
+    /// True one time in `n`
+    pub fn one_in(&mut self, n: usize) -> bool {
+        self.below(n) == 0
+    }
+
+    /// An i32 that's small most of the time but hits the edges now and then
+    pub fn i32(&mut self) -> i32 {
+        match self.below(8) {
+            0 => *self.pick(&[i32::MIN, i32::MAX, 0, -1]),
+            1 => self.range(i32::MIN.into(), i32::MAX.into()) as i32,
+            _ => self.range(-1000, 1000) as i32,
+        }
+    }
+}
+
+/// Things that break parsers, the chars the inputs use and a few they don't
+const PIECES: &[&str] = &[
+    "0",
+    "1",
+    "9",
+    "-",
+    "+",
+    ",",
+    ".",
+    "..",
+    ":",
+    ";",
+    "<",
+    ">",
+    "=",
+    "#",
+    "@",
+    "x",
+    "[",
+    "]",
+    " ",
+    "  ",
+    "\t",
+    "(",
+    ")",
+    "|",
+    "^",
+    "$",
+    "N",
+    "S",
+    "E",
+    "W",
+    "G",
+    "/",
+    "\\",
+    "a",
+    "to",
+    "units",
+    "hit",
+    "damage",
+    "with",
+    "weak",
+    "immune",
+    "Step",
+    "Guard",
+    "falls",
+    "wakes",
+    "Before:",
+    "After:",
+    "#ip",
+    "addi",
+    "é",
+    "\u{0}",
+    "💥",
+    "99999999999999999999",
+    "-2147483649",
+    "2147483648",
+];
+
+/// Mess up a valid input a few times over, inserting, deleting and replacing pieces
+pub fn mutate(rng: &mut Rng, s: &str) -> String {
+    let mut chars: Vec<char> = s.chars().collect();
+
+    for _ in 0..=rng.below(4) {
+        let at = rng.below(chars.len() + 1);
+
+        match rng.below(4) {
+            0 => {
+                let piece: Vec<char> = rng.pick(PIECES).chars().collect();
+                chars.splice(at..at, piece);
+            }
+            1 if at < chars.len() => {
+                let end = (at + 1 + rng.below(8)).min(chars.len());
+                chars.drain(at..end);
+            }
+            2 if at < chars.len() => {
+                let piece: Vec<char> = rng.pick(PIECES).chars().collect();
+                chars.splice(at..at + 1, piece);
+            }
+            _ => chars.truncate(at),
+        }
+    }
+
+    chars.into_iter().collect()
+}
+
+/// Junk glued together from the pieces
+pub fn garbage(rng: &mut Rng, max_pieces: usize) -> String {
+    (0..rng.below(max_pieces + 1))
+        .map(|_| *rng.pick(PIECES))
+        .collect()
+}
+
+fn env_num(name: &str, default: u64) -> u64 {
+    env::var(name)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+/// Feed `f` mangled versions of the `seeds` and some plain garbage. It should come back
+/// with an error for the bad ones instead of panicking, if it does panic the test fails
+/// and shows the input that did it.
+pub fn fuzz<F: FnMut(&str)>(seeds: &[&str], iters: usize, mut f: F) {
+    let iters = env_num("FUZZ_ITERS", iters as u64);
+    let mut rng = Rng::new(env_num("FUZZ_SEED", 0x5eed));
+
+    for _ in 0..iters {
+        let input = if seeds.is_empty() || rng.one_in(4) {
+            garbage(&mut rng, 30)
+        } else {
+            let seed = *rng.pick(seeds);
+            mutate(&mut rng, seed)
+        };
+
+        let res = panic::catch_unwind(AssertUnwindSafe(|| f(&input)));
+        if res.is_err() {
+            panic!("panicked on {:?}", input);
+        }
+    }
+}
+
+/// `fuzz` with a day's examples for seeds, for the tests that run the parts on whole
+/// inputs
+pub fn fuzz_examples<F: FnMut(&str)>(examples: &[Example], f: F) {
+    let seeds: Vec<&str> = examples.iter().map(|(_, e)| *e).collect();
+    fuzz(&seeds, 2000, f);
+}
+
+/// Whether every number in `s` is at most `limit` either way. The puzzles do work in
+/// proportion to their numbers, so running the parts on a fuzzed input that passes
+/// keeps each case to a bounded number of steps.
+pub fn small(s: &str, limit: u64) -> bool {
+    (s.split(|c: char| !c.is_ascii_digit()))
+        .filter(|n| !n.is_empty())
+        .all(|n| n.parse::<u64>().is_ok_and(|n| n <= limit))
+}
+
+/// Check `prop` on `n` cases made up by `gen`
+pub fn check<T, G, P>(n: usize, mut gen: G, mut prop: P)
+where
+    T: std::fmt::Debug,
+    G: FnMut(&mut Rng) -> T,
+    P: FnMut(&T),
+{
+    let mut rng = Rng::new(env_num("FUZZ_SEED", 0x5eed));
+
+    for _ in 0..n {
+        let case = gen(&mut rng);
+        let res = panic::catch_unwind(AssertUnwindSafe(|| prop(&case)));
+        if res.is_err() {
+            panic!("failed for {:?}", case);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn range_stays_put() {
+        let mut rng = Rng::new(1);
+
+        for _ in 0..10_000 {
+            let v = rng.range(-3, 3);
+            assert!((-3..=3).contains(&v));
+        }
+
+        rng.range(i64::MIN, i64::MAX);
+    }
+
+    #[test]
+    fn small_numbers() {
+        assert!(small("x=1, y=-20..300", 300));
+        assert!(!small("x=1, y=-20..301", 300));
+        assert!(!small("99999999999999999999", u64::MAX));
+        assert!(small("no numbers", 0));
+    }
+
+    #[test]
+    fn mutate_keeps_chars_whole() {
+        let mut rng = Rng::new(7);
+
+        for _ in 0..1000 {
+            // Would blow up on a bad char boundary
+            let s = mutate(&mut rng, "é💥x=1, y=2..3");
+            let _ = s.chars().count();
+        }
+    }
+}
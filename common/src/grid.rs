@@ -0,0 +1,295 @@
+//! Dense 2D maps indexed by `Pos`, the top left corner can be anywhere
+
+use std::ops::{Index, IndexMut};
+
+use crate::parse::bad_char;
+use crate::{ParseError, Pos};
+
+/// Up, left, right, down, so reading order
+const SIDES: [Pos; 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+
+/// Sides and corners in reading order
+const AROUND: [Pos; 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    /// Position of the first cell
+    origin: Pos,
+    w: usize,
+    h: usize,
+    cells: Vec<T>,
+}
+
+impl<T: Clone> Grid<T> {
+    /// `w` by `h` of `fill`, starting at (0, 0)
+    pub fn new(w: usize, h: usize, fill: T) -> Grid<T> {
+        Grid {
+            origin: (0, 0),
+            w,
+            h,
+            cells: vec![fill; w * h],
+        }
+    }
+
+    /// Covers everything from `min` to `max`, both included
+    pub fn from_corners(min: Pos, max: Pos, fill: T) -> Grid<T> {
+        let w = (max.0 - min.0 + 1).max(0) as usize;
+        let h = (max.1 - min.1 + 1).max(0) as usize;
+
+        Grid {
+            origin: min,
+            ..Grid::new(w, h, fill)
+        }
+    }
+
+    /// A map drawn with one char per cell, `f` makes the cell or None if the char isn't
+    /// allowed. Short lines get padded with `pad` out to the longest one.
+    pub fn parse<F>(
+        input: &str,
+        what: &'static str,
+        pad: T,
+        mut f: F,
+    ) -> Result<Grid<T>, ParseError>
+    where
+        F: FnMut(Pos, char) -> Option<T>,
+    {
+        let w = input.lines().map(|l| l.chars().count()).max().unwrap_or(0);
+        let mut cells = vec![];
+
+        for (y, line) in input.lines().enumerate() {
+            let mut n = 0;
+
+            for (x, c) in line.chars().enumerate() {
+                let cell =
+                    f((x as i32, y as i32), c).ok_or_else(|| bad_char(what, y + 1, x + 1, c))?;
+                cells.push(cell);
+                n += 1;
+            }
+
+            cells.extend((n..w).map(|_| pad.clone()));
+        }
+
+        Ok(Grid {
+            origin: (0, 0),
+            w,
+            h: input.lines().count(),
+            cells,
+        })
+    }
+}
+
+impl<T> Grid<T> {
+    pub fn width(&self) -> usize {
+        self.w
+    }
+
+    pub fn height(&self) -> usize {
+        self.h
+    }
+
+    /// Top left corner
+    pub fn min(&self) -> Pos {
+        self.origin
+    }
+
+    /// Bottom right corner
+    pub fn max(&self) -> Pos {
+        (
+            self.origin.0 + self.w as i32 - 1,
+            self.origin.1 + self.h as i32 - 1,
+        )
+    }
+
+    fn idx(&self, p: Pos) -> Option<usize> {
+        let x = p.0 as i64 - self.origin.0 as i64;
+        let y = p.1 as i64 - self.origin.1 as i64;
+
+        if x < 0 || y < 0 || x >= self.w as i64 || y >= self.h as i64 {
+            return None;
+        }
+
+        Some(y as usize * self.w + x as usize)
+    }
+
+    fn pos(&self, i: usize) -> Pos {
+        pos_at(self.origin, self.w, i)
+    }
+
+    pub fn contains(&self, p: Pos) -> bool {
+        self.idx(p).is_some()
+    }
+
+    pub fn get(&self, p: Pos) -> Option<&T> {
+        self.idx(p).map(|i| &self.cells[i])
+    }
+
+    pub fn get_mut(&mut self, p: Pos) -> Option<&mut T> {
+        self.idx(p).map(move |i| &mut self.cells[i])
+    }
+
+    /// Every position in reading order, top to bottom then left to right
+    pub fn positions(&self) -> impl Iterator<Item = Pos> + '_ {
+        (0..self.cells.len()).map(move |i| self.pos(i))
+    }
+
+    /// Cells with their positions in reading order
+    pub fn iter(&self) -> impl Iterator<Item = (Pos, &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Pos, &mut T)> {
+        let (origin, w) = (self.origin, self.w);
+        let cells = self.cells.iter_mut().enumerate();
+        cells.map(move |(i, c)| (pos_at(origin, w, i), c))
+    }
+
+    /// Just the cells, in reading order
+    pub fn values(&self) -> std::slice::Iter<'_, T> {
+        self.cells.iter()
+    }
+
+    pub fn values_mut(&mut self) -> std::slice::IterMut<'_, T> {
+        self.cells.iter_mut()
+    }
+
+    /// One slice per row, top first
+    pub fn rows(&self) -> std::slice::Chunks<'_, T> {
+        self.cells.chunks(self.w.max(1))
+    }
+
+    /// The sides of `p` that are on the grid, in reading order
+    pub fn neighbours4(&self, p: Pos) -> impl Iterator<Item = Pos> + '_ {
+        self.around(p, &SIDES)
+    }
+
+    /// Sides and corners of `p` that are on the grid, in reading order
+    pub fn neighbours8(&self, p: Pos) -> impl Iterator<Item = Pos> + '_ {
+        self.around(p, &AROUND)
+    }
+
+    fn around<'a>(&'a self, p: Pos, offsets: &'static [Pos]) -> impl Iterator<Item = Pos> + 'a {
+        offsets
+            .iter()
+            .map(move |d| (p.0 + d.0, p.1 + d.1))
+            .filter(move |&n| self.contains(n))
+    }
+
+    pub fn map<U, F: FnMut(&T) -> U>(&self, f: F) -> Grid<U> {
+        Grid {
+            origin: self.origin,
+            w: self.w,
+            h: self.h,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+
+    /// Draw it back out one char per cell, lines split by '\n' with none at the end
+    pub fn render<F: FnMut(Pos, &T) -> char>(&self, mut f: F) -> String {
+        let mut out = String::with_capacity((self.w + 1) * self.h);
+
+        for (i, c) in self.cells.iter().enumerate() {
+            if i > 0 && i % self.w == 0 {
+                out.push('\n');
+            }
+            out.push(f(self.pos(i), c));
+        }
+
+        out
+    }
+}
+
+fn pos_at(origin: Pos, w: usize, i: usize) -> Pos {
+    (origin.0 + (i % w) as i32, origin.1 + (i / w) as i32)
+}
+
+impl<T> Index<Pos> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, p: Pos) -> &T {
+        match self.idx(p) {
+            Some(i) => &self.cells[i],
+            None => panic!(
+                "{:?} is off the grid ({:?} to {:?})",
+                p,
+                self.min(),
+                self.max()
+            ),
+        }
+    }
+}
+
+impl<T> IndexMut<Pos> for Grid<T> {
+    fn index_mut(&mut self, p: Pos) -> &mut T {
+        match self.idx(p) {
+            Some(i) => &mut self.cells[i],
+            None => panic!(
+                "{:?} is off the grid ({:?} to {:?})",
+                p,
+                self.min(),
+                self.max()
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offset_origin() {
+        let mut g = Grid::from_corners((-2, 5), (1, 7), 0);
+        assert_eq!((g.width(), g.height(), g.max()), (4, 3, (1, 7)));
+
+        g[(-2, 5)] = 1;
+        g[(1, 7)] = 2;
+        assert_eq!(g.get((-3, 5)), None);
+        assert_eq!(g.get((1, 8)), None);
+        assert_eq!(g.iter().next(), Some(((-2, 5), &1)));
+        assert_eq!(g.iter().last(), Some(((1, 7), &2)));
+        assert_eq!(g.rows().nth(2), Some(&[0, 0, 0, 2][..]));
+    }
+
+    #[test]
+    fn neighbours() {
+        let g = Grid::new(3, 3, ());
+
+        let n: Vec<Pos> = g.neighbours4((1, 1)).collect();
+        assert_eq!(n, vec![(1, 0), (0, 1), (2, 1), (1, 2)]);
+
+        let n: Vec<Pos> = g.neighbours8((0, 0)).collect();
+        assert_eq!(n, vec![(1, 0), (0, 1), (1, 1)]);
+    }
+
+    #[test]
+    fn parse_render() {
+        let text = "#.#\n.\n##";
+        let g = Grid::parse(text, "'#' or '.'", false, |_, c| match c {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None,
+        })
+        .unwrap();
+
+        assert_eq!((g.width(), g.height()), (3, 3));
+        assert_eq!(g.render(|_, &w| if w { '#' } else { '.' }), "#.#\n...\n##.");
+
+        let e = Grid::parse(
+            "..\n.x",
+            "'.'",
+            (),
+            |_, c| if c == '.' { Some(()) } else { None },
+        )
+        .unwrap_err();
+        assert_eq!(e.to_string(), "line 2, column 2: expected '.' at 'x'");
+    }
+}
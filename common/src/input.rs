@@ -0,0 +1,223 @@
+use std::cell::Cell;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io::{self, Read};
+use std::path::PathBuf;
+use std::process;
+
+use crate::output::{Answer, Format, Record, TSV_HEADER};
+use crate::parse::ParseError;
+
+/// A named puzzle example baked into a day, (name, text)
+pub type Example = (&'static str, &'static str);
+
+/// Where to get the puzzle input from
+#[derive(Debug, Clone, PartialEq)]
+pub enum Source {
+    File(PathBuf),
+    Stdin,
+    Example(String),
+}
+
+#[derive(Debug)]
+pub enum InputError {
+    Io(PathBuf, io::Error),
+    Stdin(io::Error),
+    NoExample(String, Vec<&'static str>),
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InputError::Io(p, e) => write!(f, "can't read {}: {}", p.display(), e),
+            InputError::Stdin(e) => write!(f, "can't read stdin: {}", e),
+            InputError::NoExample(name, names) => {
+                write!(f, "no example named '{}'", name)?;
+                if names.is_empty() {
+                    write!(f, ", there are none for this day")
+                } else {
+                    write!(f, ", try one of: {}", names.join(", "))
+                }
+            }
+        }
+    }
+}
+
+impl std::error::Error for InputError {}
+
+/// Why a part has no answer, bad input or input that parsed but didn't work out
+#[derive(Debug, Clone, PartialEq)]
+pub enum SolveError {
+    Parse(ParseError),
+    Run(String),
+}
+
+impl SolveError {
+    /// Where in the input it went wrong, for parse errors that know
+    pub fn snippet(&self, input: &str) -> Option<String> {
+        match self {
+            SolveError::Parse(e) => e.snippet(input),
+            SolveError::Run(_) => None,
+        }
+    }
+
+    pub fn report(&self, input: &str) -> String {
+        match self {
+            SolveError::Parse(e) => e.report(input),
+            SolveError::Run(e) => format!("error: {}", e),
+        }
+    }
+}
+
+impl From<ParseError> for SolveError {
+    fn from(e: ParseError) -> SolveError {
+        SolveError::Parse(e)
+    }
+}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SolveError::Parse(e) => write!(f, "{}", e),
+            SolveError::Run(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for SolveError {}
+
+impl Source {
+    /// `-` is stdin, anything else is a path
+    pub fn from_arg(arg: &str) -> Source {
+        if arg == "-" {
+            Source::Stdin
+        } else {
+            Source::File(PathBuf::from(arg))
+        }
+    }
+
+    pub fn read(&self, examples: &[Example]) -> Result<String, InputError> {
+        match self {
+            Source::File(p) => fs::read_to_string(p).map_err(|e| InputError::Io(p.clone(), e)),
+            Source::Stdin => {
+                let mut s = String::new();
+                io::stdin()
+                    .read_to_string(&mut s)
+                    .map_err(InputError::Stdin)?;
+                Ok(s)
+            }
+            Source::Example(name) => example(examples, name).map(str::to_owned).ok_or_else(|| {
+                InputError::NoExample(name.clone(), examples.iter().map(|e| e.0).collect())
+            }),
+        }
+    }
+}
+
+/// Look up an example by name
+pub fn example(examples: &[Example], name: &str) -> Option<&'static str> {
+    examples
+        .iter()
+        .find(|(n, _)| *n == name)
+        .map(|(_, text)| *text)
+}
+
+/// For tests, where a missing example is a bug
+pub fn ex(examples: &[Example], name: &str) -> &'static str {
+    example(examples, name).unwrap_or_else(|| panic!("no example named '{}'", name))
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Source::File(p) => write!(f, "{}", p.display()),
+            Source::Stdin => write!(f, "stdin"),
+            Source::Example(name) => write!(f, "example '{}'", name),
+        }
+    }
+}
+
+const USAGE: &str = "Usage: dayN [PATH | - | --example <name>] [--format text|json|tsv]";
+
+fn usage(msg: &str) -> ! {
+    eprintln!("{}\n{}", msg, USAGE);
+    process::exit(2);
+}
+
+/// Pick the source and output format from a day binary's arguments: nothing for
+/// ./input, `-` for stdin, `--example <name>` or a path
+pub fn args_from_env() -> (Source, Format) {
+    let mut source = None;
+    let mut format = Format::Text;
+    let mut it = env::args().skip(1);
+
+    while let Some(arg) = it.next() {
+        match arg.as_str() {
+            "--example" => match it.next() {
+                Some(name) => source = Some(Source::Example(name)),
+                None => usage("--example needs a name"),
+            },
+            "--format" => match it.next().map(|f| f.parse()) {
+                Some(Ok(f)) => format = f,
+                Some(Err(e)) => usage(&e),
+                None => usage("--format needs a value"),
+            },
+            _ if source.is_some() => usage(&format!("unexpected '{}'", arg)),
+            _ => source = Some(Source::from_arg(&arg)),
+        }
+    }
+
+    let source = source.unwrap_or_else(|| Source::File(PathBuf::from("input")));
+    (source, format)
+}
+
+/// Meant for the day binaries, holds the input picked on the command line and prints
+/// the answers the way that was asked for
+pub struct Puzzle {
+    pub day: usize,
+    pub input: String,
+    pub format: Format,
+    header: Cell<bool>,
+}
+
+impl Puzzle {
+    /// Bails out with a message if the input can't be read
+    pub fn load(day: usize, examples: &[Example]) -> Puzzle {
+        let (source, format) = args_from_env();
+
+        let input = source.read(examples).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            process::exit(1);
+        });
+
+        Puzzle {
+            day,
+            input,
+            format,
+            header: Cell::new(false),
+        }
+    }
+
+    /// Print an answer, or bail out saying why there isn't one and pointing at the bit
+    /// of the input that didn't parse if that's why
+    pub fn answer<A: Answer, E: Into<SolveError>>(&self, part: usize, res: Result<A, E>) {
+        let rec = match res {
+            Ok(a) => Record::new(self.day, part, &a),
+            Err(e) => {
+                eprintln!("{}", e.into().report(&self.input));
+                process::exit(1);
+            }
+        };
+
+        match self.format {
+            Format::Text => println!("{}", rec.text()),
+            Format::Json => println!("{}", rec.json()),
+            Format::Tsv => {
+                if !self.header.replace(true) {
+                    println!("{}", TSV_HEADER);
+                }
+                println!("{}", rec.tsv());
+            }
+        }
+    }
+}
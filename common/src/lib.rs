@@ -0,0 +1,13 @@
+//! Bits and pieces that every day used to copy around
+
+pub mod fuzz;
+pub mod grid;
+pub mod input;
+pub mod output;
+pub mod parse;
+pub mod pos;
+
+pub use crate::grid::Grid;
+pub use crate::input::SolveError;
+pub use crate::parse::ParseError;
+pub use crate::pos::{m_dist, Manhattan, Pos, Pos3, Pos4};
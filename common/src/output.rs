@@ -0,0 +1,289 @@
+//! Answers as records that scripts can read, `{day, part, answer, extra}`
+
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Int(i64),
+    Str(String),
+}
+
+impl From<i32> for Value {
+    fn from(v: i32) -> Self {
+        Value::Int(v.into())
+    }
+}
+
+impl From<i64> for Value {
+    fn from(v: i64) -> Self {
+        Value::Int(v)
+    }
+}
+
+impl From<usize> for Value {
+    fn from(v: usize) -> Self {
+        Value::Int(v as i64)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(v: &str) -> Self {
+        Value::Str(v.to_owned())
+    }
+}
+
+impl From<String> for Value {
+    fn from(v: String) -> Self {
+        Value::Str(v)
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Int(v) => write!(f, "{}", v),
+            Value::Str(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+pub type Extra = Vec<(&'static str, Value)>;
+
+/// What the parts give back, the extra bits are whatever else is worth knowing about
+/// how the answer came out
+pub trait Answer: fmt::Display {
+    fn extra(&self) -> Extra {
+        Vec::new()
+    }
+}
+
+impl Answer for i32 {}
+impl Answer for i64 {}
+impl Answer for usize {}
+impl Answer for String {}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Text,
+    Json,
+    Tsv,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            "tsv" => Ok(Format::Tsv),
+            _ => Err(format!("unknown format '{}', try text, json or tsv", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    pub day: usize,
+    pub part: usize,
+    pub answer: String,
+    pub extra: Extra,
+}
+
+pub const TSV_HEADER: &str = "day\tpart\tanswer\textra";
+
+fn json_str(s: &str) -> String {
+    let mut out = String::from("\"");
+
+    for c in s.chars() {
+        match c {
+            '"' => out += "\\\"",
+            '\\' => out += "\\\\",
+            '\n' => out += "\\n",
+            '\r' => out += "\\r",
+            '\t' => out += "\\t",
+            c if (c as u32) < 0x20 => out += &format!("\\u{:04x}", c as u32),
+            c => out.push(c),
+        }
+    }
+
+    out.push('"');
+    out
+}
+
+// Tabs and line breaks would mess up the columns
+fn tsv_str(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+// And in extra the pairs would run together, \s is a semicolon and \e an equals sign
+fn tsv_extra(s: &str) -> String {
+    tsv_str(s).replace(';', "\\s").replace('=', "\\e")
+}
+
+impl Record {
+    pub fn new<A: Answer + ?Sized>(day: usize, part: usize, a: &A) -> Record {
+        Record {
+            day,
+            part,
+            answer: a.to_string(),
+            extra: a.extra(),
+        }
+    }
+
+    /// One object on a single line, the answer is always a string
+    pub fn json(&self) -> String {
+        let extra: Vec<String> = self
+            .extra
+            .iter()
+            .map(|(k, v)| match v {
+                Value::Int(i) => format!("{}:{}", json_str(k), i),
+                Value::Str(s) => format!("{}:{}", json_str(k), json_str(s)),
+            })
+            .collect();
+
+        format!(
+            "{{\"day\":{},\"part\":{},\"answer\":{},\"extra\":{{{}}}}}",
+            self.day,
+            self.part,
+            json_str(&self.answer),
+            extra.join(",")
+        )
+    }
+
+    /// Goes under `TSV_HEADER`, extra is key=value pairs split by semicolons. Backslashes
+    /// escape anything that would get in the way.
+    pub fn tsv(&self) -> String {
+        let extra: Vec<String> = self
+            .extra
+            .iter()
+            .map(|(k, v)| format!("{}={}", tsv_extra(k), tsv_extra(&v.to_string())))
+            .collect();
+
+        format!(
+            "{}\t{}\t{}\t{}",
+            self.day,
+            self.part,
+            tsv_str(&self.answer),
+            extra.join(";")
+        )
+    }
+
+    /// "Part 1: 42", multi-line answers start on their own line
+    pub fn text(&self) -> String {
+        if self.answer.contains('\n') {
+            format!("Part {}:\n{}", self.part, self.answer)
+        } else {
+            format!("Part {}: {}", self.part, self.answer)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Square;
+
+    impl fmt::Display for Square {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "90,269,16")
+        }
+    }
+
+    impl Answer for Square {
+        fn extra(&self) -> Extra {
+            vec![
+                ("x", 90.into()),
+                ("size", 16.into()),
+                ("note", "a \"b\"\tc".into()),
+            ]
+        }
+    }
+
+    #[test]
+    fn formats() {
+        let r = Record::new(11, 2, &Square);
+
+        assert_eq!(
+            r.json(),
+            r#"{"day":11,"part":2,"answer":"90,269,16","extra":{"x":90,"size":16,"note":"a \"b\"\tc"}}"#
+        );
+        assert_eq!(r.tsv(), "11\t2\t90,269,16\tx=90;size=16;note=a \"b\"\\tc");
+        assert_eq!(r.text(), "Part 2: 90,269,16");
+
+        let r = Record::new(10, 1, &"#.\n.#".to_owned());
+        assert_eq!(
+            r.json(),
+            r##"{"day":10,"part":1,"answer":"#.\n.#","extra":{}}"##
+        );
+        assert_eq!(r.tsv(), "10\t1\t#.\\n.#\t");
+        assert_eq!(r.text(), "Part 1:\n#.\n.#");
+    }
+
+    struct Awkward;
+
+    impl fmt::Display for Awkward {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "a;b=c")
+        }
+    }
+
+    impl Answer for Awkward {
+        fn extra(&self) -> Extra {
+            vec![
+                ("k=v;", "x;y=z".into()),
+                ("\\s", "\\e\t=".into()),
+                ("n", (-3).into()),
+            ]
+        }
+    }
+
+    fn unescape(s: &str) -> String {
+        let mut out = String::new();
+        let mut chars = s.chars();
+
+        while let Some(c) = chars.next() {
+            if c != '\\' {
+                out.push(c);
+                continue;
+            }
+            out.push(match chars.next().unwrap() {
+                't' => '\t',
+                'n' => '\n',
+                'r' => '\r',
+                's' => ';',
+                'e' => '=',
+                c => c,
+            });
+        }
+        out
+    }
+
+    #[test]
+    fn tsv_round_trip() {
+        let line = Record::new(1, 2, &Awkward).tsv();
+        assert_eq!(
+            line,
+            "1\t2\ta;b=c\tk\\ev\\s=x\\sy\\ez;\\\\s=\\\\e\\t\\e;n=-3"
+        );
+
+        let cols: Vec<&str> = line.split('\t').collect();
+        assert_eq!(unescape(cols[2]), "a;b=c");
+
+        let extra: Vec<(String, String)> = (cols[3].split(';'))
+            .map(|kv| {
+                let (k, v) = kv.split_once('=').unwrap();
+                (unescape(k), unescape(v))
+            })
+            .collect();
+        let want: Vec<(String, String)> = (Awkward.extra().into_iter())
+            .map(|(k, v)| (k.to_owned(), v.to_string()))
+            .collect();
+        assert_eq!(extra, want);
+    }
+}
@@ -0,0 +1,400 @@
+use std::char::ParseCharError;
+use std::error::Error;
+use std::fmt;
+use std::num::ParseIntError;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ErrorKind {
+    Int(ParseIntError),
+    Char(ParseCharError),
+    /// Ran out of line while looking for this
+    Missing(&'static str),
+    /// Found the token but it isn't this
+    Expected(&'static str),
+    /// It all parses but doesn't make sense, like a cart running off its track
+    Invalid(String),
+}
+
+impl From<ParseIntError> for ErrorKind {
+    fn from(e: ParseIntError) -> Self {
+        ErrorKind::Int(e)
+    }
+}
+
+impl From<ParseCharError> for ErrorKind {
+    fn from(e: ParseCharError) -> Self {
+        ErrorKind::Char(e)
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ErrorKind::Int(e) => write!(f, "{}", e),
+            ErrorKind::Char(e) => write!(f, "{}", e),
+            ErrorKind::Missing(what) => write!(f, "missing {}", what),
+            ErrorKind::Expected(what) => write!(f, "expected {}", what),
+            ErrorKind::Invalid(why) => write!(f, "{}", why),
+        }
+    }
+}
+
+/// What went wrong and where, lines and columns start from 1
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub kind: ErrorKind,
+    pub line: Option<usize>,
+    pub col: usize,
+    pub token: String,
+}
+
+impl ParseError {
+    pub fn new<K: Into<ErrorKind>>(kind: K, col: usize, token: &str) -> ParseError {
+        ParseError {
+            kind: kind.into(),
+            line: None,
+            col,
+            token: token.to_owned(),
+        }
+    }
+
+    /// Nothing left on the line, points just past its end
+    pub fn missing(what: &'static str, line: &str) -> ParseError {
+        ParseError::new(ErrorKind::Missing(what), line.chars().count() + 1, "")
+    }
+
+    pub fn expected(what: &'static str, tok: Token) -> ParseError {
+        ParseError::new(ErrorKind::Expected(what), tok.col, tok.text)
+    }
+
+    /// Input with none of `what` in it at all
+    pub fn empty(what: &'static str) -> ParseError {
+        ParseError::missing(what, "").at_line(1)
+    }
+
+    /// Something wrong with the input as a whole rather than a spot in it, `at` puts it
+    /// somewhere anyway
+    pub fn invalid(why: impl Into<String>) -> ParseError {
+        ParseError::new(ErrorKind::Invalid(why.into()), 0, "")
+    }
+
+    /// Point at line `line`, column `col`
+    pub fn at(mut self, line: usize, col: usize) -> ParseError {
+        self.line = Some(line);
+        self.col = col;
+        self
+    }
+
+    /// Fill in the line number, keeps one that's already there
+    pub fn at_line(mut self, line: usize) -> ParseError {
+        self.line.get_or_insert(line);
+        self
+    }
+
+    /// The offending line of `input` with the token underlined
+    pub fn snippet(&self, input: &str) -> Option<String> {
+        let line_no = self.line?;
+        let text = input.lines().nth(line_no.checked_sub(1)?)?;
+        let gutter = line_no.to_string().len();
+        let width = self.token.chars().count().max(1);
+
+        Some(format!(
+            "{:g$} |\n{} | {}\n{:g$} | {:c$}{}",
+            "",
+            line_no,
+            text,
+            "",
+            "",
+            "^".repeat(width),
+            g = gutter,
+            c = self.col.saturating_sub(1),
+        ))
+    }
+
+    /// Multi-line view of the error pointing at the offending bit of `input`
+    pub fn report(&self, input: &str) -> String {
+        match self.snippet(input) {
+            Some(s) => format!("error: {}\n{}", self, s),
+            None => format!("error: {}", self),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.line, self.col) {
+            (Some(l), 0) => write!(f, "line {}: ", l)?,
+            (Some(l), c) => write!(f, "line {}, column {}: ", l, c)?,
+            (None, 0) => (),
+            (None, c) => write!(f, "column {}: ", c)?,
+        }
+        write!(f, "{}", self.kind)?;
+        if !self.token.is_empty() {
+            write!(f, " at '{}'", self.token)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for ParseError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match &self.kind {
+            ErrorKind::Int(e) => Some(e),
+            ErrorKind::Char(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// A piece of a line and the column it starts at
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Token<'a> {
+    pub col: usize,
+    pub text: &'a str,
+}
+
+impl<'a> Token<'a> {
+    pub fn parse<T>(&self) -> Result<T, ParseError>
+    where
+        T: FromStr,
+        T::Err: Into<ErrorKind>,
+    {
+        self.text
+            .parse()
+            .map_err(|e: T::Err| ParseError::new(e, self.col, self.text))
+    }
+
+    /// Make sure the token is exactly `s`
+    pub fn expect(self, s: &'static str) -> Result<Token<'a>, ParseError> {
+        if self.text == s {
+            Ok(self)
+        } else {
+            Err(ParseError::expected(s, self))
+        }
+    }
+}
+
+/// Splits a line into tokens while keeping track of the columns
+pub struct Tokens<'a> {
+    line: &'a str,
+    toks: Vec<Token<'a>>,
+    next: usize,
+}
+
+impl<'a> Tokens<'a> {
+    fn from_spans(line: &'a str, spans: Vec<(usize, usize)>) -> Tokens<'a> {
+        let toks = spans
+            .into_iter()
+            .map(|(start, end)| Token {
+                col: line[..start].chars().count() + 1,
+                text: &line[start..end],
+            })
+            .collect();
+
+        Tokens {
+            line,
+            toks,
+            next: 0,
+        }
+    }
+
+    /// Tokens made of chars matching `keep`, everything else separates them
+    pub fn split(line: &'a str, keep: impl Fn(char) -> bool) -> Tokens<'a> {
+        let mut spans = vec![];
+        let mut start = None;
+
+        for (i, c) in line.char_indices() {
+            match (keep(c), start) {
+                (true, None) => start = Some(i),
+                (false, Some(s)) => {
+                    spans.push((s, i));
+                    start = None;
+                }
+                _ => (),
+            }
+        }
+
+        if let Some(s) = start {
+            spans.push((s, line.len()));
+        }
+
+        Tokens::from_spans(line, spans)
+    }
+
+    /// Whitespace separated words
+    pub fn words(line: &'a str) -> Tokens<'a> {
+        Tokens::split(line, |c| !c.is_whitespace())
+    }
+
+    /// Integers with an optional sign, skips all the punctuation around them. A '-'
+    /// right after a digit is a separator so dates like 1518-11-01 come out in pieces
+    pub fn numbers(line: &'a str) -> Tokens<'a> {
+        let mut spans = vec![];
+        let mut start: Option<usize> = None;
+        let mut prev = None;
+
+        for (i, c) in line.char_indices() {
+            let digit = c.is_ascii_digit();
+
+            match start {
+                Some(_) if digit => (),
+                Some(s) => {
+                    // A lone sign isn't a number
+                    if line[s..i].chars().any(|c| c.is_ascii_digit()) {
+                        spans.push((s, i));
+                    }
+                    start = None;
+                }
+                None => (),
+            }
+
+            let after_digit = prev.is_some_and(|p: char| p.is_ascii_digit());
+            if start.is_none() && (digit || ((c == '-' || c == '+') && !after_digit)) {
+                start = Some(i);
+            }
+
+            prev = Some(c);
+        }
+
+        if let Some(s) = start {
+            if line[s..].chars().any(|c| c.is_ascii_digit()) {
+                spans.push((s, line.len()));
+            }
+        }
+
+        Tokens::from_spans(line, spans)
+    }
+
+    /// The next token, or an error saying `what` is missing
+    pub fn next_tok(&mut self, what: &'static str) -> Result<Token<'a>, ParseError> {
+        let tok = self
+            .toks
+            .get(self.next)
+            .copied()
+            .ok_or_else(|| ParseError::missing(what, self.line))?;
+        self.next += 1;
+        Ok(tok)
+    }
+
+    /// Parse the next token, `what` names it in the error if there isn't one
+    pub fn parse_next<T>(&mut self, what: &'static str) -> Result<T, ParseError>
+    where
+        T: FromStr,
+        T::Err: Into<ErrorKind>,
+    {
+        self.next_tok(what)?.parse()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.next >= self.toks.len()
+    }
+}
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        let tok = self.toks.get(self.next).copied();
+        self.next += 1;
+        tok
+    }
+}
+
+/// Run `f` over every line of the input, errors get their line number filled in
+pub fn map_lines<T, F>(input: &str, mut f: F) -> Result<Vec<T>, ParseError>
+where
+    F: FnMut(&str) -> Result<T, ParseError>,
+{
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, l)| f(l).map_err(|e| e.at_line(i + 1)))
+        .collect()
+}
+
+/// Parse every line of the input with `FromStr`
+pub fn parse_lines<T>(input: &str) -> Result<Vec<T>, ParseError>
+where
+    T: FromStr<Err = ParseError>,
+{
+    map_lines(input, str::parse)
+}
+
+/// Error for a character that doesn't belong at this spot of a map or a regex
+pub fn bad_char(what: &'static str, line: usize, col: usize, c: char) -> ParseError {
+    let mut buf = [0; 4];
+    ParseError::new(ErrorKind::Expected(what), col, c.encode_utf8(&mut buf)).at_line(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(t: Tokens<'_>) -> Vec<(usize, &str)> {
+        t.map(|t| (t.col, t.text)).collect()
+    }
+
+    #[test]
+    fn numbers() {
+        let t = Tokens::numbers("position=< 9,  -1> velocity=<-1,  0>");
+        assert_eq!(texts(t), vec![(12, "9"), (16, "-1"), (30, "-1"), (35, "0")]);
+
+        let t = Tokens::numbers("[1518-11-01 00:05] - Guard #10");
+        assert_eq!(
+            texts(t),
+            vec![
+                (2, "1518"),
+                (7, "11"),
+                (10, "01"),
+                (13, "00"),
+                (16, "05"),
+                (29, "10")
+            ]
+        );
+    }
+
+    #[test]
+    fn words() {
+        let t = Tokens::words("  #1 @ 1,3: 4x4");
+        assert_eq!(
+            texts(t),
+            vec![(3, "#1"), (6, "@"), (8, "1,3:"), (13, "4x4")]
+        );
+    }
+
+    #[test]
+    fn report() {
+        let input = "1, 2\n3, x4\n";
+        let err = parse_lines::<Pair>(input).unwrap_err();
+
+        assert_eq!(err.line, Some(2));
+        assert_eq!(err.col, 4);
+        assert_eq!(err.token, "x4");
+        assert_eq!(
+            err.report(input),
+            "error: line 2, column 4: invalid digit found in string at 'x4'\n  |\n2 | 3, x4\n  |    ^^"
+        );
+    }
+
+    #[test]
+    fn missing() {
+        let err = parse_lines::<Pair>("1, 2\n3").unwrap_err();
+        assert_eq!(err.kind, ErrorKind::Missing("y"));
+        assert_eq!((err.line, err.col), (Some(2), 2));
+    }
+
+    #[derive(Debug)]
+    struct Pair;
+
+    impl FromStr for Pair {
+        type Err = ParseError;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            let mut it = Tokens::split(s, |c| c != ',' && c != ' ');
+            let _: i32 = it.parse_next("x")?;
+            let _: i32 = it.parse_next("y")?;
+            Ok(Pair)
+        }
+    }
+}
@@ -0,0 +1,39 @@
+pub type Pos = (i32, i32);
+pub type Pos3 = (i32, i32, i32);
+pub type Pos4 = (i32, i32, i32, i32);
+
+/// Anything we can measure the taxicab distance between
+pub trait Manhattan {
+    fn m_dist(&self, other: &Self) -> i32;
+}
+
+impl Manhattan for Pos {
+    fn m_dist(&self, other: &Self) -> i32 {
+        (self.0 - other.0).abs() + (self.1 - other.1).abs()
+    }
+}
+
+impl Manhattan for Pos3 {
+    fn m_dist(&self, other: &Self) -> i32 {
+        (self.0 - other.0).abs() + (self.1 - other.1).abs() + (self.2 - other.2).abs()
+    }
+}
+
+impl Manhattan for Pos4 {
+    fn m_dist(&self, other: &Self) -> i32 {
+        (self.0 - other.0).abs()
+            + (self.1 - other.1).abs()
+            + (self.2 - other.2).abs()
+            + (self.3 - other.3).abs()
+    }
+}
+
+impl<T: Manhattan> Manhattan for &T {
+    fn m_dist(&self, other: &Self) -> i32 {
+        (*self).m_dist(*other)
+    }
+}
+
+pub fn m_dist<P: Manhattan>(a: P, b: P) -> i32 {
+    a.m_dist(&b)
+}
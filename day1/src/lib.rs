@@ -0,0 +1,76 @@
+use std::collections::HashSet;
+
+use common::input::Example;
+use common::parse::{map_lines, Tokens};
+use common::ParseError;
+
+pub const EXAMPLES: &[Example] = &[("example", include_str!("../examples/example.txt"))];
+
+fn freqs(input: &str) -> Result<Vec<i32>, ParseError> {
+    let vals = map_lines(input, |l| Tokens::words(l).parse_next("frequency change"))?;
+    if vals.is_empty() {
+        return Err(ParseError::empty("frequency change"));
+    }
+    Ok(vals)
+}
+
+pub fn part1(input: &str) -> Result<i32, ParseError> {
+    Ok(freqs(input)?.iter().sum())
+}
+
+pub fn part2(input: &str) -> Result<i32, ParseError> {
+    let vals = freqs(input)?;
+
+    // Each pass shifts every frequency by the total, so one can only come back around
+    // within as many passes as it takes the total to cover the spread of a pass
+    let sums: Vec<i64> = vals
+        .iter()
+        .scan(0, |acc, &x| {
+            *acc += x as i64;
+            Some(*acc)
+        })
+        .collect();
+    let total = sums[sums.len() - 1];
+    let spread = sums.iter().max().unwrap().max(&0) - sums.iter().min().unwrap().min(&0);
+    let passes = if total == 0 {
+        1
+    } else {
+        (spread / total.abs() + 2) as usize
+    };
+
+    let mut sum_iter = vals
+        .iter()
+        .cycle()
+        .take(passes * vals.len())
+        .scan(0, |acc, x| {
+            *acc += x;
+            Some(*acc)
+        });
+    let mut hs = HashSet::new();
+    hs.insert(0);
+    sum_iter
+        .find(|s| !hs.insert(*s))
+        .ok_or_else(|| ParseError::invalid("the frequency never repeats"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::input::ex;
+
+    #[test]
+    fn examples() {
+        assert_eq!(part1(ex(EXAMPLES, "example")).unwrap(), 3);
+        assert_eq!(part2(ex(EXAMPLES, "example")).unwrap(), 2);
+    }
+
+    #[test]
+    fn bad_input() {
+        let e = part1("").unwrap_err();
+        assert_eq!(e.to_string(), "line 1, column 1: missing frequency change");
+        assert_eq!(
+            part2("+1\n+2\n").unwrap_err().to_string(),
+            "the frequency never repeats"
+        );
+    }
+}
@@ -0,0 +1,186 @@
+use std::fmt;
+use std::str::FromStr;
+
+use common::input::Example;
+use common::parse::{parse_lines, Tokens};
+use common::{Grid, ParseError};
+
+pub const EXAMPLES: &[Example] = &[("example", include_str!("../examples/example.txt"))];
+
+#[derive(Debug, PartialEq)]
+struct Point {
+    x: i32,
+    y: i32,
+    vx: i32,
+    vy: i32,
+}
+
+impl Point {
+    fn mv(&mut self) -> Option<()> {
+        self.x = self.x.checked_add(self.vx)?;
+        self.y = self.y.checked_add(self.vy)?;
+        Some(())
+    }
+
+    fn mv_back(&mut self) {
+        self.x -= self.vx;
+        self.y -= self.vy;
+    }
+}
+
+impl fmt::Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "position=<{:>6}, {:>6}> velocity=<{:>2}, {:>2}>",
+            self.x, self.y, self.vx, self.vy
+        )
+    }
+}
+
+impl FromStr for Point {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // position=< 9,  1> velocity=< 0,  2>
+        let mut it = Tokens::numbers(s);
+        let x = it.parse_next("x")?;
+        let y = it.parse_next("y")?;
+        let vx = it.parse_next("x velocity")?;
+        let vy = it.parse_next("y velocity")?;
+
+        Ok(Point { x, y, vx, vy })
+    }
+}
+
+fn corners(points: &[Point]) -> ((i32, i32), (i32, i32)) {
+    let min_corner = (
+        points.iter().map(|p| p.x).min().unwrap(),
+        points.iter().map(|p| p.y).min().unwrap(),
+    );
+
+    let max_corner = (
+        points.iter().map(|p| p.x).max().unwrap(),
+        points.iter().map(|p| p.y).max().unwrap(),
+    );
+
+    (min_corner, max_corner)
+}
+
+fn height(points: &[Point]) -> i32 {
+    let ((_, min_y), (_, max_y)) = corners(points);
+    max_y - min_y
+}
+
+// Move the points until they are packed the tightest, that's when the message shows up
+fn align(input: &str) -> Result<(Vec<Point>, usize), ParseError> {
+    let mut points: Vec<Point> = parse_lines(input)?;
+    if points.is_empty() {
+        return Err(ParseError::empty("point"));
+    }
+    // Otherwise they get further apart sooner or later
+    if points.iter().all(|p| p.vy == points[0].vy) {
+        return Err(ParseError::invalid("the points never come together"));
+    }
+
+    for s in 0.. {
+        let h = height(&points);
+
+        for point in &mut points {
+            point
+                .mv()
+                .ok_or_else(|| ParseError::invalid("the points fly off the edge"))?;
+        }
+
+        if height(&points) > h {
+            for point in &mut points {
+                point.mv_back();
+            }
+            return Ok((points, s));
+        }
+    }
+
+    unreachable!()
+}
+
+pub fn part1(input: &str) -> Result<String, ParseError> {
+    let (points, _) = align(input)?;
+    let (min_corner, max_corner) = corners(&points);
+    let mut canvas = Grid::from_corners(min_corner, max_corner, '.');
+
+    for p in &points {
+        canvas[(p.x, p.y)] = '#';
+    }
+
+    Ok(canvas.render(|_, &c| c))
+}
+
+pub fn part2(input: &str) -> Result<usize, ParseError> {
+    Ok(align(input)?.1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::fuzz::{check, fuzz, fuzz_examples, small};
+    use common::input::ex;
+
+    #[test]
+    fn examples() {
+        let hi = "\
+#...#..###
+#...#...#.
+#...#...#.
+#####...#.
+#...#...#.
+#...#...#.
+#...#...#.
+#...#..###";
+
+        assert_eq!(part1(ex(EXAMPLES, "example")).unwrap(), hi);
+        assert_eq!(part2(ex(EXAMPLES, "example")).unwrap(), 3);
+    }
+
+    #[test]
+    fn bad_input() {
+        let e = part1("").unwrap_err();
+        assert_eq!(e.to_string(), "line 1, column 1: missing point");
+
+        let input = "position=< 1,  2> velocity=< 3, -1>\nposition=< 3,  4> velocity=<-1, -1>\n";
+        let e = part2(input).unwrap_err();
+        assert_eq!(e.to_string(), "the points never come together");
+
+        let input =
+            "position=< 0, 2147483600> velocity=< 0, 1>\nposition=< 0, 0> velocity=< 0, 2>\n";
+        let e = part2(input).unwrap_err();
+        assert_eq!(e.to_string(), "the points fly off the edge");
+    }
+
+    #[test]
+    fn round_trip() {
+        check(
+            1000,
+            |r| Point {
+                x: r.i32(),
+                y: r.i32(),
+                vx: r.i32(),
+                vy: r.i32(),
+            },
+            |p| assert_eq!(&p.to_string().parse::<Point>().unwrap(), p),
+        );
+    }
+
+    #[test]
+    fn fuzz_point() {
+        let seeds: Vec<&str> = ex(EXAMPLES, "example").lines().collect();
+        fuzz(&seeds, 5000, |s| {
+            let _ = s.parse::<Point>();
+        });
+
+        fuzz_examples(EXAMPLES, |s| {
+            if small(s, 100) {
+                let _ = part1(s);
+                let _ = part2(s);
+            }
+        });
+    }
+}
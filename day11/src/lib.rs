@@ -0,0 +1,167 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use common::input::Example;
+use common::output::{Answer, Extra};
+use common::parse::Tokens;
+use common::{Grid, ParseError};
+
+pub const EXAMPLES: &[Example] = &[
+    ("example1", include_str!("../examples/example1.txt")),
+    ("example2", include_str!("../examples/example2.txt")),
+];
+
+struct Cell(i64, i64);
+
+impl Cell {
+    fn rack_id(&self) -> i64 {
+        self.0 + 10
+    }
+
+    fn power_level(&self, serial: i64) -> i64 {
+        let mut pwr = self.rack_id() * self.1;
+        pwr += serial;
+        pwr *= self.rack_id();
+        pwr = (pwr / 100) % 10;
+        pwr - 5
+    }
+}
+
+const AREA: usize = 300;
+
+// Cells are numbered from 1 so the grid starts there
+fn total_power_level(grid: &Grid<i64>, x: usize, y: usize, square: usize) -> i64 {
+    let mut sum = 0;
+
+    for l in grid.rows().skip(y - 1).take(square) {
+        sum += l[x - 1..].iter().take(square).sum::<i64>();
+    }
+
+    sum
+}
+
+/// Top-left corner of the best 3x3 square
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Corner {
+    pub x: usize,
+    pub y: usize,
+    pub power: i64,
+}
+
+impl fmt::Display for Corner {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{},{}", self.x, self.y)
+    }
+}
+
+impl Answer for Corner {
+    fn extra(&self) -> Extra {
+        vec![
+            ("x", self.x.into()),
+            ("y", self.y.into()),
+            ("power", self.power.into()),
+        ]
+    }
+}
+
+/// Top-left corner and size of the best square of any size
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Square {
+    pub x: usize,
+    pub y: usize,
+    pub size: usize,
+    pub power: i64,
+}
+
+impl fmt::Display for Square {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{},{},{}", self.x, self.y, self.size)
+    }
+}
+
+impl Answer for Square {
+    fn extra(&self) -> Extra {
+        vec![
+            ("x", self.x.into()),
+            ("y", self.y.into()),
+            ("size", self.size.into()),
+            ("power", self.power.into()),
+        ]
+    }
+}
+
+fn grid(serial: i64) -> Grid<i64> {
+    let max = AREA as i32;
+    let mut grid = Grid::from_corners((1, 1), (max, max), 0);
+
+    for ((x, y), c) in grid.iter_mut() {
+        *c = Cell(x.into(), y.into()).power_level(serial);
+    }
+
+    grid
+}
+
+fn serial(input: &str) -> Result<i64, ParseError> {
+    let line = input.lines().next().unwrap_or("");
+    Tokens::words(line)
+        .parse_next("grid serial number")
+        .map_err(|e| e.at_line(1))
+}
+
+pub fn part1(input: &str) -> Result<Corner, ParseError> {
+    let grid = grid(serial(input)?);
+    let mut dat = HashMap::new();
+
+    for y in 1..=(AREA - 3 + 1) {
+        for x in 1..=(AREA - 3 + 1) {
+            let lvl = total_power_level(&grid, x, y, 3);
+            dat.insert((x, y), lvl);
+        }
+    }
+
+    let (&(x, y), &power) = dat.iter().max_by_key(|kv| kv.1).unwrap();
+    Ok(Corner { x, y, power })
+}
+
+pub fn part2(input: &str) -> Result<Square, ParseError> {
+    let grid = grid(serial(input)?);
+    let mut dat = HashMap::new();
+
+    for sq in 1..300 {
+        for y in 1..=(AREA - sq + 1) {
+            for x in 1..=(AREA - sq + 1) {
+                let lvl = total_power_level(&grid, x, y, sq);
+                dat.insert((x, y, sq), lvl);
+            }
+        }
+    }
+
+    let (&(x, y, size), &power) = dat.iter().max_by_key(|kv| kv.1).unwrap();
+    Ok(Square { x, y, size, power })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::input::ex;
+
+    #[test]
+    fn part1_examples() {
+        let c = part1(ex(EXAMPLES, "example1")).unwrap();
+        assert_eq!((c.x, c.y, c.power), (33, 45, 29));
+
+        let c = part1(ex(EXAMPLES, "example2")).unwrap();
+        assert_eq!((c.x, c.y, c.power), (21, 61, 30));
+    }
+
+    // Brute force over every size, takes a while
+    #[test]
+    #[ignore]
+    fn part2_examples() {
+        let s = part2(ex(EXAMPLES, "example1")).unwrap();
+        assert_eq!((s.x, s.y, s.size, s.power), (90, 269, 16, 113));
+
+        let s = part2(ex(EXAMPLES, "example2")).unwrap();
+        assert_eq!((s.x, s.y, s.size, s.power), (232, 251, 12, 119));
+    }
+}
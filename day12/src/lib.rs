@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+
+use common::input::Example;
+use common::parse::{ErrorKind, Token, Tokens};
+use common::ParseError;
+
+pub const EXAMPLES: &[Example] = &[("example", include_str!("../examples/example.txt"))];
+
+fn pots(tok: Token) -> Result<String, ParseError> {
+    let bad = tok.text.char_indices().find(|&(_, c)| c != '#' && c != '.');
+
+    match bad {
+        Some((i, c)) => Err(ParseError::new(
+            ErrorKind::Expected("'#' or '.'"),
+            tok.col + tok.text[..i].chars().count(),
+            &c.to_string(),
+        )),
+        None => Ok(tok.text.to_owned()),
+    }
+}
+
+// ...## => #
+fn gen_rule(s: &str) -> Result<(String, char), ParseError> {
+    let mut it = Tokens::words(s);
+    let rule = pots(it.next_tok("rule")?)?;
+    it.next_tok("'=>'")?.expect("=>")?;
+    let out = pots(it.next_tok("plant")?)?;
+
+    Ok((rule, out.chars().next().unwrap()))
+}
+
+const WIDTH: usize = 5;
+
+struct Field {
+    eq: Option<i64>,
+    offset: i64,
+    state: String,
+    rules: HashMap<String, char>,
+}
+
+impl Field {
+    fn next(&mut self) {
+        let padded = format!("....{}....", self.state);
+        let tmp_vec: Vec<char> = padded.chars().collect(); // convert to Vec<char> for .windows() :P
+        let old_offset = self.offset;
+
+        let mut new_state: String = tmp_vec
+            .windows(WIDTH)
+            .map(|r| {
+                let s: String = r.iter().collect(); // convert to string for HashMap
+                                                    // The examples only list the rules that grow a plant
+                self.rules.get(&s).copied().unwrap_or('.')
+            })
+            .collect();
+
+        // Unless they've all died
+        if let Some(first) = new_state.find('#') {
+            self.offset += 2 - first as i64;
+        }
+        new_state = new_state.trim_matches('.').to_owned();
+
+        if new_state == self.state {
+            self.eq = Some(self.offset - old_offset);
+        }
+
+        self.state = new_state;
+    }
+
+    fn sum(&self) -> i64 {
+        self.state
+            .chars()
+            .enumerate()
+            .filter(|(_, c)| *c == '#')
+            .map(|(i, _)| (i as i64) - self.offset)
+            .sum()
+    }
+
+    fn is_eq(&self) -> bool {
+        self.eq.is_some()
+    }
+
+    fn yolo(&mut self, turns: i64) {
+        assert!(self.is_eq());
+        self.offset += self.eq.unwrap() * turns;
+    }
+}
+
+fn field(input: &str) -> Result<Field, ParseError> {
+    let mut lines = input.lines();
+    let first_line = lines.next().unwrap_or("");
+
+    // initial state: #..#.#..##......###...###
+    let state = {
+        let mut it = Tokens::words(first_line);
+        it.next_tok("'initial'")
+            .and_then(|t| t.expect("initial"))
+            .and_then(|_| it.next_tok("'state:'")?.expect("state:"))
+            .and_then(|_| pots(it.next_tok("initial state")?))
+            .map_err(|e| e.at_line(1))?
+    };
+
+    let rules = lines
+        .enumerate()
+        .skip(1)
+        .map(|(i, l)| gen_rule(l).map_err(|e| e.at_line(i + 2)))
+        .collect::<Result<HashMap<_, _>, _>>()?;
+
+    // Plants all the way out to infinity
+    if rules.get(".....") == Some(&'#') {
+        return Err(ParseError::invalid("plants can't grow out of nothing"));
+    }
+
+    Ok(Field {
+        state,
+        rules,
+        offset: 0,
+        eq: None,
+    })
+}
+
+pub fn part1(input: &str) -> Result<i64, ParseError> {
+    let mut field = field(input)?;
+
+    for _ in 1..=20 {
+        field.next();
+    }
+
+    Ok(field.sum())
+}
+
+/// Generations to wait for the plants to start moving along the same each time
+const SETTLE: i64 = 1000;
+
+pub fn part2(input: &str) -> Result<i64, ParseError> {
+    let limit = 50000000000_i64;
+    let mut field = field(input)?;
+    let mut turn = 0;
+
+    while !field.is_eq() {
+        if turn == SETTLE {
+            return Err(ParseError::invalid("the plants never settle down"));
+        }
+        field.next();
+        turn += 1;
+    }
+
+    field.yolo(limit - turn);
+
+    Ok(field.sum())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::fuzz::fuzz_examples;
+    use common::input::ex;
+
+    #[test]
+    fn examples() {
+        assert_eq!(part1(ex(EXAMPLES, "example")).unwrap(), 325);
+    }
+
+    #[test]
+    fn bad_input() {
+        let e = part1("initial state: #\n\n..... => #\n").unwrap_err();
+        assert_eq!(e.to_string(), "plants can't grow out of nothing");
+
+        // Every plant spreads to both sides and dies
+        let input = "initial state: #\n\n.#... => #\n...#. => #\n";
+        let e = part2(input).unwrap_err();
+        assert_eq!(e.to_string(), "the plants never settle down");
+
+        // Nothing left, which stays the same from then on
+        assert_eq!(part1("initial state: #.#\n\n").unwrap(), 0);
+        assert_eq!(part2("initial state: #.#\n\n").unwrap(), 0);
+    }
+
+    #[test]
+    fn fuzz_field() {
+        fuzz_examples(EXAMPLES, |s| {
+            if field(s).is_ok() {
+                let _ = part1(s);
+                let _ = part2(s);
+            }
+        });
+    }
+}
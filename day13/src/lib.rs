@@ -0,0 +1,369 @@
+use std::fmt;
+
+use common::input::Example;
+use common::output::{Answer, Extra};
+use common::{Grid, ParseError, Pos};
+
+pub const EXAMPLES: &[Example] = &[
+    ("part1", include_str!("../examples/part1.txt")),
+    ("part2", include_str!("../examples/part2.txt")),
+];
+
+#[derive(Debug, Clone, Copy)]
+enum C {
+    Beam,
+    Inter,
+    Line,
+    L,
+    R,
+}
+
+impl C {
+    fn from_char(c: char) -> Option<C> {
+        let out = match c {
+            '|' => C::Beam,
+            '^' => C::Beam,
+            'v' => C::Beam,
+            '+' => C::Inter,
+            '-' => C::Line,
+            '>' => C::Line,
+            '<' => C::Line,
+            '\\' => C::L,
+            '/' => C::R,
+            _ => return None,
+        };
+        Some(out)
+    }
+}
+
+/// None where there's no track
+type TrackMap = Grid<Option<C>>;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Turn {
+    Straight,
+    Left,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Dir {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Dir {
+    fn turn(self, t: Turn) -> Dir {
+        let left = |d| match d {
+            Dir::Up => Dir::Left,
+            Dir::Down => Dir::Right,
+            Dir::Left => Dir::Down,
+            Dir::Right => Dir::Up,
+        };
+
+        let right = |d| match d {
+            Dir::Up => Dir::Right,
+            Dir::Down => Dir::Left,
+            Dir::Left => Dir::Up,
+            Dir::Right => Dir::Down,
+        };
+
+        match t {
+            Turn::Straight => self,
+            Turn::Left => left(self),
+            Turn::Right => right(self),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct TurnGen {
+    turns: Vec<Turn>,
+    id: usize,
+}
+
+impl TurnGen {
+    pub fn new(t: &[Turn]) -> TurnGen {
+        TurnGen {
+            turns: t.to_vec(),
+            id: 0,
+        }
+    }
+}
+
+impl Iterator for TurnGen {
+    type Item = Turn;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let t = self.turns[self.id];
+        self.id = (self.id + 1) % self.turns.len();
+        Some(t)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Cart {
+    dead: bool,
+    pos: Pos,
+    dir: Dir,
+    turns: TurnGen,
+}
+
+const TURN_RULE: [Turn; 3] = [Turn::Left, Turn::Straight, Turn::Right];
+
+impl Cart {
+    fn from_char(pos: Pos, c: char) -> Option<Cart> {
+        let dir = match c {
+            '^' => Dir::Up,
+            'v' => Dir::Down,
+            '<' => Dir::Left,
+            '>' => Dir::Right,
+            _ => return None,
+        };
+
+        let turns = TurnGen::new(&TURN_RULE);
+
+        Some(Cart {
+            pos,
+            dir,
+            turns,
+            dead: false,
+        })
+    }
+
+    fn mv(&mut self, map: &TrackMap) -> Result<(), ParseError> {
+        let next_pos = match self.dir {
+            Dir::Up => (self.pos.0, self.pos.1 - 1),
+            Dir::Down => (self.pos.0, self.pos.1 + 1),
+            Dir::Left => (self.pos.0 - 1, self.pos.1),
+            Dir::Right => (self.pos.0 + 1, self.pos.1),
+        };
+
+        let track = match map.get(next_pos) {
+            Some(&Some(c)) => c,
+            _ => {
+                let (x, y) = self.pos;
+                return Err(
+                    ParseError::invalid("cart leaves the track").at(y as usize + 1, x as usize + 1)
+                );
+            }
+        };
+
+        let next_dir = match track {
+            C::L => match self.dir {
+                Dir::Up => Dir::Left,
+                Dir::Down => Dir::Right,
+                Dir::Left => Dir::Up,
+                Dir::Right => Dir::Down,
+            },
+            C::R => match self.dir {
+                Dir::Up => Dir::Right,
+                Dir::Left => Dir::Down,
+                Dir::Down => Dir::Left,
+                Dir::Right => Dir::Up,
+            },
+            C::Inter => {
+                let turn = self.turns.next().unwrap();
+                self.dir.turn(turn)
+            }
+            _ => self.dir,
+        };
+
+        self.dir = next_dir;
+        self.pos = next_pos;
+        Ok(())
+    }
+}
+
+/// Cart location, formatted the way the puzzle wants it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Loc(pub usize, pub usize);
+
+impl fmt::Display for Loc {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{},{}", self.0, self.1)
+    }
+}
+
+fn loc(p: Pos) -> Loc {
+    Loc(p.0 as usize, p.1 as usize)
+}
+
+impl Answer for Loc {
+    fn extra(&self) -> Extra {
+        vec![("x", self.0.into()), ("y", self.1.into())]
+    }
+}
+
+fn tracks(input: &str) -> Result<(TrackMap, Vec<Cart>), ParseError> {
+    let mut carts = vec![];
+
+    let map = Grid::parse(input, "track or cart", None, |p, c| {
+        if let Some(cart) = Cart::from_char(p, c) {
+            carts.push(cart);
+        }
+
+        match c {
+            ' ' => Some(None),
+            _ => C::from_char(c).map(Some),
+        }
+    })?;
+
+    if carts.is_empty() {
+        return Err(ParseError::empty("cart"));
+    }
+    Ok((map, carts))
+}
+
+/// Notices the carts going round and round the same way forever, using Brent's
+/// algorithm so it only has to keep one copy of them
+struct Loops {
+    saved: Vec<Cart>,
+    power: usize,
+    len: usize,
+}
+
+impl Loops {
+    fn new() -> Loops {
+        Loops {
+            saved: vec![],
+            power: 1,
+            len: 0,
+        }
+    }
+
+    fn check(&mut self, carts: &[Cart]) -> Result<(), ParseError> {
+        if self.saved == carts {
+            return Err(ParseError::invalid("the carts go round forever"));
+        }
+
+        self.len += 1;
+        if self.len == self.power {
+            self.saved = carts.to_vec();
+            self.power *= 2;
+            self.len = 0;
+        }
+        Ok(())
+    }
+}
+
+pub fn part1(input: &str) -> Result<Loc, ParseError> {
+    let (map, mut carts) = tracks(input)?;
+    if carts.len() < 2 {
+        return Err(ParseError::invalid("it takes two carts to crash"));
+    }
+    let mut loops = Loops::new();
+
+    loop {
+        carts.sort_by_key(|c| (c.pos.1, c.pos.0));
+        loops.check(&carts)?;
+
+        // Onwership RIP
+        //for (idx, cart) in carts.iter_mut().enumerate() {
+        //cart.mv(&map);
+
+        //let collision = carts.iter().any(|c| c.pos == cart.pos);
+        //}
+
+        for i in 0..carts.len() {
+            carts[i].mv(&map)?;
+
+            let collision = carts
+                .iter()
+                .filter(|c| !std::ptr::eq(*c, &carts[i]))
+                .any(|c| c.pos == carts[i].pos);
+            if collision {
+                return Ok(loc(carts[i].pos));
+            }
+        }
+    }
+}
+
+pub fn part2(input: &str) -> Result<Loc, ParseError> {
+    let (map, mut carts) = tracks(input)?;
+    // They crash in pairs
+    if carts.len() % 2 == 0 {
+        return Err(ParseError::invalid("an even number of carts all crash"));
+    }
+    let mut loops = Loops::new();
+
+    loop {
+        carts.sort_by_key(|c| (c.pos.1, c.pos.0));
+        loops.check(&carts)?;
+
+        // Onwership RIP
+        for i in 0..carts.len() {
+            if carts[i].dead {
+                continue;
+            }
+
+            carts[i].mv(&map)?;
+
+            let collided = carts
+                .iter()
+                .enumerate()
+                .filter(|(_, c)| !c.dead)
+                .filter(|(_, c)| !std::ptr::eq(*c, &carts[i]))
+                .find(|(_, c)| c.pos == carts[i].pos);
+
+            if let Some((other, _)) = collided {
+                carts[i].dead = true;
+                carts[other].dead = true;
+            }
+        }
+
+        let alive_cnt = carts.iter().filter(|c| !c.dead).count();
+
+        if alive_cnt == 1 {
+            let alive = carts.iter().find(|c| !c.dead).unwrap();
+            return Ok(loc(alive.pos));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::fuzz::fuzz_examples;
+    use common::input::ex;
+
+    #[test]
+    fn examples() {
+        assert_eq!(part1(ex(EXAMPLES, "part1")).unwrap(), Loc(7, 3));
+        assert_eq!(part2(ex(EXAMPLES, "part2")).unwrap(), Loc(6, 4));
+    }
+
+    #[test]
+    fn bad_input() {
+        let e = part1("").unwrap_err();
+        assert_eq!(e.to_string(), "line 1, column 1: missing cart");
+
+        let e = part2("-> \n").unwrap_err();
+        assert_eq!(e.to_string(), "line 1, column 2: cart leaves the track");
+        let e = part1("-> \n").unwrap_err();
+        assert_eq!(e.to_string(), "it takes two carts to crash");
+        let e = part2("->-<\n").unwrap_err();
+        assert_eq!(e.to_string(), "an even number of carts all crash");
+
+        // Chasing each other round a loop
+        let input = "\
+/->-\\
+|   |
+\\-<-/
+";
+        let e = part1(input).unwrap_err();
+        assert_eq!(e.to_string(), "the carts go round forever");
+    }
+
+    #[test]
+    fn fuzz_tracks() {
+        fuzz_examples(EXAMPLES, |s| {
+            if tracks(s).is_ok() {
+                let _ = part1(s);
+                let _ = part2(s);
+            }
+        });
+    }
+}
@@ -0,0 +1,127 @@
+use common::input::Example;
+use common::parse::{bad_char, Tokens};
+use common::ParseError;
+
+pub const EXAMPLES: &[Example] = &[
+    ("example1", include_str!("../examples/example1.txt")),
+    ("example2", include_str!("../examples/example2.txt")),
+    ("example3", include_str!("../examples/example3.txt")),
+    ("example4", include_str!("../examples/example4.txt")),
+    ("example5", include_str!("../examples/example5.txt")),
+    ("example6", include_str!("../examples/example6.txt")),
+    ("example7", include_str!("../examples/example7.txt")),
+    ("example8", include_str!("../examples/example8.txt")),
+];
+
+fn puzzle(input: &str) -> &str {
+    input.lines().next().unwrap_or("").trim()
+}
+
+pub fn part1(input: &str) -> Result<String, ParseError> {
+    let input: usize = Tokens::words(puzzle(input))
+        .parse_next("recipe count")
+        .map_err(|e| e.at_line(1))?;
+    let mut elf_1 = 0;
+    let mut elf_2 = 1;
+    let mut recipes = vec![3, 7];
+    loop {
+        let mut sum = recipes[elf_1] + recipes[elf_2];
+
+        let mut new_recipes = vec![];
+        loop {
+            new_recipes.push(sum % 10);
+            sum /= 10;
+            if sum == 0 {
+                break;
+            }
+        }
+
+        // Reverse the order
+        for n in new_recipes.into_iter().rev() {
+            recipes.push(n);
+        }
+
+        elf_1 = (elf_1 + recipes[elf_1] + 1) % recipes.len();
+        elf_2 = (elf_2 + recipes[elf_2] + 1) % recipes.len();
+
+        if recipes.len() >= input + 10 {
+            break;
+        }
+    }
+
+    Ok(recipes[input..input + 10]
+        .iter()
+        .map(|r| r.to_string())
+        .collect())
+}
+
+pub fn part2(input: &str) -> Result<usize, ParseError> {
+    // The digits are the sequence to look for, leading zeros included
+    let line = input.lines().next().unwrap_or("");
+    let offset = line.len() - line.trim_start().len();
+    let input = puzzle(line)
+        .chars()
+        .enumerate()
+        .map(|(i, c)| match c.to_digit(10) {
+            Some(d) => Ok(d as u8),
+            None => Err(bad_char("digit", 1, offset + i + 1, c)),
+        })
+        .collect::<Result<Vec<u8>, _>>()?;
+    let mut elf_1 = 0;
+    let mut elf_2 = 1;
+    let mut recipes = vec![3_u8, 7];
+
+    loop {
+        let mut sum = recipes[elf_1] + recipes[elf_2];
+
+        let mut new_recipes = vec![];
+        loop {
+            new_recipes.push(sum % 10);
+            sum /= 10;
+            if sum == 0 {
+                break;
+            }
+        }
+
+        // Reverse the order
+        for n in new_recipes.into_iter().rev() {
+            recipes.push(n);
+        }
+
+        elf_1 = (elf_1 + (recipes[elf_1] as usize) + 1) % recipes.len();
+        elf_2 = (elf_2 + (recipes[elf_2] as usize) + 1) % recipes.len();
+
+        if recipes.ends_with(&input) {
+            return Ok(recipes.len() - input.len());
+        }
+
+        // Pop last one and try again
+        let tmp = recipes.pop().unwrap();
+
+        if recipes.ends_with(&input) {
+            return Ok(recipes.len() - input.len());
+        }
+        recipes.push(tmp);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::input::ex;
+
+    #[test]
+    fn examples() {
+        let scores = ["5158916779", "0124515891", "9251071085", "5941429882"];
+        for (i, score) in scores.iter().enumerate() {
+            let name = format!("example{}", i + 1);
+            assert_eq!(part1(ex(EXAMPLES, &name)).unwrap(), *score, "{}", name);
+        }
+
+        let counts = [9, 5, 18, 2018];
+        for (i, &count) in counts.iter().enumerate() {
+            let name = format!("example{}", i + 5);
+            assert_eq!(part2(ex(EXAMPLES, &name)).unwrap(), count, "{}", name);
+        }
+    }
+}
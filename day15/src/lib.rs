@@ -0,0 +1,414 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt;
+
+use common::input::Example;
+use common::output::{Answer, Extra};
+use common::{Grid, ParseError, Pos};
+
+pub const EXAMPLES: &[Example] = &[
+    ("example1", include_str!("../examples/example1.txt")),
+    ("example2", include_str!("../examples/example2.txt")),
+];
+
+type TileMap = Grid<Tile>;
+type ActorMap = HashMap<Pos, Actor>;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Tile {
+    Floor,
+    Wall,
+}
+
+impl Tile {
+    fn from_char(c: char) -> Option<Tile> {
+        match c {
+            '#' => Some(Tile::Wall),
+            '.' | 'G' | 'E' => Some(Tile::Floor),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Role {
+    Elf,
+    Goblin,
+}
+
+#[derive(Debug, Clone)]
+struct Actor {
+    pos: Pos,
+    ap: i32,
+    hp: i32,
+    role: Role,
+}
+
+/// Get moveable spaces for a given position
+fn spaces(map: &TileMap, actors: &ActorMap, p: Pos) -> Vec<Pos> {
+    map.neighbours4(p)
+        .filter(|&s| map[s] != Tile::Wall) // No walls
+        .filter(|s| actors.get(s).is_none()) // No other actors
+        .collect()
+}
+
+/// Get all reachable locations with their distances
+fn distance_map(
+    map: &TileMap,
+    actors: &ActorMap,
+    p: Pos,
+    stop_point: Option<Pos>,
+) -> HashMap<Pos, usize> {
+    let mut dmap = HashMap::new();
+
+    dmap.insert(p, 0);
+    let mut last = vec![p]; // vec is faster than hashset for the input size
+
+    for dist in 0.. {
+        let mut cur = vec![];
+        'dmap: for p in &last {
+            for s in spaces(map, actors, *p) {
+                if !dmap.contains_key(&s) && !cur.contains(&s) {
+                    cur.push(s);
+                }
+                if Some(s) == stop_point {
+                    break 'dmap;
+                }
+            }
+        }
+
+        if cur.is_empty() {
+            break;
+        }
+
+        for t in &cur {
+            dmap.insert(*t, dist + 1);
+            if Some(*t) == stop_point {
+                return dmap;
+            }
+        }
+        last = cur;
+    }
+
+    dmap
+}
+
+/// Finds the min distance by calculating everything, RIP A*
+fn disance_between(map: &TileMap, actors: &ActorMap, a: Pos, b: Pos) -> usize {
+    distance_map(map, actors, a, Some(b))[&b]
+}
+
+impl Actor {
+    fn from_char(pos: Pos, c: char) -> Option<Actor> {
+        let role = match c {
+            'E' => Role::Elf,
+            'G' => Role::Goblin,
+            _ => return None,
+        };
+        Some(Actor {
+            role,
+            pos,
+            hp: 200,
+            ap: 3,
+        })
+    }
+
+    /// Get target enemy pos if in range
+    fn enemy(&self, actors: &ActorMap) -> Option<Pos> {
+        let neighbors = [(0, 1), (0, -1), (1, 0), (-1, 0)];
+        let p = self.pos;
+
+        neighbors
+            .iter()
+            .map(|&s| (s.0 + p.0, s.1 + p.1))
+            .filter_map(|s| actors.get(&s))
+            .filter(|a| a.role != self.role)
+            .min_by(|a, b| {
+                let hp_cmp = a.hp.cmp(&b.hp);
+                if let Ordering::Equal = hp_cmp {
+                    ((a.pos).1, (a.pos).0).cmp(&((b.pos).1, (b.pos).0)) // Thanks Obama
+                } else {
+                    hp_cmp
+                }
+            })
+            .map(|a| a.pos)
+    }
+
+    /// One turn
+    fn mv(&mut self, map: &TileMap, actors: &mut ActorMap) {
+        // Enemy in target
+        if let Some(enemy_pos) = self.enemy(actors) {
+            let enemy = actors.get_mut(&enemy_pos).unwrap();
+            enemy.hp -= self.ap;
+            if enemy.hp <= 0 {
+                actors.remove(&enemy_pos);
+            }
+            return;
+        }
+
+        let in_range = actors
+            .values()
+            .filter(|a| a.role != self.role)
+            .flat_map(|a| spaces(map, actors, a.pos));
+
+        let dmap = distance_map(map, actors, self.pos, None);
+
+        let reachables = in_range
+            .filter(|p| dmap.contains_key(p))
+            .map(|p| (p, dmap[&p]));
+
+        let target_dest = reachables.min_by(|a, b| {
+            let dist_cmp = a.1.cmp(&b.1);
+            if let Ordering::Equal = dist_cmp {
+                ((a.0).1, (a.0).0).cmp(&((b.0).1, (b.0).0)) // Thanks Obama
+            } else {
+                dist_cmp
+            }
+        });
+
+        let next_pos = if let Some((p, _)) = target_dest {
+            spaces(map, actors, self.pos)
+                .iter()
+                .map(|s| (s, disance_between(map, actors, *s, p)))
+                .min_by(|a, b| {
+                    let dist_cmp = a.1.cmp(&b.1);
+                    if let Ordering::Equal = dist_cmp {
+                        ((a.0).1, (a.0).0).cmp(&((b.0).1, (b.0).0)) // Thanks Obama
+                    } else {
+                        dist_cmp
+                    }
+                })
+                .map(|a| *a.0)
+        } else {
+            None
+        };
+
+        self.pos = next_pos.unwrap_or(self.pos);
+
+        // Check for enemies again
+        if let Some(enemy_pos) = self.enemy(actors) {
+            let enemy = actors.get_mut(&enemy_pos).unwrap();
+            enemy.hp -= self.ap;
+            if enemy.hp <= 0 {
+                actors.remove(&enemy_pos);
+            }
+        }
+    }
+}
+
+#[allow(dead_code)]
+fn draw_map(map: &TileMap, actors: &ActorMap) {
+    let text = map.render(|p, tile| match (actors.get(&p), tile) {
+        (Some(a), _) if a.role == Role::Goblin => 'G',
+        (Some(_), _) => 'E',
+        (None, Tile::Wall) => '#',
+        (None, Tile::Floor) => '.',
+    });
+    println!("{}", text);
+}
+
+/// How a fight ended
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Battle {
+    pub winner: Role,
+    pub rounds: i32,
+    pub hp: i32,
+    pub ap: i32,
+}
+
+impl Battle {
+    pub fn outcome(&self) -> i32 {
+        self.rounds * self.hp
+    }
+}
+
+impl fmt::Display for Battle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.outcome())
+    }
+}
+
+impl Answer for Battle {
+    fn extra(&self) -> Extra {
+        let winner = match self.winner {
+            Role::Elf => "Elves",
+            Role::Goblin => "Goblins",
+        };
+
+        vec![
+            ("winner", winner.into()),
+            ("rounds", self.rounds.into()),
+            ("hp", self.hp.into()),
+            ("elf_ap", self.ap.into()),
+        ]
+    }
+}
+
+fn cave(input: &str) -> Result<(TileMap, ActorMap), ParseError> {
+    let mut actors = HashMap::new();
+
+    let map = Grid::parse(input, "'#', '.', 'G' or 'E'", Tile::Wall, |p, c| {
+        if let Some(actor) = Actor::from_char(p, c) {
+            actors.insert(p, actor);
+        }
+        Tile::from_char(c)
+    })?;
+
+    if actors.is_empty() {
+        return Err(ParseError::empty("elf or goblin"));
+    }
+    Ok((map, actors))
+}
+
+/// Where everyone is and how they're doing, sorted
+fn state(actors: &ActorMap) -> Vec<(Pos, i32)> {
+    let mut state: Vec<_> = actors.values().map(|a| (a.pos, a.hp)).collect();
+    state.sort_unstable();
+    state
+}
+
+/// Fight until one side is gone, gives up early if an elf dies and `no_losses` is set
+fn fight(
+    map: &TileMap,
+    mut actors: ActorMap,
+    ap: i32,
+    no_losses: bool,
+) -> Result<Option<Battle>, ParseError> {
+    for e in actors.values_mut().filter(|a| a.role == Role::Elf) {
+        e.ap = ap;
+    }
+
+    let elves = actors.values().filter(|a| a.role == Role::Elf).count();
+
+    //draw_map(&map, &actors);
+    //println!("");
+
+    for round in 0.. {
+        let before = state(&actors);
+        let mut turns = actors.keys().cloned().collect::<Vec<_>>();
+        turns.sort_by_key(|c| (c.1, c.0));
+
+        let mut ended = false;
+
+        for t in turns {
+            // Will get None if died mid turn so check it
+            if let Some(mut act) = actors.remove(&t) {
+                // Nobody left to hit, combat ends and this round doesn't count
+                ended = actors.values().all(|a| a.role == act.role);
+                if ended {
+                    actors.insert(act.pos, act);
+                    break;
+                }
+
+                act.mv(map, &mut actors);
+                actors.insert(act.pos, act);
+            }
+        }
+        //println!("{}", round);
+        //draw_map(&map, &actors);
+        //println!("");
+
+        let cur_elves = actors.values().filter(|a| a.role == Role::Elf).count();
+        if no_losses && cur_elves < elves {
+            return Ok(None);
+        }
+
+        if ended {
+            let winner = actors.values().next().unwrap().role;
+            let hp: i32 = actors.values().map(|a| a.hp).sum();
+            return Ok(Some(Battle {
+                winner,
+                rounds: round,
+                hp,
+                ap,
+            }));
+        }
+
+        // Nobody moved or got hit, so nobody ever will
+        if state(&actors) == before {
+            return Err(ParseError::invalid(
+                "the elves and goblins can't get to each other",
+            ));
+        }
+    }
+
+    unreachable!()
+}
+
+pub fn part1(input: &str) -> Result<Battle, ParseError> {
+    let (map, actors) = cave(input)?;
+    Ok(fight(&map, actors, 3, false)?.unwrap())
+}
+
+pub fn part2(input: &str) -> Result<Battle, ParseError> {
+    let (map, actors) = cave(input)?;
+
+    // Maybe we should do binary search but whatevz, got the star already. Past 200 an
+    // elf kills a goblin with every hit so more doesn't help.
+    for ap in 4..=200 {
+        if let Some(b) = fight(&map, actors.clone(), ap, true)? {
+            return Ok(b);
+        }
+    }
+
+    Err(ParseError::invalid("an elf dies however hard they hit"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::fuzz::fuzz_examples;
+    use common::input::ex;
+
+    #[test]
+    fn examples() {
+        let b = part1(ex(EXAMPLES, "example1")).unwrap();
+        assert_eq!((b.winner, b.rounds, b.hp), (Role::Goblin, 47, 590));
+        assert_eq!(b.outcome(), 27730);
+
+        let b = part2(ex(EXAMPLES, "example1")).unwrap();
+        assert_eq!((b.winner, b.ap, b.outcome()), (Role::Elf, 15, 4988));
+
+        assert_eq!(part1(ex(EXAMPLES, "example2")).unwrap().outcome(), 39514);
+
+        let b = part2(ex(EXAMPLES, "example2")).unwrap();
+        assert_eq!((b.ap, b.outcome()), (4, 31284));
+    }
+
+    #[test]
+    fn bad_input() {
+        let e = part1("").unwrap_err();
+        assert_eq!(e.to_string(), "line 1, column 1: missing elf or goblin");
+
+        let e = part1("#######\n#E.#.G#\n#######\n").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "the elves and goblins can't get to each other"
+        );
+
+        // Hemmed in by more goblins than it can fight off
+        let input = "\
+#########
+#GGGGGGG#
+#GGGGGGG#
+#GGGGGGG#
+#GGGEGGG#
+#GGGGGGG#
+#GGGGGGG#
+#GGGGGGG#
+#########
+";
+        let e = part2(input).unwrap_err();
+        assert_eq!(e.to_string(), "an elf dies however hard they hit");
+    }
+
+    #[test]
+    fn fuzz_cave() {
+        fuzz_examples(EXAMPLES, |s| {
+            if cave(s).is_ok() {
+                let _ = part1(s);
+                let _ = part2(s);
+            }
+        });
+    }
+}
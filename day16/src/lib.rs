@@ -0,0 +1,137 @@
+use std::fmt;
+
+use common::input::Example;
+use common::{ParseError, SolveError};
+use elfcode::infer::{solve, Solution};
+use elfcode::manual::Manual;
+use elfcode::vm::Fault;
+use elfcode::Vm;
+
+pub const EXAMPLES: &[Example] = &[("example", include_str!("../examples/example.txt"))];
+
+/// Why part 2 couldn't get an answer out of the manual
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    Parse(ParseError),
+    /// The samples don't settle on one mapping
+    Mapping(Solution),
+    /// The program uses a number none of the samples do
+    NoSamples(i32),
+    Fault(Fault),
+}
+
+impl From<ParseError> for Error {
+    fn from(e: ParseError) -> Error {
+        Error::Parse(e)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Parse(e) => write!(f, "{}", e),
+            Error::Mapping(s) => write!(f, "{}", s),
+            Error::NoSamples(op) => write!(f, "no samples for opcode {}", op),
+            Error::Fault(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<Error> for SolveError {
+    fn from(e: Error) -> SolveError {
+        match e {
+            Error::Parse(e) => SolveError::Parse(e),
+            e => SolveError::Run(e.to_string()),
+        }
+    }
+}
+
+pub fn part1(input: &str) -> Result<usize, ParseError> {
+    let manual: Manual = input.parse()?;
+
+    Ok(manual
+        .samples
+        .iter()
+        .filter(|s| s.candidates().len() >= 3)
+        .count())
+}
+
+pub fn part2(input: &str) -> Result<i32, Error> {
+    let manual: Manual = input.parse()?;
+
+    let mapping = match solve(&manual.samples, 5) {
+        Solution::Unique(m) => m,
+        s => return Err(Error::Mapping(s)),
+    };
+    let program = manual.decode(&mapping).map_err(Error::NoSamples)?;
+
+    let mut vm = Vm::new(program, [0; 4]);
+    vm.run().map_err(Error::Fault)?;
+
+    Ok(vm.reg[0])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::fuzz::fuzz_examples;
+    use common::input::ex;
+
+    #[test]
+    fn examples() {
+        assert_eq!(part1(ex(EXAMPLES, "example")).unwrap(), 1);
+    }
+
+    #[test]
+    fn errors() {
+        // One sample fits three opcodes
+        match part2(ex(EXAMPLES, "example")) {
+            Err(Error::Mapping(Solution::Ambiguous { mappings, more })) => {
+                assert_eq!((mappings.len(), more), (3, false))
+            }
+            r => panic!("{:?}", r),
+        }
+
+        // 4 can only be addi, but there's nothing on 9
+        let input = "\
+Before: [3, 0, 0, 0]
+4 0 7 1
+After:  [3, 10, 0, 0]
+
+
+
+4 0 1 0
+9 1 2 3
+";
+        assert_eq!(part2(input), Err(Error::NoSamples(9)));
+        assert_eq!(
+            SolveError::from(part2(input).unwrap_err()),
+            SolveError::Run("no samples for opcode 9".to_owned())
+        );
+
+        // Both samples are opcode 4, but one only fits addi and the other only muli
+        let input = "\
+Before: [3, 0, 0, 0]
+4 0 7 1
+After:  [3, 10, 0, 0]
+
+Before: [3, 0, 0, 0]
+4 0 7 1
+After:  [3, 21, 0, 0]
+";
+        match part2(input) {
+            Err(Error::Mapping(Solution::Unsat(bad))) => assert_eq!(bad, vec![0, 1]),
+            r => panic!("{:?}", r),
+        }
+    }
+
+    #[test]
+    fn fuzz_parse() {
+        fuzz_examples(EXAMPLES, |s| {
+            if s.parse::<Manual>().is_ok() {
+                let _ = part1(s);
+                let _ = part2(s);
+            }
+        });
+    }
+}
@@ -0,0 +1,248 @@
+use common::input::Example;
+use common::parse::{map_lines, Tokens};
+use common::{Grid, ParseError, Pos};
+
+pub const EXAMPLES: &[Example] = &[("example", include_str!("../examples/example.txt"))];
+
+const SRC: Pos = (500, 0);
+
+fn down(p: Pos) -> Pos {
+    (p.0, p.1 + 1)
+}
+
+fn left(p: Pos) -> Pos {
+    (p.0 - 1, p.1)
+}
+
+fn right(p: Pos) -> Pos {
+    (p.0 + 1, p.1)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Water {
+    Still,
+    Stream,
+}
+
+#[derive(Debug)]
+enum Outcome {
+    Pour,
+    Stay,
+}
+
+#[derive(Debug)]
+enum Dir {
+    Left,
+    Right,
+}
+
+#[allow(dead_code)]
+fn draw_map(clays: &Grid<bool>, water: &Grid<Option<Water>>) {
+    let text = water.render(|p, w| match w {
+        Some(Water::Still) => '~',
+        Some(Water::Stream) => '|',
+        None if clays[p] => '#',
+        None => '.',
+    });
+    println!("{}", text);
+}
+
+fn go_dir(clays: &Grid<bool>, water: &Grid<Option<Water>>, p: Pos, d: Dir) -> (Outcome, Vec<Pos>) {
+    let mut cur = p;
+    let mut below = down(p);
+
+    let mut points = vec![];
+
+    loop {
+        if !clays[below] && water[below] != Some(Water::Still) {
+            points.push(cur);
+            return (Outcome::Pour, points);
+        } else if clays[cur] {
+            // Should be still (if both sides hit)
+            return (Outcome::Stay, points);
+        }
+
+        points.push(cur);
+
+        match d {
+            Dir::Left => {
+                cur = left(cur);
+                below = left(below);
+            }
+            Dir::Right => {
+                cur = right(cur);
+                below = right(below);
+            }
+        }
+    }
+}
+
+// x=495, y=2..7
+fn vein(line: &str) -> Result<(bool, i32, i32, i32), ParseError> {
+    let first = Tokens::words(line).next_tok("vein")?;
+    let vertical = match first.text.chars().next() {
+        Some('x') => true,
+        Some('y') => false,
+        _ => return Err(ParseError::expected("'x=' or 'y='", first)),
+    };
+
+    let mut it = Tokens::numbers(line);
+    let p = it.parse_next("position")?;
+    let from = it.parse_next("range start")?;
+    let to = it.parse_next("range end")?;
+
+    Ok((vertical, p, from, to))
+}
+
+/// Clay with a free column either side for the water to pour down and room up to the
+/// spring
+fn clays(input: &str) -> Result<Grid<bool>, ParseError> {
+    let mut points = vec![];
+
+    for (vertical, p, from, to) in map_lines(input, vein)? {
+        if vertical {
+            points.extend((from..=to).map(|y| (p, y)));
+        } else {
+            points.extend((from..=to).map(|x| (x, p)));
+        }
+    }
+    if points.is_empty() {
+        return Err(ParseError::empty("clay"));
+    }
+
+    let (min, max) = points.iter().fold((SRC, SRC), |(min, max), p| {
+        (
+            (min.0.min(p.0), min.1.min(p.1)),
+            (max.0.max(p.0), max.1.max(p.1)),
+        )
+    });
+
+    let mut clays = Grid::from_corners((min.0 - 1, min.1), (max.0 + 1, max.1), false);
+    for p in points {
+        clays[p] = true;
+    }
+
+    Ok(clays)
+}
+
+/// Pour water until it settles, gets (all water, still water)
+fn flow(input: &str) -> Result<(usize, usize), ParseError> {
+    let clays = clays(input)?;
+    let mut water = clays.map(|_| None);
+    water[SRC] = Some(Water::Stream);
+
+    let clay_ys = || clays.iter().filter(|(_, &c)| c).map(|(p, _)| p.1);
+    let max_y = clay_ys().max().unwrap();
+    let min_y = clay_ys().min().unwrap();
+
+    // Where the water is streaming, can have some that have settled since
+    let mut streams = vec![SRC];
+
+    // Until a round goes by with nothing new, as long as that's not too long coming
+    let mut changed = true;
+    for _ in 0..1000 {
+        if !changed {
+            break;
+        }
+        changed = false;
+
+        streams.retain(|&p| water[p] == Some(Water::Stream));
+        streams.sort_unstable();
+        streams.dedup();
+
+        for s in streams.clone() {
+            // Find where streams hit below
+            for y in s.1..max_y {
+                let point = (s.0, y);
+                let below = down(point);
+
+                if water[below] == Some(Water::Stream) {
+                    // Stream already goes down
+                    break;
+                } else if clays[below] || water[below] == Some(Water::Still) {
+                    let (res_left, left) = go_dir(&clays, &water, point, Dir::Left);
+                    let (res_right, right) = go_dir(&clays, &water, point, Dir::Right);
+
+                    match (res_left, res_right) {
+                        // Stay
+                        (Outcome::Stay, Outcome::Stay) => {
+                            for p in left.iter().chain(right.iter()) {
+                                changed |= water[*p] != Some(Water::Still);
+                                water[*p] = Some(Water::Still);
+                            }
+                        }
+                        // Pour
+                        _ => {
+                            for p in left.iter().chain(right.iter()) {
+                                changed |= water[*p] != Some(Water::Stream);
+                                water[*p] = Some(Water::Stream);
+                                streams.push(*p);
+                            }
+                        }
+                    }
+                    break;
+                } else {
+                    changed = true;
+                    water[below] = Some(Water::Stream);
+                    streams.push(below);
+                }
+            }
+        }
+    }
+
+    let part1 = water
+        .iter()
+        .filter(|(p, w)| p.1 >= min_y && w.is_some())
+        .count();
+    let part2 = water
+        .iter()
+        .filter(|(p, w)| p.1 >= min_y && **w == Some(Water::Still))
+        .count();
+
+    Ok((part1, part2))
+}
+
+pub fn part1(input: &str) -> Result<usize, ParseError> {
+    Ok(flow(input)?.0)
+}
+
+pub fn part2(input: &str) -> Result<usize, ParseError> {
+    Ok(flow(input)?.1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::fuzz::{fuzz, fuzz_examples, small};
+    use common::input::ex;
+
+    #[test]
+    fn examples() {
+        assert_eq!(part1(ex(EXAMPLES, "example")).unwrap(), 57);
+        assert_eq!(part2(ex(EXAMPLES, "example")).unwrap(), 29);
+    }
+
+    #[test]
+    fn bad_input() {
+        let e = part1("").unwrap_err();
+        assert_eq!(e.to_string(), "line 1, column 1: missing clay");
+        // A backwards range has no clay in it either
+        let e = part2("x=495, y=7..2\n").unwrap_err();
+        assert_eq!(e.to_string(), "line 1, column 1: missing clay");
+    }
+
+    #[test]
+    fn fuzz_vein() {
+        let seeds: Vec<&str> = ex(EXAMPLES, "example").lines().collect();
+        fuzz(&seeds, 5000, |s| {
+            let _ = vein(s);
+        });
+
+        fuzz_examples(EXAMPLES, |s| {
+            if small(s, 600) {
+                let _ = part1(s);
+                let _ = part2(s);
+            }
+        });
+    }
+}
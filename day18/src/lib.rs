@@ -0,0 +1,152 @@
+use std::collections::HashMap;
+
+use common::input::Example;
+use common::{Grid, ParseError, Pos};
+
+pub const EXAMPLES: &[Example] = &[("example", include_str!("../examples/example.txt"))];
+
+#[derive(Hash, Copy, Clone, Debug, PartialEq, Eq)]
+enum C {
+    Open,
+    Tree,
+    Lumberyard,
+}
+
+impl C {
+    fn from_char(c: char) -> Result<C, ()> {
+        let out = match c {
+            '.' => C::Open,
+            '|' => C::Tree,
+            '#' => C::Lumberyard,
+            _ => return Err(()),
+        };
+
+        Ok(out)
+    }
+}
+
+fn neieghbors(area: &Grid<C>, p: Pos) -> impl Iterator<Item = C> + '_ {
+    area.neighbours8(p).map(move |n| area[n])
+}
+
+#[allow(dead_code)]
+fn draw_map(area: &Grid<C>) {
+    let text = area.render(|_, c| match c {
+        C::Open => '.',
+        C::Tree => '|',
+        C::Lumberyard => '#',
+    });
+    println!("{}", text);
+}
+
+type History = HashMap<Grid<C>, usize>;
+
+/// Run until the area repeats, gets every state seen with the cycle start and length
+fn simulate(input: &str) -> Result<(History, usize, usize), ParseError> {
+    let mut area = Grid::parse(input, "'.', '|' or '#'", C::Open, |_, c| {
+        C::from_char(c).ok()
+    })?;
+    if area.width() == 0 {
+        return Err(ParseError::empty("acre"));
+    }
+
+    let mut past = HashMap::new();
+    let mut cycle_start = 0;
+    let mut cycle = 0;
+
+    for t in 1.. {
+        let mut area2 = area.clone();
+
+        for (k, v) in area.iter() {
+            let n: Vec<C> = neieghbors(&area, k).collect();
+
+            let n = match v {
+                C::Open => {
+                    let c = n.iter().filter(|&&a| a == C::Tree).count();
+                    if c >= 3 {
+                        C::Tree
+                    } else {
+                        *v
+                    }
+                }
+                C::Tree => {
+                    let c = n.iter().filter(|&&a| a == C::Lumberyard).count();
+                    if c >= 3 {
+                        C::Lumberyard
+                    } else {
+                        *v
+                    }
+                }
+                C::Lumberyard => {
+                    let l = n.contains(&C::Lumberyard);
+                    let t = n.contains(&C::Tree);
+                    if l && t {
+                        *v
+                    } else {
+                        C::Open
+                    }
+                }
+            };
+
+            area2[k] = n;
+        }
+
+        area = area2;
+
+        if let Some(past_turn) = past.get(&area) {
+            cycle = t - past_turn;
+            cycle_start = *past_turn;
+            break;
+        }
+
+        past.insert(area.clone(), t);
+    }
+
+    Ok((past, cycle_start, cycle))
+}
+
+fn resource_value(state: &Grid<C>) -> usize {
+    let t = state.values().filter(|&&a| a == C::Tree).count();
+    let l = state.values().filter(|&&a| a == C::Lumberyard).count();
+    t * l
+}
+
+/// Resource value after `minutes`, which has to be at least 1
+fn after(input: &str, minutes: usize) -> Result<usize, ParseError> {
+    let (past, cycle_start, cycle) = simulate(input)?;
+    let t = if minutes < cycle_start {
+        minutes
+    } else {
+        (minutes - cycle_start) % cycle + cycle_start
+    };
+    let (k, _) = past.iter().find(|(_, v)| **v == t).unwrap();
+    Ok(resource_value(k))
+}
+
+pub fn part1(input: &str) -> Result<usize, ParseError> {
+    after(input, 10)
+}
+
+pub fn part2(input: &str) -> Result<usize, ParseError> {
+    after(input, 1000000000)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::input::ex;
+
+    #[test]
+    fn examples() {
+        assert_eq!(part1(ex(EXAMPLES, "example")).unwrap(), 1147);
+    }
+
+    #[test]
+    fn bad_input() {
+        let e = part1("").unwrap_err();
+        assert_eq!(e.to_string(), "line 1, column 1: missing acre");
+        // Settles down well before the 10 minutes are up
+        assert_eq!(part1("||\n||\n").unwrap(), 0);
+        assert_eq!(part1("##|\n|||\n").unwrap(), 2 * 4);
+    }
+}
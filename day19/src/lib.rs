@@ -0,0 +1,117 @@
+use std::fmt;
+
+use common::input::Example;
+use common::{ParseError, SolveError};
+use elfcode::cycle::{self, Outcome};
+use elfcode::vm::Fault;
+use elfcode::{Overflow, Program, Vm};
+
+pub const EXAMPLES: &[Example] = &[("example", include_str!("../examples/example.txt"))];
+
+/// Instructions before giving up, counting all the ones the divisor loops stand in for.
+/// Part 2 of the real input takes about 900 trillion.
+const BUDGET: u64 = 10_000_000_000_000_000;
+
+/// Why the program didn't give an answer
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    Parse(ParseError),
+    /// Anything but halting
+    Outcome(Outcome),
+    Fault(Fault),
+}
+
+impl From<ParseError> for Error {
+    fn from(e: ParseError) -> Error {
+        Error::Parse(e)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Parse(e) => write!(f, "{}", e),
+            Error::Outcome(out) => write!(f, "{}", out),
+            Error::Fault(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<Error> for SolveError {
+    fn from(e: Error) -> SolveError {
+        match e {
+            Error::Parse(e) => SolveError::Parse(e),
+            e => SolveError::Run(e.to_string()),
+        }
+    }
+}
+
+/// Run until the program halts, the divisor loops in one go. One that goes round in
+/// circles instead is an error rather than a hang, and so is running out of `budget`.
+fn run(program: Program, reg: [i64; 6], budget: u64) -> Result<[i64; 6], Error> {
+    let mut vm = Vm::new(program, reg)
+        .with_overflow(Overflow::Checked)
+        .optimized();
+
+    match cycle::run(&mut vm, Some(budget)).map_err(Error::Fault)? {
+        Outcome::Halted { .. } => Ok(vm.reg),
+        out => Err(Error::Outcome(out)),
+    }
+}
+
+pub fn part1(input: &str) -> Result<i64, Error> {
+    Ok(run(input.parse()?, [0; 6], BUDGET)?[0])
+}
+
+pub fn part2(input: &str) -> Result<i64, Error> {
+    Ok(run(input.parse()?, [1, 0, 0, 0, 0, 0], BUDGET)?[0])
+}
+
+// `cargo run -p elfcode -- decompile day19/input` shows the two loops, a is the sum of
+// the divisors of b
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::fuzz::fuzz_examples;
+    use common::input::ex;
+
+    #[test]
+    fn examples() {
+        assert_eq!(part1(ex(EXAMPLES, "example")).unwrap(), 6);
+    }
+
+    #[test]
+    fn errors() {
+        let e = part1("#ip 0\nseti 0 0 1\nseti 0 0 0").unwrap_err();
+        assert_eq!(
+            e,
+            Error::Outcome(Outcome::Cycled {
+                entry: 1,
+                start: 1,
+                period: 1
+            })
+        );
+        assert_eq!(
+            SolveError::from(e),
+            SolveError::Run(
+                "never halts, from pc 1 after 1 instructions it repeats every 1".to_owned()
+            )
+        );
+
+        let e = part2("#ip 9\nseti 0 0 0").unwrap_err();
+        assert!(matches!(e, Error::Fault(f) if f.pc == 0), "{:?}", e);
+    }
+
+    #[test]
+    fn fuzz_parse() {
+        fuzz_examples(EXAMPLES, |s| {
+            if let Ok(p) = s.parse::<Program>() {
+                // Both parts, with a budget as it can count up forever
+                for r0 in 0..2 {
+                    let _ = run(p.clone(), [r0, 0, 0, 0, 0, 0], 10_000);
+                }
+            }
+        });
+    }
+}
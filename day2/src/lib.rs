@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+
+use common::input::Example;
+use common::ParseError;
+
+pub const EXAMPLES: &[Example] = &[
+    ("part1", include_str!("../examples/part1.txt")),
+    ("part2", include_str!("../examples/part2.txt")),
+];
+
+pub fn part1(input: &str) -> Result<usize, ParseError> {
+    let mut twos = 0;
+    let mut threes = 0;
+
+    for b in input.lines() {
+        let mut hm: HashMap<char, usize> = HashMap::new();
+        for c in b.chars() {
+            let cnt = *hm.get(&c).unwrap_or(&0) + 1;
+            hm.insert(c, cnt);
+        }
+
+        if hm.values().any(|&v| v == 2) {
+            twos += 1;
+        }
+
+        if hm.values().any(|&v| v == 3) {
+            threes += 1;
+        }
+    }
+
+    Ok(twos * threes)
+}
+
+pub fn part2(input: &str) -> Result<String, ParseError> {
+    let boxes: Vec<_> = input.lines().collect();
+    if boxes.is_empty() {
+        return Err(ParseError::empty("box id"));
+    }
+
+    let diff = |a: &str, b: &str| {
+        a.chars()
+            .zip(b.chars())
+            .fold(0, |acc, (c1, c2)| if c1 != c2 { acc + 1 } else { acc })
+    };
+
+    let (b1, b2) = {
+        let mut b1 = None;
+        let mut b2 = None;
+
+        for (idx, a) in boxes.iter().enumerate() {
+            for b in boxes.iter().skip(idx + 1) {
+                if diff(a, b) == 1 {
+                    b1 = Some(a);
+                    b2 = Some(b);
+                    break;
+                }
+            }
+        }
+
+        match (b1, b2) {
+            (Some(b1), Some(b2)) => (b1, b2),
+            _ => return Err(ParseError::invalid("no two box ids differ by one letter")),
+        }
+    };
+
+    Ok(b1
+        .chars()
+        .zip(b2.chars())
+        .filter(|(c1, c2)| c1 == c2)
+        .map(|(c1, _)| c1)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::input::ex;
+
+    #[test]
+    fn examples() {
+        assert_eq!(part1(ex(EXAMPLES, "part1")).unwrap(), 12);
+        assert_eq!(part2(ex(EXAMPLES, "part2")).unwrap(), "fgij");
+    }
+
+    #[test]
+    fn bad_input() {
+        let e = part2("").unwrap_err();
+        assert_eq!(e.to_string(), "line 1, column 1: missing box id");
+        let e = part2("abc\nxyz\n").unwrap_err();
+        assert_eq!(e.to_string(), "no two box ids differ by one letter");
+    }
+}
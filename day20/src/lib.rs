@@ -0,0 +1,205 @@
+use std::collections::HashMap;
+
+use common::input::Example;
+use common::parse::bad_char;
+use common::{ParseError, Pos};
+
+pub const EXAMPLES: &[Example] = &[
+    ("example1", include_str!("../examples/example1.txt")),
+    ("example2", include_str!("../examples/example2.txt")),
+    ("example3", include_str!("../examples/example3.txt")),
+    ("example4", include_str!("../examples/example4.txt")),
+    ("example5", include_str!("../examples/example5.txt")),
+];
+
+// "Magical" version that doesn't read directions at all
+fn longest(text: &str) -> (usize, usize) {
+    let nonletter = text.find(['|', '(', ')']);
+
+    let token_len = if let Some(l) = nonletter {
+        l
+    } else {
+        return (text.len(), text.len());
+    };
+
+    let ch = text.chars().nth(token_len).unwrap();
+    let text_len = token_len + 1;
+
+    match ch {
+        '|' | ')' => (token_len, text_len),
+        '(' => {
+            let mut children_len = None;
+            let mut child_text_len = 0;
+
+            loop {
+                if text.chars().nth(token_len + child_text_len).unwrap() == ')' {
+                    break;
+                }
+
+                let (clen, ctext_len) = longest(&text[text_len + child_text_len..]);
+                child_text_len += ctext_len;
+
+                if clen == 0 {
+                    children_len = Some(0);
+                } else if let Some(m) = children_len.as_mut() {
+                    *m = std::cmp::max(clen, *m);
+                } else {
+                    children_len = Some(clen);
+                }
+            }
+
+            let children_len = children_len.unwrap_or(0);
+
+            let (next_len, next_text_len) = longest(&text[text_len + child_text_len..]);
+
+            (
+                token_len + children_len + next_len,
+                text_len + child_text_len + next_text_len,
+            )
+        }
+        _ => unreachable!(),
+    }
+}
+
+fn char_to_vec(c: char) -> (i32, i32) {
+    match c {
+        'N' => (1, 0),
+        'S' => (-1, 0),
+        'W' => (0, -1),
+        'E' => (0, 1),
+        _ => unreachable!(),
+    }
+}
+
+// Not as magical
+fn walk(text: &str, maze: &mut HashMap<Pos, usize>, pos: Pos) -> (usize, Pos) {
+    let nonletter = text.find(['|', '(', ')']);
+
+    let mut dist = maze[&pos];
+
+    let mut cur_pos = pos;
+
+    for c in text
+        .chars()
+        .take_while(|&c| c != '|' && c != '(' && c != ')')
+    {
+        let v = char_to_vec(c);
+        cur_pos = (cur_pos.0 + v.0, cur_pos.1 + v.1);
+        dist += 1;
+        // Only insert when first seen, we only enter the same room on detours
+        maze.entry(cur_pos).or_insert(dist);
+    }
+
+    let token_len = if let Some(l) = nonletter {
+        l
+    } else {
+        return (text.len(), cur_pos);
+    };
+
+    let ch = text.chars().nth(token_len).unwrap();
+    let text_len = token_len + 1;
+
+    match ch {
+        '|' | ')' => (text_len, cur_pos),
+        '(' => {
+            let mut child_text_len = 0;
+            let mut cpos = cur_pos;
+            loop {
+                if text.chars().nth(token_len + child_text_len).unwrap() == ')' {
+                    break;
+                }
+
+                let (ctext_len, c) = walk(&text[text_len + child_text_len..], maze, cur_pos);
+                cpos = c;
+                child_text_len += ctext_len;
+            }
+
+            let (next_text_len, fpos) = walk(&text[text_len + child_text_len..], maze, cpos);
+
+            (text_len + child_text_len + next_text_len, fpos)
+        }
+        _ => unreachable!(),
+    }
+}
+
+/// Checks the regex is ^...$ around directions and balanced branches, gets the inside
+fn regex(input: &str) -> Result<&str, ParseError> {
+    let line = input.lines().next().unwrap_or("");
+    let text = line.trim();
+    let offset = line.len() - line.trim_start().len();
+    let last = text.chars().count().saturating_sub(1);
+    let mut depth = 0;
+
+    for (i, c) in text.chars().enumerate() {
+        let col = offset + i + 1;
+
+        match c {
+            '^' if i == 0 => continue,
+            _ if i == 0 => return Err(bad_char("'^'", 1, col, c)),
+            '$' if i == last => continue,
+            _ if i == last => return Err(bad_char("'$'", 1, col, c)),
+            'N' | 'S' | 'W' | 'E' => (),
+            '|' if depth > 0 => (),
+            '(' => depth += 1,
+            ')' if depth > 0 => depth -= 1,
+            ')' | '|' => return Err(bad_char("a '(' before it", 1, col, c)),
+            _ => return Err(bad_char("'N', 'S', 'W', 'E', '|', '(' or ')'", 1, col, c)),
+        }
+    }
+
+    if text.len() < 2 {
+        return Err(ParseError::missing("'^...$'", line).at_line(1));
+    }
+
+    if depth > 0 {
+        return Err(ParseError::missing("')'", &line[..offset + text.len() - 1]).at_line(1));
+    }
+
+    Ok(&text[1..text.len() - 1])
+}
+
+pub fn part1(input: &str) -> Result<usize, ParseError> {
+    let stripped = regex(input)?;
+    let (max_len, text_read) = longest(stripped);
+    // Assert we read all of it
+    assert_eq!(text_read, stripped.len());
+    Ok(max_len)
+}
+
+pub fn part2(input: &str) -> Result<usize, ParseError> {
+    let stripped = regex(input)?;
+    let mut maze = HashMap::new();
+    maze.insert((0, 0), 0);
+    let (text_read, _) = walk(stripped, &mut maze, (0, 0));
+    // Assert we read all of it
+    assert_eq!(text_read, stripped.len());
+
+    Ok(maze.values().filter(|&&d| d >= 1000).count())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::fuzz::fuzz_examples;
+    use common::input::ex;
+
+    #[test]
+    fn examples() {
+        let doors = [3, 10, 18, 23, 31];
+
+        for (i, &d) in doors.iter().enumerate() {
+            let name = format!("example{}", i + 1);
+            assert_eq!(part1(ex(EXAMPLES, &name)).unwrap(), d, "{}", name);
+        }
+    }
+
+    #[test]
+    fn fuzz_regex() {
+        fuzz_examples(EXAMPLES, |s| {
+            if regex(s).is_ok() {
+                let _ = part1(s);
+                let _ = part2(s);
+            }
+        });
+    }
+}
@@ -0,0 +1,134 @@
+use std::fmt;
+
+use common::input::Example;
+use common::{ParseError, SolveError};
+use elfcode::halt::{self, halting_values, End, Halts};
+use elfcode::{Overflow, Vm};
+
+// No examples for this one
+pub const EXAMPLES: &[Example] = &[];
+
+/// Instructions before giving up, the real input needs about 2.5 billion to see every
+/// value
+const BUDGET: u64 = 10_000_000_000;
+
+/// Why there's no answer
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    Parse(ParseError),
+    /// The program isn't one whose r0 checks can be found
+    Halt(halt::Error),
+    /// No value to give, from how the search ended
+    NoAnswer(End),
+}
+
+impl From<ParseError> for Error {
+    fn from(e: ParseError) -> Error {
+        Error::Parse(e)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Parse(e) => write!(f, "{}", e),
+            Error::Halt(e) => write!(f, "{}", e),
+            Error::NoAnswer(End::Cycled) => write!(f, "no value of r0 halts it"),
+            Error::NoAnswer(End::Halted) => write!(f, "it halts whatever r0 is"),
+            Error::NoAnswer(End::Budget) => {
+                write!(f, "ran out of budget before the values of r0 ran out")
+            }
+        }
+    }
+}
+
+impl From<Error> for SolveError {
+    fn from(e: Error) -> SolveError {
+        match e {
+            Error::Parse(e) => SolveError::Parse(e),
+            e => SolveError::Run(e.to_string()),
+        }
+    }
+}
+
+/// The values of r0 that halt it, found from wherever the program checks r0
+fn halts(input: &str, budget: u64) -> Result<Halts<i64>, Error> {
+    // The hash multiplies past what an i32 holds, and the divide by 256 loop is done
+    // natively
+    let vm = Vm::new(input.parse()?, [0_i64; 6])
+        .with_overflow(Overflow::Checked)
+        .optimized();
+
+    halting_values(vm, Some(budget)).map_err(Error::Halt)
+}
+
+pub fn part1(input: &str) -> Result<i64, Error> {
+    let h = halts(input, BUDGET)?;
+    match h.fastest() {
+        Some(halt) => Ok(halt.value),
+        None => Err(Error::NoAnswer(h.end)),
+    }
+}
+
+pub fn part2(input: &str) -> Result<i64, Error> {
+    let h = halts(input, BUDGET)?;
+    match h.last() {
+        Some(halt) => Ok(halt.value),
+        None => Err(Error::NoAnswer(h.end)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Steps r1 through 5, 3, 1, 4, 2 and round again, halting if r0 matches
+    const MOD: &str = "\
+#ip 5
+seti 5 0 1
+eqrr 1 0 2
+addr 2 5 5
+seti 4 0 5
+seti 11 0 5
+addi 1 3 1
+seti 5 0 2
+gtrr 1 2 2
+addr 5 2 5
+seti 0 0 5
+addi 1 -5 1
+seti 0 0 5
+seti 99 0 3
+";
+
+    #[test]
+    fn values() {
+        let h = halts(MOD, BUDGET).unwrap();
+        let values: Vec<i64> = h.values.iter().map(|h| h.value).collect();
+        assert_eq!(values, vec![5, 3, 1, 4, 2]);
+
+        assert_eq!(part1(MOD).unwrap(), 5);
+        assert_eq!(part2(MOD).unwrap(), 2);
+    }
+
+    #[test]
+    fn errors() {
+        let e = part1("#ip 5\naddi 1 1 1\n").unwrap_err();
+        assert_eq!(e, Error::Halt(halt::Error::NoCheck));
+        assert_eq!(
+            SolveError::from(e),
+            SolveError::Run("no eqrr against r0 that halts the program".to_owned())
+        );
+
+        let e = part2(&MOD.replace("seti 5 0 2", "setr 0 0 2")).unwrap_err();
+        assert_eq!(e, Error::Halt(halt::Error::UsesR0(6)));
+
+        // Not long enough to see them all come round again
+        let h = halts(MOD, 20).unwrap();
+        assert_eq!(h.fastest().unwrap().value, 5);
+        assert_eq!(h.last(), None);
+        assert_eq!(
+            Error::NoAnswer(h.end).to_string(),
+            "ran out of budget before the values of r0 ran out"
+        );
+    }
+}
@@ -0,0 +1,293 @@
+use std::cmp::Ord;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::collections::HashMap;
+
+use common::input::Example;
+use common::parse::Tokens;
+use common::{ParseError, Pos};
+
+pub const EXAMPLES: &[Example] = &[("example", include_str!("../examples/example.txt"))];
+
+#[derive(Debug, Clone, Copy)]
+enum Type {
+    Rocky,
+    Wet,
+    Narrow,
+}
+
+impl Type {
+    fn from_lvl(lvl: i32) -> Type {
+        match lvl % 3 {
+            0 => Type::Rocky,
+            1 => Type::Wet,
+            2 => Type::Narrow,
+            _ => unreachable!(),
+        }
+    }
+
+    #[allow(dead_code)]
+    fn as_char(&self) -> char {
+        match *self {
+            Type::Rocky => '.',
+            Type::Wet => '=',
+            Type::Narrow => '|',
+        }
+    }
+
+    fn risk(&self) -> i32 {
+        match *self {
+            Type::Rocky => 0,
+            Type::Wet => 1,
+            Type::Narrow => 2,
+        }
+    }
+
+    fn tools(&self) -> [Tool; 2] {
+        match *self {
+            Type::Rocky => [Tool::CGear, Tool::Torch],
+            Type::Wet => [Tool::CGear, Tool::Neither],
+            Type::Narrow => [Tool::Torch, Tool::Neither],
+        }
+    }
+}
+
+struct Cave {
+    depth: i32,
+    target: Pos,
+    glevels: HashMap<Pos, i32>,
+}
+
+impl Cave {
+    fn new(input: &str) -> Result<Cave, ParseError> {
+        let mut depth = None;
+        let mut target = None;
+
+        for (n, line) in input.lines().enumerate() {
+            let mut set = || {
+                let mut it = Tokens::split(line, |c| c != ',' && !c.is_whitespace());
+                let key = it.next_tok("'depth:' or 'target:'")?;
+
+                match key.text {
+                    "depth:" => depth = Some(it.parse_next("depth")?),
+                    "target:" => target = Some((it.parse_next("x")?, it.parse_next("y")?)),
+                    _ => return Err(ParseError::expected("'depth:' or 'target:'", key)),
+                }
+
+                Ok(())
+            };
+
+            set().map_err(|e: ParseError| e.at_line(n + 1))?;
+        }
+
+        let end = input.lines().count() + 1;
+        let missing = |what| ParseError::missing(what, "").at_line(end);
+
+        let cave = Cave {
+            depth: depth.ok_or_else(|| missing("depth"))?,
+            target: target.ok_or_else(|| missing("target"))?,
+            glevels: HashMap::new(),
+        };
+
+        // The search only goes right and down from the mouth
+        if cave.depth < 0 || cave.target.0 < 0 || cave.target.1 < 0 {
+            return Err(ParseError::invalid(
+                "the depth and the target can't be negative",
+            ));
+        }
+        Ok(cave)
+    }
+
+    fn elevel(&mut self, p: Pos) -> i32 {
+        let g = self.geo_index(p);
+        (g + self.depth) % 20183
+    }
+
+    fn get_type(&mut self, p: Pos) -> Type {
+        let e = self.elevel(p);
+        Type::from_lvl(e)
+    }
+
+    fn geo_index(&mut self, p: Pos) -> i32 {
+        if let Some(g) = self.glevels.get(&p) {
+            return *g;
+        }
+
+        let g = match p {
+            (0, 0) => 0,
+            _ if p == self.target => 0,
+            (x, 0) => x * 16807,
+            (0, y) => y * 48271,
+            (x, y) => {
+                let p1 = (x - 1, y);
+                let p2 = (x, y - 1);
+                let e1 = self.elevel(p1);
+                let e2 = self.elevel(p2);
+                e1 * e2
+            }
+        };
+
+        self.glevels.insert(p, g);
+        g
+    }
+}
+
+pub fn part1(input: &str) -> Result<i32, ParseError> {
+    let mut cave = Cave::new(input)?;
+    let mut sum = 0;
+
+    for y in 0..=cave.target.1 {
+        for x in 0..=cave.target.0 {
+            let e = cave.elevel((x, y));
+            let t = Type::from_lvl(e);
+
+            sum += t.risk();
+        }
+    }
+
+    Ok(sum)
+}
+
+pub fn part2(input: &str) -> Result<i32, ParseError> {
+    let mut cave = Cave::new(input)?;
+
+    // Or the search would go on forever looking for a way in
+    if let Type::Wet = cave.get_type(cave.target) {
+        return Err(ParseError::invalid(
+            "the torch can't go into the wet target",
+        ));
+    }
+    Ok(ucs(&mut cave))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Tool {
+    Neither,
+    Torch,
+    CGear,
+}
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+struct State {
+    p: Pos,
+    t: Tool,
+}
+
+impl State {
+    pub fn new(t: Tool, p: Pos) -> State {
+        State { p, t }
+    }
+}
+
+#[derive(PartialEq, Eq)]
+struct C(State, i32);
+
+impl Ord for C {
+    fn cmp(&self, other: &C) -> Ordering {
+        self.1.cmp(&other.1).reverse()
+    }
+}
+
+impl PartialOrd for C {
+    fn partial_cmp(&self, other: &C) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn ucs(cave: &mut Cave) -> i32 {
+    let adj = [(0, 1), (0, -1), (1, 0), (-1, 0)];
+
+    let initial = State::new(Tool::Torch, (0, 0));
+
+    let mut frontier = BinaryHeap::new();
+    frontier.push(C(initial, 0));
+    let mut bests = HashMap::new();
+
+    loop {
+        // get min cost
+        let C(cur, cost) = frontier.pop().unwrap();
+
+        let p = cur.p;
+        let t = cave.get_type(p);
+
+        if p == cave.target && cur.t == Tool::Torch {
+            return cost;
+        }
+
+        let neighbors = adj
+            .iter()
+            .map(|&s| (s.0 + p.0, s.1 + p.1))
+            .filter(|&n| n.0 >= 0 && n.1 >= 0);
+
+        for tool in &t.tools() {
+            if cur.t != *tool {
+                // same pos, different tool
+                let new_state = State::new(*tool, p);
+                let new_cost = cost + 7;
+
+                if let Some(b) = bests.get_mut(&new_state) {
+                    if new_cost < *b {
+                        *b = new_cost;
+                        frontier.push(C(new_state, new_cost));
+                    }
+                } else {
+                    bests.insert(new_state.clone(), new_cost);
+                    frontier.push(C(new_state, new_cost));
+                }
+            }
+        }
+
+        for n in neighbors {
+            let nt = cave.get_type(n);
+            if nt.tools().contains(&cur.t) {
+                // can we use this tool there?
+                let new_state = State::new(cur.t, n);
+                let new_cost = cost + 1;
+
+                if let Some(b) = bests.get_mut(&new_state) {
+                    if new_cost < *b {
+                        *b = new_cost;
+                        frontier.push(C(new_state, new_cost));
+                    }
+                } else {
+                    bests.insert(new_state.clone(), new_cost);
+                    frontier.push(C(new_state, new_cost));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::fuzz::{fuzz_examples, small};
+    use common::input::ex;
+
+    #[test]
+    fn examples() {
+        assert_eq!(part1(ex(EXAMPLES, "example")).unwrap(), 114);
+        assert_eq!(part2(ex(EXAMPLES, "example")).unwrap(), 45);
+    }
+
+    #[test]
+    fn bad_input() {
+        let e = part2("depth: 510\ntarget: -1,10\n").unwrap_err();
+        assert_eq!(e.to_string(), "the depth and the target can't be negative");
+
+        let e = part2("depth: 10\ntarget: 10,10\n").unwrap_err();
+        assert_eq!(e.to_string(), "the torch can't go into the wet target");
+    }
+
+    #[test]
+    fn fuzz_cave() {
+        fuzz_examples(EXAMPLES, |s| {
+            // The depth doesn't change how long it takes, how far away the target is does
+            let near = |c: Cave| c.target.0 <= 50 && c.target.1 <= 50;
+            if Cave::new(s).is_ok_and(near) && small(s, 1000) {
+                let _ = part1(s);
+                let _ = part2(s);
+            }
+        });
+    }
+}
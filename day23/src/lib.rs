@@ -0,0 +1,225 @@
+use std::cmp;
+use std::fmt;
+use std::str::FromStr;
+
+use common::input::Example;
+use common::parse::{parse_lines, Tokens};
+use common::{m_dist, ParseError, Pos3 as Pos};
+
+pub const EXAMPLES: &[Example] = &[
+    ("part1", include_str!("../examples/part1.txt")),
+    ("part2", include_str!("../examples/part2.txt")),
+];
+
+#[derive(Debug, PartialEq)]
+struct NanoBot {
+    p: Pos,
+    r: i32,
+}
+
+impl FromStr for NanoBot {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // pos=<0,0,0>, r=4
+        let mut it = Tokens::numbers(s);
+        let x = it.parse_next("x")?;
+        let y = it.parse_next("y")?;
+        let z = it.parse_next("z")?;
+        let r = it.parse_next("radius")?;
+
+        Ok(NanoBot { p: (x, y, z), r })
+    }
+}
+
+impl fmt::Display for NanoBot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "pos=<{},{},{}>, r={}",
+            self.p.0, self.p.1, self.p.2, self.r
+        )
+    }
+}
+
+impl NanoBot {
+    fn in_range(&self, p: Pos) -> bool {
+        self.r >= m_dist(self.p, p)
+    }
+}
+
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Debug)]
+struct AABB {
+    min: Pos,
+    max: Pos,
+}
+
+impl AABB {
+    fn intersect(&self, b: &NanoBot) -> bool {
+        let x = cmp::max(self.min.0, cmp::min(b.p.0, self.max.0));
+        let y = cmp::max(self.min.1, cmp::min(b.p.1, self.max.1));
+        let z = cmp::max(self.min.2, cmp::min(b.p.2, self.max.2));
+
+        let p = (x, y, z);
+        b.in_range(p)
+    }
+
+    fn intersect_all(&self, bots: &[NanoBot]) -> usize {
+        bots.iter().filter(|b| self.intersect(b)).count()
+    }
+
+    fn is_size_one(&self) -> bool {
+        self.min == self.max
+    }
+
+    fn div(self) -> Vec<AABB> {
+        let div_x = (self.max.0 - self.min.0) / 2 + self.min.0;
+        let div_y = (self.max.1 - self.min.1) / 2 + self.min.1;
+        let div_z = (self.max.2 - self.min.2) / 2 + self.min.2;
+
+        let mut out = vec![];
+
+        for x in 0..=1 {
+            for y in 0..=1 {
+                for z in 0..=1 {
+                    let (min_x, max_x) = if x == 0 {
+                        (self.min.0, div_x)
+                    } else {
+                        (div_x + 1, self.max.0)
+                    };
+                    let (min_y, max_y) = if y == 0 {
+                        (self.min.1, div_y)
+                    } else {
+                        (div_y + 1, self.max.1)
+                    };
+                    let (min_z, max_z) = if z == 0 {
+                        (self.min.2, div_z)
+                    } else {
+                        (div_z + 1, self.max.2)
+                    };
+
+                    let mbox = AABB {
+                        min: (min_x, min_y, min_z),
+                        max: (max_x, max_y, max_z),
+                    };
+
+                    // A side one wide doesn't split in two
+                    if min_x <= max_x && min_y <= max_y && min_z <= max_z {
+                        out.push(mbox);
+                    }
+                }
+            }
+        }
+
+        out
+    }
+}
+
+fn bots(input: &str) -> Result<Vec<NanoBot>, ParseError> {
+    let bots: Vec<NanoBot> = parse_lines(input)?;
+    if bots.is_empty() {
+        return Err(ParseError::empty("nanobot"));
+    }
+    Ok(bots)
+}
+
+pub fn part1(input: &str) -> Result<usize, ParseError> {
+    let bots = bots(input)?;
+    let max_r = bots.iter().max_by_key(|b| b.r).unwrap();
+    Ok(bots.iter().filter(|b| max_r.in_range(b.p)).count())
+}
+
+pub fn part2(input: &str) -> Result<i32, ParseError> {
+    let bots = bots(input)?;
+
+    let min_x = bots.iter().map(|b| b.p.0 - b.r).min().unwrap();
+    let min_y = bots.iter().map(|b| b.p.1 - b.r).min().unwrap();
+    let min_z = bots.iter().map(|b| b.p.2 - b.r).min().unwrap();
+
+    let max_x = bots.iter().map(|b| b.p.0 + b.r).max().unwrap();
+    let max_y = bots.iter().map(|b| b.p.1 + b.r).max().unwrap();
+    let max_z = bots.iter().map(|b| b.p.2 + b.r).max().unwrap();
+
+    let mbox = AABB {
+        min: (min_x, min_y, min_z),
+        max: (max_x, max_y, max_z),
+    };
+
+    let mut frontier = {
+        let cnt = mbox.intersect_all(&bots);
+        vec![(mbox, cnt)]
+    };
+
+    loop {
+        let idx = frontier
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, i)| i.1)
+            .unwrap()
+            .0;
+
+        let (most, _) = frontier.remove(idx);
+
+        if most.is_size_one() {
+            // Didn't need to check for the closest point for my input ¯\_(ツ)_/¯
+            return Ok(m_dist((0, 0, 0), most.min));
+        }
+
+        for child in most.div() {
+            let cnt = child.intersect_all(&bots);
+            frontier.push((child, cnt));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::fuzz::{check, fuzz, fuzz_examples, small};
+    use common::input::ex;
+
+    #[test]
+    fn examples() {
+        assert_eq!(part1(ex(EXAMPLES, "part1")).unwrap(), 7);
+        assert_eq!(part2(ex(EXAMPLES, "part2")).unwrap(), 36);
+    }
+
+    #[test]
+    fn bad_input() {
+        let e = part1("").unwrap_err();
+        assert_eq!(e.to_string(), "line 1, column 1: missing nanobot");
+        let e = part2("\n").unwrap_err();
+        assert_eq!(e.to_string(), "line 1, column 1: missing x");
+    }
+
+    #[test]
+    fn round_trip() {
+        check(
+            1000,
+            |r| NanoBot {
+                p: (r.i32(), r.i32(), r.i32()),
+                r: r.i32().saturating_abs(),
+            },
+            |b| assert_eq!(&b.to_string().parse::<NanoBot>().unwrap(), b),
+        );
+    }
+
+    #[test]
+    fn fuzz_nanobot() {
+        let seeds: Vec<&str> = ex(EXAMPLES, "part1")
+            .lines()
+            .chain(ex(EXAMPLES, "part2").lines())
+            .collect();
+        fuzz(&seeds, 5000, |s| {
+            let _ = s.parse::<NanoBot>();
+        });
+
+        fuzz_examples(EXAMPLES, |s| {
+            if small(s, 200) {
+                let _ = part1(s);
+                let _ = part2(s);
+            }
+        });
+    }
+}
@@ -0,0 +1,459 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+use common::input::Example;
+use common::output::{Answer, Extra};
+use common::parse::Tokens;
+use common::ParseError;
+
+pub const EXAMPLES: &[Example] = &[("example", include_str!("../examples/example.txt"))];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Side {
+    ImmuneSystem,
+    Infection,
+}
+
+/// Who won and how many units they had left
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct War {
+    pub winner: Side,
+    pub units: i32,
+    pub boost: i32,
+}
+
+impl fmt::Display for War {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.units)
+    }
+}
+
+impl Answer for War {
+    fn extra(&self) -> Extra {
+        let winner = match self.winner {
+            Side::ImmuneSystem => "Immune System",
+            Side::Infection => "Infection",
+        };
+
+        vec![
+            ("winner", winner.into()),
+            ("units", self.units.into()),
+            ("boost", self.boost.into()),
+        ]
+    }
+}
+
+// stringly typed rip
+#[derive(Debug, Clone, PartialEq)]
+struct Group {
+    side: Side,
+    units: i32,
+    hp: i32,
+    ap: i32,
+    weak: Vec<String>,
+    immune: Vec<String>,
+    attack_type: String,
+    initiative: i32,
+    tgt: Option<usize>,
+}
+
+impl Group {
+    /// Wide enough for the biggest boosts
+    fn epower(&self) -> i64 {
+        i64::from(self.ap) * i64::from(self.units)
+    }
+
+    fn calc_dmg_from(&self, other: &Group) -> i64 {
+        if self.weak.contains(&other.attack_type) {
+            other.epower().saturating_mul(2)
+        } else if self.immune.contains(&other.attack_type) {
+            0
+        } else {
+            other.epower()
+        }
+    }
+
+    fn get_attacked(&mut self, dmg: i64) -> i32 {
+        let dead_cnt = (dmg / i64::from(self.hp)).min(self.units.into()) as i32;
+        self.units -= dead_cnt;
+        dead_cnt
+    }
+
+    fn target(&self, others: &HashMap<usize, Group>, tgts: &[usize]) -> Option<usize> {
+        tgts.iter()
+            .filter(|&g| others[g].side != self.side)
+            .filter(|&g| others[g].calc_dmg_from(self) != 0)
+            .max_by(|&a, &b| {
+                let dmg_cmp = others[a]
+                    .calc_dmg_from(self)
+                    .cmp(&others[b].calc_dmg_from(self));
+                if let Ordering::Equal = dmg_cmp {
+                    let ep_cmp = others[a].epower().cmp(&others[b].epower());
+                    if let Ordering::Equal = ep_cmp {
+                        others[a].initiative.cmp(&others[b].initiative)
+                    } else {
+                        ep_cmp
+                    }
+                } else {
+                    dmg_cmp
+                }
+            })
+            .copied()
+    }
+}
+
+impl FromStr for Group {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // The Amazing Parse-Man:
+        let mut val: Option<i32> = None;
+        let mut prev: Option<&str> = None;
+        let mut units: Option<i32> = None;
+        let mut hp: Option<i32> = None;
+        let mut ap: Option<i32> = None;
+        let mut attack_type: Option<&str> = None;
+        let mut weak = vec![];
+        let mut immune = vec![];
+
+        for w in s.split_whitespace() {
+            if let Ok(v) = w.parse() {
+                val = Some(v);
+            }
+
+            match w {
+                "units" => {
+                    units = val.take();
+                }
+                "hit" => {
+                    hp = val.take();
+                }
+                "damage" => {
+                    attack_type = prev.take();
+                    ap = val.take();
+                }
+                _ => (),
+            }
+            prev = Some(w);
+        }
+
+        let initiative = val;
+
+        // Parse weak/immune
+        {
+            let weakto = s.split("weak to").nth(1);
+            if let Some(w) = weakto {
+                let wstr: String = w
+                    .chars()
+                    .take_while(|&c| c != ';' && c != ')')
+                    .filter(|&c| c != ',')
+                    .collect();
+                weak = wstr.split_whitespace().map(|s| s.to_owned()).collect();
+            }
+        }
+
+        {
+            let immuneto = s.split("immune to").nth(1);
+            if let Some(w) = immuneto {
+                let wstr: String = w
+                    .chars()
+                    .take_while(|&c| c != ';' && c != ')')
+                    .filter(|&c| c != ',')
+                    .collect();
+                immune = wstr.split_whitespace().map(|s| s.to_owned()).collect();
+            }
+        }
+
+        let missing = |what| ParseError::missing(what, s);
+
+        if [units, hp, ap].iter().any(|v| v.is_some_and(|v| v <= 0)) {
+            return Err(ParseError::invalid(
+                "units, hit points and damage have to be above 0",
+            ));
+        }
+
+        Ok(Group {
+            units: units.ok_or_else(|| missing("'N units'"))?,
+            hp: hp.ok_or_else(|| missing("'N hit points'"))?,
+            ap: ap.ok_or_else(|| missing("'N <type> damage'"))?,
+            weak,
+            immune,
+            attack_type: attack_type
+                .ok_or_else(|| missing("'N <type> damage'"))?
+                .to_owned(),
+            initiative: initiative.ok_or_else(|| missing("'initiative N'"))?,
+            tgt: None,
+            side: Side::ImmuneSystem,
+        })
+    }
+}
+
+impl fmt::Display for Group {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} units each with {} hit points ", self.units, self.hp)?;
+
+        let mut mods = vec![];
+        if !self.weak.is_empty() {
+            mods.push(format!("weak to {}", self.weak.join(", ")));
+        }
+        if !self.immune.is_empty() {
+            mods.push(format!("immune to {}", self.immune.join(", ")));
+        }
+        if !mods.is_empty() {
+            write!(f, "({}) ", mods.join("; "))?;
+        }
+
+        write!(
+            f,
+            "with an attack that does {} {} damage at initiative {}",
+            self.ap, self.attack_type, self.initiative
+        )
+    }
+}
+
+/// Fight it out, gets None if it's a deadlock
+fn run(grps: &mut HashMap<usize, Group>, boost: i32) -> Option<War> {
+    for g in grps.values_mut().filter(|g| g.side == Side::ImmuneSystem) {
+        g.ap = g.ap.saturating_add(boost);
+    }
+
+    loop {
+        // Sort by desc epower, initiative
+        let by_epower: Vec<usize> = {
+            let mut tmp: Vec<_> = grps.iter().collect();
+            tmp.sort_by(|(_, a), (_, b)| {
+                let ep_cmp = b.epower().cmp(&a.epower());
+                if let Ordering::Equal = ep_cmp {
+                    b.initiative.cmp(&a.initiative)
+                } else {
+                    ep_cmp
+                }
+            });
+            tmp.iter().map(|a| *a.0).collect()
+        };
+
+        let mut tgts: Vec<usize> = grps.keys().cloned().collect();
+
+        // Target the ids
+        for id in &by_epower {
+            let tgt = grps[id].target(grps, &tgts);
+            grps.get_mut(id).unwrap().tgt = tgt;
+            if let Some(t) = tgt {
+                tgts.retain(|&g| g != t);
+            }
+        }
+
+        // Sort by initiatives
+        let by_init: Vec<usize> = {
+            let mut tmp: Vec<_> = grps.iter().collect();
+            tmp.sort_by_key(|(_, g)| std::cmp::Reverse(g.initiative));
+            tmp.iter().map(|a| *a.0).collect()
+        };
+
+        let mut dead_sum = 0;
+        for id in &by_init {
+            if grps[id].units <= 0 {
+                continue;
+            }
+
+            if let Some(tgt_idx) = grps[id].tgt {
+                let dmg = grps[&tgt_idx].calc_dmg_from(&grps[id]);
+                dead_sum += grps.get_mut(&tgt_idx).unwrap().get_attacked(dmg);
+            }
+        }
+
+        grps.retain(|_, g| g.units > 0);
+
+        let units_a: i32 = grps
+            .values()
+            .filter(|g| g.side == Side::Infection)
+            .map(|g| g.units)
+            .sum();
+        let units_b: i32 = grps
+            .values()
+            .filter(|g| g.side == Side::ImmuneSystem)
+            .map(|g| g.units)
+            .sum();
+
+        if units_a == 0 {
+            return Some(War {
+                winner: Side::ImmuneSystem,
+                units: units_b,
+                boost,
+            });
+        }
+
+        if units_b == 0 {
+            return Some(War {
+                winner: Side::Infection,
+                units: units_a,
+                boost,
+            });
+        }
+
+        // Deadlock case
+        if dead_sum == 0 {
+            return None;
+        }
+    }
+}
+
+fn groups(input: &str) -> Result<HashMap<usize, Group>, ParseError> {
+    let mut grps = HashMap::new();
+
+    let mut id_gen = 0..;
+    let mut cur_side = None;
+
+    for (n, line) in input.lines().enumerate() {
+        match line.trim() {
+            "Immune System:" => cur_side = Some(Side::ImmuneSystem),
+            "Infection:" => cur_side = Some(Side::Infection),
+            "" => (),
+            _ => {
+                let side = cur_side.ok_or_else(|| {
+                    let first = Tokens::words(line).next().unwrap();
+                    ParseError::expected("'Immune System:' or 'Infection:' first", first)
+                });
+
+                let mut g: Group = side
+                    .and_then(|_| line.parse())
+                    .map_err(|e: ParseError| e.at_line(n + 1))?;
+                g.side = cur_side.unwrap();
+                grps.insert(id_gen.next().unwrap(), g);
+            }
+        }
+    }
+
+    Ok(grps)
+}
+
+fn deadlock() -> ParseError {
+    ParseError::invalid("neither side can kill any more units")
+}
+
+pub fn part1(input: &str) -> Result<War, ParseError> {
+    run(&mut groups(input)?, 0).ok_or_else(deadlock)
+}
+
+pub fn part2(input: &str) -> Result<War, ParseError> {
+    let grps = groups(input)?;
+
+    // With this much every hit the immune system lands wipes out a whole group, if
+    // that's not enough no boost is
+    let most = (grps.values())
+        .filter(|g| g.side == Side::Infection)
+        .map(|g| i64::from(g.units) * i64::from(g.hp))
+        .max()
+        .unwrap_or(0);
+    let most = i32::try_from(most).unwrap_or(i32::MAX);
+
+    let wins = |b| run(&mut grps.clone(), b).filter(|w| w.winner == Side::ImmuneSystem);
+    if wins(most).is_none() {
+        return Err(ParseError::invalid(
+            "the immune system can't win however big the boost",
+        ));
+    }
+    Ok((0..=most).find_map(wins).unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::fuzz::{check, fuzz, small, Rng};
+    use common::input::ex;
+
+    #[test]
+    fn examples() {
+        let w = part1(ex(EXAMPLES, "example")).unwrap();
+        assert_eq!((w.winner, w.units), (Side::Infection, 5216));
+
+        let w = part2(ex(EXAMPLES, "example")).unwrap();
+        assert_eq!((w.winner, w.units, w.boost), (Side::ImmuneSystem, 51, 1570));
+    }
+
+    #[test]
+    fn bad_input() {
+        let e = part1("Immune System:\n0 units each with 5 hit points with an attack that does 4 fire damage at initiative 2").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 2: units, hit points and damage have to be above 0"
+        );
+
+        // Nobody can hurt anybody
+        let war = "\
+Immune System:
+5 units each with 10 hit points (immune to fire) with an attack that does 4 fire damage at initiative 2
+
+Infection:
+5 units each with 10 hit points (immune to fire) with an attack that does 4 fire damage at initiative 1
+";
+        assert_eq!(
+            part1(war).unwrap_err().to_string(),
+            "neither side can kill any more units"
+        );
+        assert_eq!(
+            part2(war).unwrap_err().to_string(),
+            "the immune system can't win however big the boost"
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        const TYPES: &[&str] = &["bludgeoning", "cold", "fire", "radiation", "slashing"];
+
+        // Each type at most once, in any order
+        let types = |r: &mut Rng| {
+            let mut ts: Vec<String> = TYPES
+                .iter()
+                .filter(|_| r.one_in(3))
+                .map(|&t| t.to_owned())
+                .collect();
+            let n = ts.len().max(1);
+            ts.rotate_left(r.below(n));
+            ts
+        };
+
+        check(
+            1000,
+            |r| Group {
+                side: Side::ImmuneSystem,
+                units: r.range(1, 10_000) as i32,
+                hp: r.range(1, 100_000) as i32,
+                ap: r.range(1, 1000) as i32,
+                weak: types(r),
+                immune: types(r),
+                attack_type: r.pick(TYPES).to_string(),
+                initiative: r.range(1, 100) as i32,
+                tgt: None,
+            },
+            |g| assert_eq!(&g.to_string().parse::<Group>().unwrap(), g),
+        );
+    }
+
+    #[test]
+    fn fuzz_groups() {
+        let seeds: Vec<&str> = ex(EXAMPLES, "example").lines().collect();
+        fuzz(&seeds, 5000, |s| {
+            let _ = s.parse::<Group>();
+        });
+
+        // The example scaled down so the search for a boost doesn't take long
+        let small_war = "\
+Immune System:
+17 units each with 53 hit points (weak to radiation, bludgeoning) with an attack that does 45 fire damage at initiative 2
+98 units each with 12 hit points (immune to fire; weak to bludgeoning, slashing) with an attack that does 2 slashing damage at initiative 3
+
+Infection:
+80 units each with 47 hit points (weak to radiation) with an attack that does 11 bludgeoning damage at initiative 1
+44 units each with 29 hit points (immune to radiation; weak to fire, cold) with an attack that does 1 slashing damage at initiative 4
+";
+        fuzz(&[small_war], 1000, |s| {
+            if groups(s).is_ok() && small(s, 100) {
+                let _ = part1(s);
+                let _ = part2(s);
+            }
+        });
+    }
+}
@@ -0,0 +1,155 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use common::input::Example;
+use common::parse::{parse_lines, Tokens};
+use common::{m_dist, Manhattan, ParseError, Pos4};
+
+pub const EXAMPLES: &[Example] = &[
+    ("example1", include_str!("../examples/example1.txt")),
+    ("example2", include_str!("../examples/example2.txt")),
+    ("example3", include_str!("../examples/example3.txt")),
+    ("example4", include_str!("../examples/example4.txt")),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Pos(i32, i32, i32, i32);
+
+impl Manhattan for Pos {
+    fn m_dist(&self, other: &Pos) -> i32 {
+        let a: Pos4 = (self.0, self.1, self.2, self.3);
+        a.m_dist(&(other.0, other.1, other.2, other.3))
+    }
+}
+
+impl FromStr for Pos {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut it = Tokens::split(s, |c| c != ',' && !c.is_whitespace());
+
+        Ok(Pos(
+            it.parse_next("x")?,
+            it.parse_next("y")?,
+            it.parse_next("z")?,
+            it.parse_next("t")?,
+        ))
+    }
+}
+
+impl fmt::Display for Pos {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{},{},{},{}", self.0, self.1, self.2, self.3)
+    }
+}
+
+#[derive(Debug)]
+struct Const {
+    points: Vec<Pos>,
+}
+
+impl Const {
+    pub fn new() -> Const {
+        Const { points: vec![] }
+    }
+
+    fn dist_pos(&self, other: Pos) -> i32 {
+        self.points.iter().map(|&p| m_dist(p, other)).min().unwrap()
+    }
+
+    fn dist_con(&self, other: &Const) -> i32 {
+        other
+            .points
+            .iter()
+            .map(|&o| self.dist_pos(o))
+            .min()
+            .unwrap()
+    }
+}
+
+// There's no part 2 on the last day
+pub fn part1(input: &str) -> Result<usize, ParseError> {
+    let ps: Vec<Pos> = parse_lines(input)?;
+
+    let mut id_gen = 0..;
+    let mut consts: HashMap<_, Const> = HashMap::new();
+
+    for p in ps {
+        let mut closest = consts.values_mut().min_by_key(|c| c.dist_pos(p));
+
+        match closest {
+            Some(ref mut c) if c.dist_pos(p) <= 3 => c.points.push(p),
+            _ => {
+                let mut new_c = Const::new();
+                new_c.points.push(p);
+                consts.insert(id_gen.next().unwrap(), new_c);
+            }
+        }
+    }
+
+    loop {
+        let mut equil = true;
+        let keys: Vec<_> = consts.keys().cloned().collect();
+
+        for cur_key in &keys {
+            let mut cur = consts.remove(cur_key).unwrap();
+
+            let mut closest = consts.values_mut().min_by_key(|o| o.dist_con(&cur));
+
+            match closest {
+                Some(ref mut c) if c.dist_con(&cur) <= 3 => {
+                    c.points.append(&mut cur.points);
+                    equil = false;
+                }
+                _ => {
+                    consts.insert(*cur_key, cur);
+                }
+            }
+        }
+
+        if equil {
+            break;
+        }
+    }
+
+    Ok(consts.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::fuzz::{check, fuzz, fuzz_examples};
+    use common::input::ex;
+
+    #[test]
+    fn examples() {
+        let consts = [2, 4, 3, 8];
+
+        for (i, &c) in consts.iter().enumerate() {
+            let name = format!("example{}", i + 1);
+            assert_eq!(part1(ex(EXAMPLES, &name)).unwrap(), c, "{}", name);
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        check(
+            1000,
+            |r| Pos(r.i32(), r.i32(), r.i32(), r.i32()),
+            |p| assert_eq!(&p.to_string().parse::<Pos>().unwrap(), p),
+        );
+    }
+
+    #[test]
+    fn fuzz_pos() {
+        let seeds: Vec<&str> = EXAMPLES.iter().flat_map(|(_, e)| e.lines()).collect();
+        fuzz(&seeds, 5000, |s| {
+            let _ = s.parse::<Pos>();
+        });
+
+        fuzz_examples(EXAMPLES, |s| {
+            let _ = part1(s);
+        });
+    }
+}
@@ -0,0 +1,154 @@
+use std::fmt;
+use std::str::FromStr;
+
+use common::input::Example;
+use common::parse::{parse_lines, Tokens};
+use common::{Grid, ParseError, Pos};
+
+pub const EXAMPLES: &[Example] = &[("example", include_str!("../examples/example.txt"))];
+
+#[derive(Debug, PartialEq)]
+struct Rect {
+    id: usize,
+    corner: (usize, usize),
+    size: (usize, usize),
+}
+
+impl FromStr for Rect {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // #1 @ 1,3: 4x4
+        let mut it = Tokens::split(s, |c| !" #@,:x".contains(c));
+        let id = it.parse_next("id")?;
+        let corner = (it.parse_next("left edge")?, it.parse_next("top edge")?);
+        let size = (it.parse_next("width")?, it.parse_next("height")?);
+
+        Ok(Rect { id, corner, size })
+    }
+}
+
+impl fmt::Display for Rect {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "#{} @ {},{}: {}x{}",
+            self.id, self.corner.0, self.corner.1, self.size.0, self.size.1
+        )
+    }
+}
+
+#[derive(Debug)]
+struct Fabric {
+    grid: Grid<usize>,
+}
+
+impl Fabric {
+    fn new(w: usize, h: usize) -> Fabric {
+        Fabric {
+            grid: Grid::new(w, h, 0),
+        }
+    }
+
+    // Squares of the rect that are on the fabric
+    fn squares(&self, r: &Rect) -> impl Iterator<Item = Pos> {
+        let clip = |from: usize, len: usize, max: usize| {
+            from.min(max) as i32..from.saturating_add(len).min(max) as i32
+        };
+        let xs = clip(r.corner.0, r.size.0, self.grid.width());
+        let ys = clip(r.corner.1, r.size.1, self.grid.height());
+
+        ys.flat_map(move |y| xs.clone().map(move |x| (x, y)))
+    }
+
+    fn add_rect(&mut self, r: &Rect) {
+        for p in self.squares(r) {
+            self.grid[p] += 1;
+        }
+    }
+
+    fn count_overlap(&self) -> usize {
+        self.grid.values().filter(|&&v| v > 1).count()
+    }
+
+    fn is_safe(&self, r: &Rect) -> bool {
+        self.squares(r).all(|p| self.grid[p] == 1)
+    }
+}
+
+fn fabric(rects: &[Rect]) -> Fabric {
+    let mut f = Fabric::new(1000, 1000);
+
+    for r in rects {
+        f.add_rect(r);
+    }
+
+    f
+}
+
+pub fn part1(input: &str) -> Result<usize, ParseError> {
+    Ok(fabric(&parse_lines(input)?).count_overlap())
+}
+
+pub fn part2(input: &str) -> Result<usize, ParseError> {
+    let rects: Vec<Rect> = parse_lines(input)?;
+    if rects.is_empty() {
+        return Err(ParseError::empty("claim"));
+    }
+    let f = fabric(&rects);
+
+    rects
+        .iter()
+        .find(|r| f.is_safe(r))
+        .map(|r| r.id)
+        .ok_or_else(|| ParseError::invalid("every claim overlaps another"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::fuzz::{check, fuzz, fuzz_examples, small};
+    use common::input::ex;
+
+    #[test]
+    fn examples() {
+        assert_eq!(part1(ex(EXAMPLES, "example")).unwrap(), 4);
+        assert_eq!(part2(ex(EXAMPLES, "example")).unwrap(), 3);
+    }
+
+    #[test]
+    fn bad_input() {
+        let e = part2("").unwrap_err();
+        assert_eq!(e.to_string(), "line 1, column 1: missing claim");
+        let e = part2("#1 @ 0,0: 2x2\n#2 @ 1,1: 2x2\n").unwrap_err();
+        assert_eq!(e.to_string(), "every claim overlaps another");
+    }
+
+    #[test]
+    fn round_trip() {
+        check(
+            1000,
+            |r| Rect {
+                id: r.below(100_000),
+                corner: (r.below(1000), r.below(1000)),
+                size: (r.below(100), r.below(100)),
+            },
+            |rect| assert_eq!(&rect.to_string().parse::<Rect>().unwrap(), rect),
+        );
+    }
+
+    #[test]
+    fn fuzz_rect() {
+        let seeds: Vec<&str> = ex(EXAMPLES, "example").lines().collect();
+        fuzz(&seeds, 5000, |s| {
+            let _ = s.parse::<Rect>();
+        });
+
+        fuzz_examples(EXAMPLES, |s| {
+            if small(s, 100) {
+                let _ = part1(s);
+                let _ = part2(s);
+            }
+        });
+    }
+}
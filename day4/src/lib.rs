@@ -0,0 +1,323 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use common::input::Example;
+use common::output::{Answer, Extra};
+use common::parse::Tokens;
+use common::ParseError;
+
+pub const EXAMPLES: &[Example] = &[("example", include_str!("../examples/example.txt"))];
+
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Date {
+    yr: i64,
+    mth: i64,
+    day: i64,
+    hr: i64,
+    min: i64,
+}
+
+impl Date {
+    fn _as_mins(&self) -> i64 {
+        let days_in_month = match self.mth {
+            1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+            4 | 6 | 9 | 11 => 30,
+            2 => 28, // rip leap years
+            _ => panic!("invalid month"),
+        };
+
+        self.min
+            + (self.hr * 60)
+            + (self.day * 24 * 60)
+            + (self.mth * days_in_month * 24 * 60)
+            + (self.yr * 365 * 31 * 24 * 60)
+    }
+}
+
+impl FromStr for Date {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // [1518-11-01 00:00]
+        let mut it = Tokens::numbers(s);
+
+        let yr = it.parse_next("year")?;
+        let mth = it.parse_next("month")?;
+        let day = it.parse_next("day")?;
+        let hr = it.parse_next("hour")?;
+        let min = it.parse_next("minute")?;
+
+        Ok(Date {
+            yr,
+            mth,
+            day,
+            hr,
+            min,
+        })
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "[{:04}-{:02}-{:02} {:02}:{:02}]",
+            self.yr, self.mth, self.day, self.hr, self.min
+        )
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Action {
+    Begin,
+    Sleep,
+    WakeUp,
+}
+
+#[derive(Debug, PartialEq)]
+struct Entry {
+    date: Date,
+    act: Action,
+    id: Option<i64>,
+}
+
+impl FromStr for Entry {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let end = s
+            .find(']')
+            .ok_or_else(|| ParseError::missing("']' after the timestamp", s))?;
+        let date = s[..=end].parse()?;
+        let mut id = None;
+
+        let mut words = Tokens::words(s);
+        words.find(|w| w.text.ends_with(']'));
+
+        let tok = words.next_tok("action")?;
+        let act = match tok.text {
+            "Guard" => {
+                // The sixth number, after the timestamp
+                let tok = Tokens::numbers(s)
+                    .nth(5)
+                    .ok_or_else(|| ParseError::missing("guard id", s))?;
+                id = Some(tok.parse()?);
+                Action::Begin
+            }
+            "falls" => Action::Sleep,
+            "wakes" => Action::WakeUp,
+            _ => return Err(ParseError::expected("'Guard', 'falls' or 'wakes'", tok)),
+        };
+
+        Ok(Entry { date, act, id })
+    }
+}
+
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (&self.act, self.id) {
+            (Action::Begin, Some(id)) => write!(f, "{} Guard #{} begins shift", self.date, id),
+            (Action::Begin, None) => write!(f, "{} Guard #? begins shift", self.date),
+            (Action::Sleep, _) => write!(f, "{} falls asleep", self.date),
+            (Action::WakeUp, _) => write!(f, "{} wakes up", self.date),
+        }
+    }
+}
+
+// Get entries in order and with IDs set, along with their line numbers
+fn get_entries(input: &str) -> Result<Vec<(Entry, usize)>, ParseError> {
+    // Keep the line numbers from before sorting for the errors
+    let mut lines: Vec<(&str, usize)> = input.lines().zip(1..).collect();
+    lines.sort_unstable();
+    let mut entries = Vec::with_capacity(lines.len());
+    let mut cur_id = None;
+
+    for (l, n) in lines {
+        let mut e: Entry = l.parse().map_err(|e: ParseError| e.at_line(n))?;
+
+        // Propogate ids from Begin actions
+        cur_id = e.id.or(cur_id);
+        if cur_id.is_none() {
+            return Err(ParseError::missing("guard on shift before this", l).at_line(n));
+        }
+        e.id = cur_id;
+
+        entries.push((e, n));
+    }
+
+    if entries.is_empty() {
+        return Err(ParseError::empty("guard record"));
+    }
+    Ok(entries)
+}
+
+// Minutes slept, by guard
+fn sleep_mins(input: &str) -> Result<HashMap<Option<i64>, Vec<i64>>, ParseError> {
+    let entries = get_entries(input)?;
+    let mut sleep_mins = HashMap::new();
+
+    for pair in entries.windows(2) {
+        let ((e1, _), (e2, n)) = (&pair[0], &pair[1]);
+        if let (Action::Sleep, Action::WakeUp) = (&e1.act, &e2.act) {
+            let (min1, min2) = (e1.date.min, e2.date.min);
+            if !(0..60).contains(&min1) || !(0..60).contains(&min2) {
+                return Err(ParseError::invalid("sleeps outside the midnight hour").at(*n, 0));
+            }
+            if min1 >= min2 {
+                return Err(ParseError::invalid("wakes up before falling asleep").at(*n, 0));
+            }
+            for min in min1..min2 {
+                let v = sleep_mins.entry(e1.id).or_insert_with(Vec::new);
+                v.push(min);
+            }
+        }
+    }
+
+    if sleep_mins.is_empty() {
+        return Err(ParseError::invalid("no guard ever falls asleep"));
+    }
+    Ok(sleep_mins)
+}
+
+/// Guard and the minute they're most likely asleep, the answer is the two multiplied
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Guard {
+    pub id: i64,
+    pub minute: i64,
+}
+
+impl fmt::Display for Guard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.id * self.minute)
+    }
+}
+
+impl Answer for Guard {
+    fn extra(&self) -> Extra {
+        vec![("guard", self.id.into()), ("minute", self.minute.into())]
+    }
+}
+
+pub fn part1(input: &str) -> Result<Guard, ParseError> {
+    let sleep_mins = sleep_mins(input)?;
+
+    // Get max sleeper
+    let (id, mins) = sleep_mins
+        .iter()
+        .max_by(|(_, v1), (_, v2)| v1.len().cmp(&v2.len()))
+        .unwrap();
+
+    // Count occurences of minutes
+    let mut hist = HashMap::new();
+    for &min in mins.iter() {
+        let v = hist.entry(min).or_insert(0);
+        *v += 1;
+    }
+
+    let (&min, _) = hist.iter().max_by(|(_, v1), (_, v2)| v1.cmp(v2)).unwrap();
+
+    Ok(Guard {
+        id: id.unwrap(),
+        minute: min,
+    })
+}
+
+pub fn part2(input: &str) -> Result<Guard, ParseError> {
+    let sleep_mins = sleep_mins(input)?;
+
+    let mut most_mins = HashMap::new();
+    for (id, mins) in &sleep_mins {
+        let mut hist = HashMap::new();
+        for &min in mins.iter() {
+            let v = hist.entry(min).or_insert(0);
+            *v += 1;
+        }
+
+        let (&min, &cnt) = hist.iter().max_by(|(_, v1), (_, v2)| v1.cmp(v2)).unwrap();
+
+        most_mins.insert(id, (min, cnt));
+    }
+
+    let (&id, (min, _)) = most_mins
+        .iter()
+        .max_by(|(_, (_, v1)), (_, (_, v2))| v1.cmp(v2))
+        .unwrap();
+
+    Ok(Guard {
+        id: id.unwrap(),
+        minute: *min,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::fuzz::{check, fuzz, fuzz_examples};
+    use common::input::ex;
+
+    #[test]
+    fn examples() {
+        let g = part1(ex(EXAMPLES, "example")).unwrap();
+        assert_eq!((g.id, g.minute, g.to_string()), (10, 24, "240".to_owned()));
+
+        let g = part2(ex(EXAMPLES, "example")).unwrap();
+        assert_eq!((g.id, g.minute, g.to_string()), (99, 45, "4455".to_owned()));
+    }
+
+    #[test]
+    fn round_trip() {
+        check(
+            1000,
+            |r| {
+                let date = Date {
+                    yr: r.range(0, 9999),
+                    mth: r.range(1, 12),
+                    day: r.range(1, 31),
+                    hr: r.range(0, 23),
+                    min: r.range(0, 59),
+                };
+                let (act, id) = match r.below(3) {
+                    0 => (Action::Begin, Some(r.range(0, 99_999))),
+                    1 => (Action::Sleep, None),
+                    _ => (Action::WakeUp, None),
+                };
+                Entry { date, act, id }
+            },
+            |e| assert_eq!(&e.to_string().parse::<Entry>().unwrap(), e),
+        );
+    }
+
+    #[test]
+    fn bad_input() {
+        let e = part1("").unwrap_err();
+        assert_eq!(e.to_string(), "line 1, column 1: missing guard record");
+
+        let e = part2("[1518-11-01 00:00] Guard #10 begins shift\n").unwrap_err();
+        assert_eq!(e.to_string(), "no guard ever falls asleep");
+
+        let input = "[1518-11-01 00:00] Guard #10 begins shift
+[1518-11-01 00:30] falls asleep
+[1518-11-01 01:10] wakes up
+";
+        let e = part1(input).unwrap_err();
+        assert_eq!(e.to_string(), "line 3: wakes up before falling asleep");
+
+        let e = part2(&input.replace("01:10", "00:99")).unwrap_err();
+        assert_eq!(e.to_string(), "line 3: sleeps outside the midnight hour");
+    }
+
+    #[test]
+    fn fuzz_entries() {
+        let seeds: Vec<&str> = ex(EXAMPLES, "example").lines().collect();
+        fuzz(&seeds, 5000, |s| {
+            let _ = s.parse::<Entry>();
+        });
+
+        fuzz_examples(EXAMPLES, |s| {
+            if get_entries(s).is_ok() {
+                let _ = part1(s);
+                let _ = part2(s);
+            }
+        });
+    }
+}
@@ -0,0 +1,88 @@
+use std::collections::HashMap;
+
+use common::input::Example;
+use common::ParseError;
+
+pub const EXAMPLES: &[Example] = &[("example", include_str!("../examples/example.txt"))];
+
+fn process(input: &[char]) -> Vec<char> {
+    let mut polymer = input.to_vec();
+    loop {
+        let mut polymer_new = Vec::new();
+        let mut it_1 = polymer.iter();
+        let mut it_2 = polymer.iter().skip(1);
+
+        loop {
+            match (it_1.next(), it_2.next()) {
+                (Some(p0), Some(p1)) => {
+                    if p0 != p1 && p0.eq_ignore_ascii_case(p1) {
+                        // Skip next cycle so we don't handle p1 again
+                        it_1.next();
+                        it_2.next();
+                    } else {
+                        polymer_new.push(*p0);
+                    }
+                }
+                (Some(p0), None) => polymer_new.push(*p0),
+                (None, Some(_)) => unreachable!(),
+                (None, None) => break, // Reached the end
+            }
+        }
+
+        if polymer.len() == polymer_new.len() {
+            break;
+        }
+
+        polymer = polymer_new;
+    }
+    polymer
+}
+
+fn polymer(input: &str) -> Result<Vec<char>, ParseError> {
+    let polymer: Vec<char> = input.trim().chars().collect();
+    if polymer.is_empty() {
+        return Err(ParseError::empty("polymer"));
+    }
+    Ok(polymer)
+}
+
+pub fn part1(input: &str) -> Result<usize, ParseError> {
+    Ok(process(&polymer(input)?).len())
+}
+
+pub fn part2(input: &str) -> Result<usize, ParseError> {
+    let polymer_org = polymer(input)?;
+    let mut results = HashMap::new();
+
+    for &c in &polymer_org {
+        let lc = c.to_ascii_lowercase();
+        results.entry(lc).or_insert_with(|| {
+            let clean: Vec<char> = polymer_org
+                .iter()
+                .filter(|&&p| !c.eq_ignore_ascii_case(&p))
+                .cloned()
+                .collect();
+            process(&clean).len()
+        });
+    }
+
+    Ok(*results.values().min().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::input::ex;
+
+    #[test]
+    fn examples() {
+        assert_eq!(part1(ex(EXAMPLES, "example")).unwrap(), 10);
+        assert_eq!(part2(ex(EXAMPLES, "example")).unwrap(), 4);
+    }
+
+    #[test]
+    fn bad_input() {
+        let e = part2("\n").unwrap_err();
+        assert_eq!(e.to_string(), "line 1, column 1: missing polymer");
+    }
+}
@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use common::input::Example;
+use common::parse::{parse_lines, Tokens};
+use common::{m_dist, Manhattan, ParseError};
+
+pub const EXAMPLES: &[Example] = &[("example", include_str!("../examples/example.txt"))];
+
+#[derive(Debug, PartialEq)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+impl FromStr for Point {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut it = Tokens::split(s, |c| c != ',' && !c.is_whitespace());
+        let x = it.parse_next("x")?;
+        let y = it.parse_next("y")?;
+        Ok(Point { x, y })
+    }
+}
+
+impl fmt::Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}, {}", self.x, self.y)
+    }
+}
+
+impl Manhattan for Point {
+    fn m_dist(&self, other: &Point) -> i32 {
+        (self.x, self.y).m_dist(&(other.x, other.y))
+    }
+}
+
+fn points(input: &str) -> Result<Vec<Point>, ParseError> {
+    let points: Vec<Point> = parse_lines(input)?;
+    if points.is_empty() {
+        return Err(ParseError::empty("point"));
+    }
+    Ok(points)
+}
+
+fn bounds(points: &[Point]) -> (Point, Point) {
+    let min = Point {
+        x: points.iter().map(|p| p.x).min().unwrap(),
+        y: points.iter().map(|p| p.y).min().unwrap(),
+    };
+
+    let max = Point {
+        x: points.iter().map(|p| p.x).max().unwrap(),
+        y: points.iter().map(|p| p.y).max().unwrap(),
+    };
+
+    (min, max)
+}
+
+pub fn part1(input: &str) -> Result<usize, ParseError> {
+    let points = points(input)?;
+    let (min, max) = bounds(&points);
+    let (min_x, min_y, max_x, max_y) = (min.x, min.y, max.x, max.y);
+
+    let mut map_a = vec![];
+    let mut map_b = vec![];
+
+    // just yolo a size
+    for y in (min_y - 10)..(max_y + 10) {
+        for x in (min_x - 10)..(max_x + 10) {
+            let p = Point { x, y };
+            let mut dists: Vec<_> = points.iter().map(|p2| m_dist(&p, p2)).enumerate().collect();
+            dists.sort_by_key(|d| d.1);
+
+            if dists.get(1).map(|d| d.1) != Some(dists[0].1) {
+                let closest = dists[0].0;
+                if (x > min_x && x < max_x) && (y > min_y && y < max_y) {
+                    map_a.push(closest);
+                }
+                map_b.push(closest);
+            }
+        }
+    }
+
+    let mut results_a = HashMap::new();
+    let mut results_b = HashMap::new();
+
+    for &id in map_a.iter() {
+        let v = results_a.entry(id).or_insert(0);
+        *v += 1;
+    }
+
+    for &id in map_b.iter() {
+        let v = results_b.entry(id).or_insert(0);
+        *v += 1;
+    }
+
+    results_a
+        .iter()
+        .filter(|(k, v)| results_b[k] == **v)
+        .map(|(_, &area)| area)
+        .max()
+        .ok_or_else(|| ParseError::invalid("every area is infinite"))
+}
+
+// Not a correct solution but worked for the given input
+fn safe_region(input: &str, max_dist: i32) -> Result<usize, ParseError> {
+    let points = points(input)?;
+    let (min, max) = bounds(&points);
+    let mut map = vec![];
+
+    for y in (min.y - 10)..(max.y + 10) {
+        for x in (min.x - 10)..(max.x + 10) {
+            let p = Point { x, y };
+            let sum = points.iter().map(|p2| m_dist(&p, p2)).sum::<i32>();
+            map.push(sum);
+        }
+    }
+
+    Ok(map.iter().filter(|&&d| d < max_dist).count())
+}
+
+pub fn part2(input: &str) -> Result<usize, ParseError> {
+    safe_region(input, 10000)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::fuzz::{check, fuzz, fuzz_examples, small};
+    use common::input::ex;
+
+    #[test]
+    fn examples() {
+        assert_eq!(part1(ex(EXAMPLES, "example")).unwrap(), 17);
+        // The example uses 32 instead of 10000
+        assert_eq!(safe_region(ex(EXAMPLES, "example"), 32).unwrap(), 16);
+    }
+
+    #[test]
+    fn bad_input() {
+        let e = part1("").unwrap_err();
+        assert_eq!(e.to_string(), "line 1, column 1: missing point");
+        let e = part1("1, 1\n").unwrap_err();
+        assert_eq!(e.to_string(), "every area is infinite");
+    }
+
+    #[test]
+    fn round_trip() {
+        check(
+            1000,
+            |r| Point {
+                x: r.i32(),
+                y: r.i32(),
+            },
+            |p| assert_eq!(&p.to_string().parse::<Point>().unwrap(), p),
+        );
+    }
+
+    #[test]
+    fn fuzz_point() {
+        let seeds: Vec<&str> = ex(EXAMPLES, "example").lines().collect();
+        fuzz(&seeds, 5000, |s| {
+            let _ = s.parse::<Point>();
+        });
+
+        fuzz_examples(EXAMPLES, |s| {
+            if small(s, 100) {
+                let _ = part1(s);
+                let _ = part2(s);
+            }
+        });
+    }
+}
@@ -0,0 +1,245 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+use common::input::Example;
+use common::parse::{parse_lines, Token, Tokens};
+use common::ParseError;
+
+pub const EXAMPLES: &[Example] = &[("example", include_str!("../examples/example.txt"))];
+
+#[derive(Debug, PartialEq)]
+struct Edge {
+    u: char,
+    v: char,
+}
+
+fn step(tok: Token) -> Result<char, ParseError> {
+    match tok.parse()? {
+        c @ 'A'..='Z' => Ok(c),
+        _ => Err(ParseError::expected("step letter", tok)),
+    }
+}
+
+impl FromStr for Edge {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Step C must be finished before step A can begin.
+        let mut it = Tokens::words(s);
+        it.next_tok("'Step'")?.expect("Step")?;
+        let u = step(it.next_tok("step")?)?;
+        let v = step(it.nth(5).ok_or_else(|| ParseError::missing("step", s))?)?;
+        Ok(Edge { u, v })
+    }
+}
+
+impl fmt::Display for Edge {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Step {} must be finished before step {} can begin.",
+            self.u, self.v
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Worker {
+    busy_until: i32,
+    task: Option<char>,
+}
+
+impl Worker {
+    pub fn new() -> Worker {
+        Worker {
+            busy_until: -1,
+            task: None,
+        }
+    }
+
+    fn work(&mut self, time: i32, task: char, base: i32) {
+        assert!(self.avail(time));
+
+        let amount: i32 = ((task as u8) - b'A' + 1) as i32;
+        self.busy_until = time + base + amount;
+        self.task = Some(task);
+    }
+
+    fn avail(&self, time: i32) -> bool {
+        self.busy_until <= time
+    }
+
+    fn output(&mut self, time: i32) -> Option<char> {
+        if !self.avail(time) {
+            return None;
+        }
+
+        // Return if there is a task
+        let tmp = self.task;
+        self.task = None;
+        tmp
+    }
+}
+
+type Deps = HashMap<char, HashSet<char>>;
+
+fn circular() -> ParseError {
+    ParseError::invalid("the steps depend on each other in a circle")
+}
+
+// Get the dependency lists, every step has one even if it's empty
+fn deps(input: &str) -> Result<Deps, ParseError> {
+    let edges: Vec<Edge> = parse_lines(input)?;
+    if edges.is_empty() {
+        return Err(ParseError::empty("step"));
+    }
+
+    let mut deps = HashMap::new(); // dependencies
+    for e in &edges {
+        deps.entry(e.u).or_insert_with(HashSet::new);
+        deps.entry(e.v).or_insert_with(HashSet::new).insert(e.u);
+    }
+
+    Ok(deps)
+}
+
+pub fn part1(input: &str) -> Result<String, ParseError> {
+    let mut deps = deps(input)?;
+    let mut visited = Vec::new();
+
+    while !deps.is_empty() {
+        let vis = deps
+            .iter()
+            .filter(|kv| kv.1.is_empty()) // Get nodes with no dependencies left
+            .map(|kv| *kv.0)
+            .min() // Sort by alphabetical
+            .ok_or_else(circular)?;
+
+        visited.push(vis);
+        deps.remove(&vis);
+
+        // Remove visited node from dependencies of others
+        for d in deps.values_mut() {
+            d.remove(&vis);
+        }
+    }
+
+    Ok(visited.iter().collect())
+}
+
+// Time to assemble with a number of workers and base time per step
+fn assemble(input: &str, workers: usize, base: i32) -> Result<i32, ParseError> {
+    let mut deps = deps(input)?;
+    let mut workers = vec![Worker::new(); workers.max(1)];
+
+    for time in 0.. {
+        let output = workers.iter_mut().filter_map(|w| w.output(time));
+
+        // Remove finished tasks from dependencies
+        for o in output {
+            for d in deps.values_mut() {
+                d.remove(&o);
+            }
+        }
+
+        for w in workers.iter_mut().filter(|w| w.avail(time)) {
+            // We end up sorting every time but whatever
+            let next = deps
+                .iter()
+                .filter(|kv| kv.1.is_empty()) // Get nodes with no dependencies left
+                .min_by_key(|kv| kv.0) // Sort by alphabetical
+                .map(|kv| *kv.0);
+
+            if let Some(id) = next {
+                deps.remove(&id);
+                w.work(time, id, base);
+            } else {
+                break;
+            }
+        }
+
+        if workers.iter().all(|w| w.avail(time)) {
+            if deps.is_empty() {
+                // All tasks done, no more tasks in queue
+                return Ok(time);
+            }
+            // Nothing on the go and nothing ready to start
+            return Err(circular());
+        }
+    }
+
+    unreachable!()
+}
+
+pub fn part2(input: &str) -> Result<i32, ParseError> {
+    assemble(input, 5, 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::fuzz::{check, fuzz, fuzz_examples, Rng};
+    use common::input::ex;
+
+    #[test]
+    fn examples() {
+        assert_eq!(part1(ex(EXAMPLES, "example")).unwrap(), "CABDFE");
+        // Two workers and no base time in the example
+        assert_eq!(assemble(ex(EXAMPLES, "example"), 2, 0).unwrap(), 15);
+    }
+
+    #[test]
+    fn bad_input() {
+        let e = part1("").unwrap_err();
+        assert_eq!(e.to_string(), "line 1, column 1: missing step");
+
+        let e = part1("Step a must be finished before step B can begin.").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 1, column 6: expected step letter at 'a'"
+        );
+
+        let input = "Step A must be finished before step B can begin.
+Step B must be finished before step A can begin.
+";
+        let msg = "the steps depend on each other in a circle";
+        assert_eq!(part1(input).unwrap_err().to_string(), msg);
+        assert_eq!(part2(input).unwrap_err().to_string(), msg);
+
+        // Two steps to start with is fine
+        let input = "Step B must be finished before step C can begin.
+Step A must be finished before step C can begin.
+";
+        assert_eq!(part1(input).unwrap(), "ABC");
+        assert_eq!(assemble(input, 2, 0).unwrap(), 5);
+    }
+
+    #[test]
+    fn round_trip() {
+        let step = |r: &mut Rng| (b'A' + r.below(26) as u8) as char;
+        check(
+            1000,
+            |r| Edge {
+                u: step(r),
+                v: step(r),
+            },
+            |e| assert_eq!(&e.to_string().parse::<Edge>().unwrap(), e),
+        );
+    }
+
+    #[test]
+    fn fuzz_edge() {
+        let seeds: Vec<&str> = ex(EXAMPLES, "example").lines().collect();
+        fuzz(&seeds, 5000, |s| {
+            let _ = s.parse::<Edge>();
+        });
+
+        fuzz_examples(EXAMPLES, |s| {
+            if deps(s).is_ok() {
+                let _ = part1(s);
+                let _ = part2(s);
+            }
+        });
+    }
+}
@@ -0,0 +1,133 @@
+use common::input::Example;
+use common::parse::{Token, Tokens};
+use common::ParseError;
+
+pub const EXAMPLES: &[Example] = &[("example", include_str!("../examples/example.txt"))];
+
+#[derive(Debug, PartialEq)]
+struct Node {
+    children: Vec<Node>,
+    meta: Vec<usize>,
+}
+
+fn sum_meta(node: &Node) -> usize {
+    node.children.iter().map(sum_meta).sum::<usize>() + node.meta.iter().sum::<usize>()
+}
+
+fn node_value(node: &Node) -> usize {
+    if node.children.is_empty() {
+        return node.meta.iter().sum();
+    }
+
+    let child_vals: Vec<usize> = node.children.iter().map(node_value).collect();
+    node.meta
+        .iter()
+        .filter_map(|m| m.checked_sub(1).and_then(|i| child_vals.get(i)))
+        .sum()
+}
+
+/// The numbers of the input with their line numbers
+struct Nums<'a> {
+    toks: Vec<(usize, Token<'a>)>,
+    next: usize,
+    /// Last line and its number, where anything missing gets reported
+    last: (usize, &'a str),
+}
+
+impl<'a> Nums<'a> {
+    fn parse_next(&mut self, what: &'static str) -> Result<usize, ParseError> {
+        let (line, tok) = match self.toks.get(self.next) {
+            Some(&t) => t,
+            None => return Err(ParseError::missing(what, self.last.1).at_line(self.last.0)),
+        };
+        self.next += 1;
+        tok.parse().map_err(|e: ParseError| e.at_line(line))
+    }
+
+    fn node(&mut self) -> Result<Node, ParseError> {
+        let children = self.parse_next("child count")?;
+        let meta_cnt = self.parse_next("metadata count")?;
+
+        let children = (0..children)
+            .map(|_| self.node())
+            .collect::<Result<_, _>>()?;
+        let meta = (0..meta_cnt)
+            .map(|_| self.parse_next("metadata"))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Node { children, meta })
+    }
+}
+
+fn tree(input: &str) -> Result<Node, ParseError> {
+    let mut nums = Nums {
+        toks: (input.lines().zip(1..))
+            .flat_map(|(l, n)| Tokens::words(l).map(move |t| (n, t)))
+            .collect(),
+        next: 0,
+        last: (input.lines().zip(1..).last())
+            .map(|(l, n)| (n, l))
+            .unwrap_or((1, "")),
+    };
+
+    let root = nums.node()?;
+    if let Some(&(line, tok)) = nums.toks.get(nums.next) {
+        return Err(ParseError::expected("nothing after the root node", tok).at_line(line));
+    }
+
+    Ok(root)
+}
+
+pub fn part1(input: &str) -> Result<usize, ParseError> {
+    Ok(sum_meta(&tree(input)?))
+}
+
+pub fn part2(input: &str) -> Result<usize, ParseError> {
+    Ok(node_value(&tree(input)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::fuzz::fuzz_examples;
+    use common::input::ex;
+
+    #[test]
+    fn examples() {
+        assert_eq!(part1(ex(EXAMPLES, "example")).unwrap(), 138);
+        assert_eq!(part2(ex(EXAMPLES, "example")).unwrap(), 66);
+    }
+
+    #[test]
+    fn bad_input() {
+        let e = part1("").unwrap_err();
+        assert_eq!(e.to_string(), "line 1, column 1: missing child count");
+        let e = part1("1 1\n").unwrap_err();
+        assert_eq!(e.to_string(), "line 1, column 4: missing child count");
+        let e = part2("0 2 1\n").unwrap_err();
+        assert_eq!(e.to_string(), "line 1, column 6: missing metadata");
+        let e = part2("0 1 1\n2\n").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 2, column 1: expected nothing after the root node at '2'"
+        );
+        let e = part1("0 1 x\n").unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "line 1, column 5: invalid digit found in string at 'x'"
+        );
+
+        // Metadata 0 points at no child
+        assert_eq!(part2("1 1 0 1 5 0").unwrap(), 0);
+    }
+
+    #[test]
+    fn fuzz_tree() {
+        fuzz_examples(EXAMPLES, |s| {
+            if tree(s).is_ok() {
+                let _ = part1(s);
+                let _ = part2(s);
+            }
+        });
+    }
+}
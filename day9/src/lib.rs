@@ -0,0 +1,135 @@
+use common::input::Example;
+use common::parse::Tokens;
+use common::ParseError;
+
+pub const EXAMPLES: &[Example] = &[
+    ("example1", include_str!("../examples/example1.txt")),
+    ("example2", include_str!("../examples/example2.txt")),
+    ("example3", include_str!("../examples/example3.txt")),
+    ("example4", include_str!("../examples/example4.txt")),
+    ("example5", include_str!("../examples/example5.txt")),
+    ("example6", include_str!("../examples/example6.txt")),
+];
+
+#[derive(Debug)]
+struct Node {
+    dat: usize,
+    next: usize,
+    prev: usize,
+}
+
+fn nth_fwd(circle: &[Node], id: usize, n: usize) -> usize {
+    let mut cur = id;
+    for _ in 0..n {
+        cur = circle[cur].next;
+    }
+    cur
+}
+
+fn nth_back(circle: &[Node], id: usize, n: usize) -> usize {
+    let mut cur = id;
+    for _ in 0..n {
+        cur = circle[cur].prev;
+    }
+    cur
+}
+
+fn insert_after(circle: &mut Vec<Node>, id: usize, v: usize) -> usize {
+    let new_id = circle.len();
+    let old_next = circle[id].next;
+    circle[id].next = new_id;
+    circle[old_next].prev = new_id;
+    circle.push(Node {
+        dat: v,
+        next: old_next,
+        prev: id,
+    });
+    new_id
+}
+
+fn remove(circle: &mut [Node], id: usize) -> usize {
+    let prev = circle[id].prev;
+    let next = circle[id].next;
+    circle[prev].next = next;
+    circle[next].prev = prev;
+    circle[id].dat
+}
+
+fn play(players: usize, points: usize) -> usize {
+    let mut scores = vec![0; players];
+
+    let mut circle = Vec::new();
+    circle.push(Node {
+        dat: 0,
+        next: 0,
+        prev: 0,
+    });
+
+    let turns = (0..players).cycle();
+    let mut marbles = 1..=points;
+
+    let mut cur = 0;
+
+    for t in turns {
+        let marble = {
+            if let Some(m) = marbles.next() {
+                m
+            } else {
+                break;
+            }
+        };
+
+        if marble % 23 != 0 {
+            let next = nth_fwd(&circle, cur, 1);
+            cur = insert_after(&mut circle, next, marble);
+        } else {
+            let next = nth_back(&circle, cur, 7);
+            cur = nth_fwd(&circle, next, 1);
+            let removed = remove(&mut circle, next);
+            scores[t] += marble + removed;
+        }
+    }
+
+    *scores.iter().max().unwrap()
+}
+
+// 10 players; last marble is worth 1618 points
+fn game(input: &str) -> Result<(usize, usize), ParseError> {
+    let line = input.lines().next().unwrap_or("");
+    let mut it = Tokens::words(line);
+
+    let players = it.parse_next("player count").map_err(|e| e.at_line(1))?;
+    let points = it
+        .nth(5)
+        .ok_or_else(|| ParseError::missing("points", line))
+        .and_then(|t| t.parse())
+        .map_err(|e| e.at_line(1))?;
+
+    Ok((players, points))
+}
+
+pub fn part1(input: &str) -> Result<usize, ParseError> {
+    let (players, points) = game(input)?;
+    Ok(play(players, points))
+}
+
+pub fn part2(input: &str) -> Result<usize, ParseError> {
+    let (players, points) = game(input)?;
+    Ok(play(players, points * 100))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::input::ex;
+
+    #[test]
+    fn examples() {
+        let scores = [32, 8317, 146373, 2764, 54718, 37305];
+
+        for (i, &score) in scores.iter().enumerate() {
+            let name = format!("example{}", i + 1);
+            assert_eq!(part1(ex(EXAMPLES, &name)).unwrap(), score, "{}", name);
+        }
+    }
+}
@@ -0,0 +1,473 @@
+//! Assembly for ElfCode, the plain format plus names. A line is a label, a directive
+//! or an instruction, and `;` starts a comment:
+//!
+//! ```text
+//! #ip 2                   ; binds r2, which can then be called ip
+//! .reg n 1                ; n is r1
+//! .const limit 10
+//!         seti limit _ n
+//! top:    addi r0 1 r0
+//!         gtrr r0 n r3
+//!         addr r3 ip ip
+//!         jmp top         ; seti top-1 0 ip
+//! ```
+//!
+//! Registers are `rN`, a `.reg` name, `ip` or a plain number. Immediates are numbers,
+//! constants or labels, with an optional `+N` or `-N`, and `_` is 0 where the operand
+//! is ignored. A label is the address of the instruction after it.
+
+use std::collections::{BTreeSet, HashMap};
+use std::convert::TryFrom;
+use std::fmt::Write;
+
+use common::parse::{ErrorKind, Token, Tokens};
+use common::ParseError;
+
+use crate::op::{Inst, Opcode, Src};
+use crate::program::Program;
+
+#[derive(Debug, Clone, Copy)]
+enum Sym {
+    Reg(i32),
+    /// Constants and labels
+    Value(i32),
+}
+
+/// An instruction's tokens, kept for the second pass once all the labels are known
+struct Line<'a> {
+    no: usize,
+    code: &'a str,
+    toks: Vec<Token<'a>>,
+}
+
+fn expected(what: &'static str, tok: Token) -> ParseError {
+    ParseError::new(ErrorKind::Expected(what), tok.col, tok.text)
+}
+
+/// `r3` as 3
+fn numbered_reg(s: &str) -> Option<i32> {
+    let n = s.strip_prefix('r')?;
+    if n.is_empty() || !n.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    n.parse().ok()
+}
+
+/// Names start with a letter or '_' and can't be something that already means something
+fn valid_name(s: &str) -> bool {
+    let mut chars = s.chars();
+    let first = chars.next().is_some_and(|c| c.is_alphabetic() || c == '_');
+
+    first
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
+        && !matches!(s, "_" | "ip" | "jmp")
+        && numbered_reg(s).is_none()
+        && Opcode::from_name(s).is_none()
+}
+
+#[derive(Default)]
+struct Syms(HashMap<String, Sym>);
+
+impl Syms {
+    fn define(&mut self, tok: Token, sym: Sym) -> Result<(), ParseError> {
+        if !valid_name(tok.text) || self.0.contains_key(tok.text) {
+            return Err(expected("a name that isn't taken", tok));
+        }
+        self.0.insert(tok.text.to_owned(), sym);
+        Ok(())
+    }
+
+    fn reg(&self, tok: Token) -> Result<i32, ParseError> {
+        if let Some(r) = numbered_reg(tok.text) {
+            return Ok(r);
+        }
+        if let Ok(r) = tok.text.parse() {
+            return Ok(r);
+        }
+
+        match self.0.get(tok.text) {
+            Some(&Sym::Reg(r)) => Ok(r),
+            _ => Err(expected("register", tok)),
+        }
+    }
+
+    /// A number, or a name with an optional offset like `top-1`
+    fn imm(&self, tok: Token) -> Result<i32, ParseError> {
+        if let Ok(v) = tok.text.parse() {
+            return Ok(v);
+        }
+
+        let split = (tok.text.char_indices().skip(1))
+            .find(|&(_, c)| c == '+' || c == '-')
+            .map_or(tok.text.len(), |(i, _)| i);
+        let (name, offset) = tok.text.split_at(split);
+
+        let base = match self.0.get(name) {
+            Some(&Sym::Value(v)) => v,
+            _ => return Err(expected("number, constant or label", tok)),
+        };
+        let offset: i32 = match offset {
+            "" => 0,
+            o => o
+                .parse()
+                .map_err(|e| ParseError::new(e, tok.col, tok.text))?,
+        };
+
+        base.checked_add(offset)
+            .ok_or_else(|| expected("an immediate that fits", tok))
+    }
+}
+
+/// The next token or an error pointing past the end of `code`
+fn operand<'a>(
+    toks: &mut impl Iterator<Item = Token<'a>>,
+    what: &'static str,
+    code: &str,
+) -> Result<Token<'a>, ParseError> {
+    toks.next().ok_or_else(|| ParseError::missing(what, code))
+}
+
+fn end_of_line<'a>(mut toks: impl Iterator<Item = Token<'a>>) -> Result<(), ParseError> {
+    match toks.next() {
+        Some(tok) => Err(expected("end of line", tok)),
+        None => Ok(()),
+    }
+}
+
+/// Lower the assembly in `src` to a plain program
+pub fn assemble(src: &str) -> Result<Program, ParseError> {
+    let mut program = Program::default();
+    let mut syms = Syms::default();
+    let mut lines = vec![];
+
+    // Labels and directives first, so instructions can jump forwards
+    for (n, line) in src.lines().enumerate() {
+        let no = n + 1;
+        let code = line.split(';').next().unwrap();
+        let mut toks = Tokens::words(code).peekable();
+
+        let directive = || -> Result<(), ParseError> {
+            while let Some(tok) = toks.next_if(|t| t.text.ends_with(':')) {
+                let name = Token {
+                    text: &tok.text[..tok.text.len() - 1],
+                    ..tok
+                };
+                let pc =
+                    i32::try_from(lines.len()).map_err(|_| expected("a shorter program", tok))?;
+                syms.define(name, Sym::Value(pc))?;
+            }
+
+            let first = match toks.peek() {
+                Some(&t) => t,
+                None => return Ok(()),
+            };
+
+            match first.text {
+                "#ip" => {
+                    toks.next();
+                    if program.ip.is_some() || !lines.is_empty() {
+                        return Err(expected("#ip only once and before the code", first));
+                    }
+                    let tok = operand(&mut toks, "ip register", code)?;
+                    let r = syms.reg(tok)?;
+                    let r = usize::try_from(r).map_err(|_| expected("register", tok))?;
+                    program.ip = Some(r);
+                    syms.0.insert("ip".to_owned(), Sym::Reg(r as i32));
+                }
+                ".reg" | ".const" => {
+                    toks.next();
+                    let name = operand(&mut toks, "name", code)?;
+                    let value = operand(&mut toks, "value", code)?;
+                    let sym = if first.text == ".reg" {
+                        Sym::Reg(syms.reg(value)?)
+                    } else {
+                        Sym::Value(syms.imm(value)?)
+                    };
+                    syms.define(name, sym)?;
+                }
+                t if t.starts_with('.') || t.starts_with('#') => {
+                    return Err(expected("#ip, .reg or .const", first))
+                }
+                _ => {
+                    lines.push(Line {
+                        no,
+                        code,
+                        toks: toks.by_ref().collect(),
+                    });
+                    return Ok(());
+                }
+            }
+
+            end_of_line(toks)
+        };
+
+        directive().map_err(|e| e.at_line(no))?;
+    }
+
+    for line in lines {
+        let inst = lower(&line, &syms, program.ip).map_err(|e| e.at_line(line.no))?;
+        program.insts.push(inst);
+    }
+
+    Ok(program)
+}
+
+fn lower(line: &Line, syms: &Syms, ip: Option<usize>) -> Result<Inst, ParseError> {
+    let mut toks = line.toks.iter().copied();
+    let op = toks.next().unwrap();
+
+    // seti target-1 0 ip, the +1 after it lands on the target
+    if op.text == "jmp" {
+        let ip = ip.ok_or_else(|| expected("#ip before a jmp", op))?;
+        let tok = operand(&mut toks, "target", line.code)?;
+        let to = syms.imm(tok)?;
+        let to = (to.checked_sub(1)).ok_or_else(|| expected("an immediate that fits", tok))?;
+        end_of_line(toks)?;
+        return Ok(Inst::new(Opcode::Seti, [to, 0, ip as i32]));
+    }
+
+    let code = Opcode::from_name(op.text).ok_or_else(|| expected("opcode", op))?;
+    let (sa, sb) = code.operands();
+
+    let mut arg = |src, what| {
+        let tok = operand(&mut toks, what, line.code)?;
+        match src {
+            Src::Reg => syms.reg(tok),
+            Src::Ignored if tok.text == "_" => Ok(0),
+            Src::Imm | Src::Ignored => syms.imm(tok),
+        }
+    };
+    let args = [arg(sa, "a")?, arg(sb, "b")?, arg(Src::Reg, "c")?];
+
+    end_of_line(toks)?;
+    Ok(Inst::new(code, args))
+}
+
+/// Where `seti k _ ip` or `addi ip k ip` goes, so long as it's somewhere in the program
+fn jump_target(program: &Program, pc: usize, inst: &Inst) -> Option<usize> {
+    let [a, b, c] = inst.args;
+    let ip = i32::try_from(program.ip?).ok()?;
+
+    let to = match inst.op {
+        Opcode::Seti if c == ip => i64::from(a) + 1,
+        Opcode::Addi if a == ip && c == ip => pc as i64 + i64::from(b) + 1,
+        _ => return None,
+    };
+
+    usize::try_from(to).ok().filter(|&t| t <= program.len())
+}
+
+/// Back to assembly with labels for the jumps, each instruction's pc and what it does
+/// in the comments. Assembling it gives the same program.
+pub fn disassemble(program: &Program) -> String {
+    let mut out = String::new();
+    let reg = |r: i32| match (program.ip, usize::try_from(r)) {
+        (Some(ip), Ok(r)) if r == ip => "ip".to_owned(),
+        (_, Ok(_)) => format!("r{}", r),
+        _ => r.to_string(),
+    };
+
+    let labels: BTreeSet<usize> = (program.insts.iter().enumerate())
+        .filter_map(|(pc, i)| jump_target(program, pc, i))
+        .collect();
+
+    if let Some(ip) = program.ip {
+        writeln!(out, "#ip {}", ip).unwrap();
+    }
+
+    for (pc, inst) in program.insts.iter().enumerate() {
+        if labels.contains(&pc) {
+            writeln!(out, "L{}:", pc).unwrap();
+        }
+
+        let (sa, sb) = inst.op.operands();
+        let [a, b, c] = inst.args;
+        let arg = |src, v: i32| match src {
+            Src::Reg => reg(v),
+            Src::Ignored if v == 0 => "_".to_owned(),
+            Src::Imm | Src::Ignored => v.to_string(),
+        };
+        let (a, b, c) = (arg(sa, a), arg(sb, b), reg(c));
+
+        let (text, what) = match jump_target(program, pc, inst) {
+            // Relative ones keep their offset
+            Some(t) if inst.op == Opcode::Addi => {
+                (format!("addi ip {} ip", b), format!("goto L{}", t))
+            }
+            Some(t) if b == "_" => (format!("jmp L{}", t), format!("goto L{}", t)),
+            Some(t) => (format!("seti L{}-1 {} ip", t, b), format!("goto L{}", t)),
+            None => (
+                format!("{} {} {} {}", inst.op, a, b, c),
+                effect(inst.op, a, b, c),
+            ),
+        };
+
+        writeln!(out, "    {:<20} ; {}: {}", text, pc, what).unwrap();
+    }
+
+    if labels.contains(&program.len()) {
+        writeln!(out, "L{}:", program.len()).unwrap();
+    }
+
+    out
+}
+
+/// Like `c = a + b`, or `c += b` when it's the same register
+fn effect(op: Opcode, a: String, b: String, c: String) -> String {
+    use Opcode::*;
+
+    let symbol = match op {
+        Setr | Seti => return format!("{} = {}", c, a),
+        Addr | Addi => "+",
+        Mulr | Muli => "*",
+        Banr | Bani => "&",
+        Borr | Bori => "|",
+        Gtir | Gtri | Gtrr => return format!("{} = {} > {}", c, a, b),
+        Eqir | Eqri | Eqrr => return format!("{} = {} == {}", c, a, b),
+    };
+
+    if a == c {
+        format!("{} {}= {}", c, symbol, b)
+    } else {
+        format!("{} = {} {} {}", c, a, symbol, b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::fuzz::{check, fuzz};
+
+    const COUNT: &str = "\
+#ip 2                   ; binds r2, which can then be called ip
+.reg n 1                ; n is r1
+.const limit 10
+        seti limit _ n
+top:    addi r0 1 r0
+        gtrr r0 n r3
+        addr r3 ip ip
+        jmp top         ; seti top-1 0 ip
+";
+
+    #[test]
+    fn assembles() {
+        let p = assemble(COUNT).unwrap();
+        assert_eq!(
+            p.to_string(),
+            "#ip 2\nseti 10 0 1\naddi 0 1 0\ngtrr 0 1 3\naddr 3 2 2\nseti 0 0 2\n"
+        );
+
+        let mut vm = crate::vm::Vm::new(p, [0; 6]);
+        vm.run().unwrap();
+        assert_eq!(vm.reg[0], 11);
+
+        // Labels on their own line, forwards, with offsets and at the very end
+        let p = assemble("#ip 0\njmp end\nseti end+2 _ r1\nend:").unwrap();
+        assert_eq!(p.to_string(), "#ip 0\nseti 1 0 0\nseti 4 0 1\n");
+    }
+
+    #[test]
+    fn disassembles() {
+        let p = assemble(COUNT).unwrap();
+        assert_eq!(
+            disassemble(&p),
+            "#ip 2
+    seti 10 _ r1         ; 0: r1 = 10
+L1:
+    addi r0 1 r0         ; 1: r0 += 1
+    gtrr r0 r1 r3        ; 2: r3 = r0 > r1
+    addr r3 ip ip        ; 3: ip = r3 + ip
+    jmp L1               ; 4: goto L1
+"
+        );
+
+        // Relative jumps keep their offset, a b that isn't 0 keeps it from being a jmp
+        let p: Program = "#ip 0\naddi 0 1 0\nseti 0 3 0".parse().unwrap();
+        assert_eq!(
+            disassemble(&p),
+            "#ip 0\n    addi ip 1 ip         ; 0: goto L2\nL1:\n    seti L1-1 3 ip       ; 1: goto L1\nL2:\n"
+        );
+
+        // Without a binding there are no jumps, and odd registers stay numbers
+        let p: Program = "seti 0 0 2\nsetr 4 3 -1".parse().unwrap();
+        let asm = disassemble(&p);
+        assert_eq!(
+            asm,
+            "    seti 0 _ r2          ; 0: r2 = 0\n    setr r4 3 -1         ; 1: -1 = r4\n"
+        );
+        assert_eq!(assemble(&asm).unwrap(), p);
+    }
+
+    #[test]
+    fn errors() {
+        let err = |s: &str| assemble(s).unwrap_err().to_string();
+
+        assert_eq!(
+            err("seti 1 _ r0\njmp 0"),
+            "line 2, column 1: expected #ip before a jmp at 'jmp'"
+        );
+        assert_eq!(
+            err("#ip 0\njmp nowhere"),
+            "line 2, column 5: expected number, constant or label at 'nowhere'"
+        );
+        assert_eq!(
+            err("a:\na:"),
+            "line 2, column 1: expected a name that isn't taken at 'a'"
+        );
+        assert_eq!(
+            err(".reg ip 3"),
+            "line 1, column 6: expected a name that isn't taken at 'ip'"
+        );
+        assert_eq!(
+            err("addx 1 2 3"),
+            "line 1, column 1: expected opcode at 'addx'"
+        );
+        assert_eq!(
+            err(".const k 1\naddr k 2 3"),
+            "line 2, column 6: expected register at 'k'"
+        );
+        assert_eq!(err("addi 1 2"), "line 1, column 9: missing c");
+        assert_eq!(
+            err("addi 1 2 3 4"),
+            "line 1, column 12: expected end of line at '4'"
+        );
+        assert_eq!(
+            err("seti 0 0 0\n#ip 0"),
+            "line 2, column 1: expected #ip only once and before the code at '#ip'"
+        );
+        assert_eq!(
+            err(".org 4"),
+            "line 1, column 1: expected #ip, .reg or .const at '.org'"
+        );
+        assert_eq!(
+            err("seti 2147483647+1 _ r0"),
+            "line 1, column 6: expected number, constant or label at '2147483647+1'"
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let gen = |r: &mut common::fuzz::Rng| {
+            let insts = (0..r.below(24))
+                .map(|_| {
+                    let op = *r.pick(&Opcode::ALL);
+                    let mut arg = || r.range(-2, 26) as i32;
+                    Inst::new(op, [arg(), arg(), arg()])
+                })
+                .collect();
+            let ip = Some(r.below(5)).filter(|_| !r.one_in(4));
+            Program::new(ip, insts)
+        };
+
+        check(2000, gen, |p| {
+            assert_eq!(&assemble(&disassemble(p)).unwrap(), p);
+        });
+    }
+
+    #[test]
+    fn fuzz_assemble() {
+        fuzz(&[COUNT, "é+1:\n#ip ip\njmp é-1"], 2000, |s| {
+            let _ = assemble(s);
+        });
+    }
+}
@@ -0,0 +1,615 @@
+//! Control flow of a program with the instructions lifted to expressions. Writes to the
+//! ip register are the jumps, reads of it are just the pc.
+
+use std::collections::BTreeSet;
+use std::convert::TryFrom;
+use std::fmt;
+use std::ops::Not;
+
+use crate::op::{Inst, Opcode, Src};
+use crate::program::Program;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BinOp {
+    Add,
+    Mul,
+    And,
+    Or,
+    Gt,
+    Le,
+    Eq,
+    Ne,
+}
+
+impl BinOp {
+    /// None for the two sets, they just copy a
+    fn of(op: Opcode) -> Option<BinOp> {
+        use Opcode::*;
+
+        match op {
+            Addr | Addi => Some(BinOp::Add),
+            Mulr | Muli => Some(BinOp::Mul),
+            Banr | Bani => Some(BinOp::And),
+            Borr | Bori => Some(BinOp::Or),
+            Setr | Seti => None,
+            Gtir | Gtri | Gtrr => Some(BinOp::Gt),
+            Eqir | Eqri | Eqrr => Some(BinOp::Eq),
+        }
+    }
+
+    pub fn eval(self, a: i64, b: i64) -> i64 {
+        match self {
+            BinOp::Add => a.wrapping_add(b),
+            BinOp::Mul => a.wrapping_mul(b),
+            BinOp::And => a & b,
+            BinOp::Or => a | b,
+            BinOp::Gt => (a > b) as i64,
+            BinOp::Le => (a <= b) as i64,
+            BinOp::Eq => (a == b) as i64,
+            BinOp::Ne => (a != b) as i64,
+        }
+    }
+
+    pub fn is_compare(self) -> bool {
+        self.negate().is_some()
+    }
+
+    /// The comparison that holds when this one doesn't
+    pub fn negate(self) -> Option<BinOp> {
+        match self {
+            BinOp::Gt => Some(BinOp::Le),
+            BinOp::Le => Some(BinOp::Gt),
+            BinOp::Eq => Some(BinOp::Ne),
+            BinOp::Ne => Some(BinOp::Eq),
+            _ => None,
+        }
+    }
+
+    /// Whether `a op (b op c)` is the same as `(a op b) op c`
+    fn associates(self) -> bool {
+        !self.is_compare()
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            BinOp::Add => "+",
+            BinOp::Mul => "*",
+            BinOp::And => "&",
+            BinOp::Or => "|",
+            BinOp::Gt => ">",
+            BinOp::Le => "<=",
+            BinOp::Eq => "==",
+            BinOp::Ne => "!=",
+        }
+    }
+
+    /// Same as C
+    fn precedence(self) -> u8 {
+        match self {
+            BinOp::Mul => 6,
+            BinOp::Add => 5,
+            BinOp::Gt | BinOp::Le => 4,
+            BinOp::Eq | BinOp::Ne => 3,
+            BinOp::And => 2,
+            BinOp::Or => 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Expr {
+    Reg(usize),
+    Imm(i64),
+    Bin(BinOp, Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+}
+
+impl Expr {
+    /// Works it out when both sides are known
+    pub fn bin(op: BinOp, a: Expr, b: Expr) -> Expr {
+        match (a, b) {
+            (Expr::Imm(a), Expr::Imm(b)) => Expr::Imm(op.eval(a, b)),
+            (a, b) => Expr::Bin(op, Box::new(a), Box::new(b)),
+        }
+    }
+
+    /// How many times it reads `r`
+    pub fn reads(&self, r: usize) -> usize {
+        let mut n = 0;
+        self.each_reg(&mut |x| n += (x == r) as usize);
+        n
+    }
+
+    pub fn each_reg<F: FnMut(usize)>(&self, f: &mut F) {
+        match self {
+            Expr::Reg(r) => f(*r),
+            Expr::Imm(_) => (),
+            Expr::Bin(_, a, b) => {
+                a.each_reg(f);
+                b.each_reg(f);
+            }
+            Expr::Not(e) => e.each_reg(f),
+        }
+    }
+
+    /// Swap reads of `r` for `with`
+    pub fn substitute(&mut self, r: usize, with: &Expr) {
+        match self {
+            Expr::Reg(x) if *x == r => *self = with.clone(),
+            Expr::Reg(_) | Expr::Imm(_) => (),
+            Expr::Bin(op, a, b) => {
+                a.substitute(r, with);
+                b.substitute(r, with);
+
+                // It might all be known now
+                if let (Expr::Imm(a), Expr::Imm(b)) = (&**a, &**b) {
+                    *self = Expr::Imm(op.eval(*a, *b));
+                }
+            }
+            Expr::Not(e) => e.substitute(r, with),
+        }
+    }
+
+    fn precedence(&self) -> u8 {
+        match self {
+            Expr::Bin(op, ..) => op.precedence(),
+            _ => u8::MAX,
+        }
+    }
+
+    fn fmt_side(&self, f: &mut fmt::Formatter, op: BinOp, right: bool) -> fmt::Result {
+        let p = self.precedence();
+        if p < op.precedence() || (p == op.precedence() && right && !op.associates()) {
+            write!(f, "({})", self)
+        } else {
+            write!(f, "{}", self)
+        }
+    }
+}
+
+impl Not for Expr {
+    type Output = Expr;
+
+    /// Flips comparisons rather than wrapping them
+    fn not(self) -> Expr {
+        match self {
+            Expr::Bin(op, a, b) if op.is_compare() => Expr::Bin(op.negate().unwrap(), a, b),
+            Expr::Not(e) => *e,
+            e => Expr::Not(Box::new(e)),
+        }
+    }
+}
+
+/// Registers are named a, b, c, ... like the puzzle text
+pub fn reg_name(r: usize) -> String {
+    match u8::try_from(r) {
+        Ok(r) if r < 26 => char::from(b'a' + r).to_string(),
+        _ => format!("r{}", r),
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Expr::Reg(r) => f.write_str(&reg_name(*r)),
+            Expr::Imm(v) => write!(f, "{}", v),
+            Expr::Bin(op, a, b) => {
+                a.fmt_side(f, *op, false)?;
+                write!(f, " {} ", op.symbol())?;
+                b.fmt_side(f, *op, true)
+            }
+            Expr::Not(e) if e.precedence() == u8::MAX => write!(f, "!{}", e),
+            Expr::Not(e) => write!(f, "!({})", e),
+        }
+    }
+}
+
+/// One instruction that doesn't jump
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stmt {
+    pub pc: usize,
+    pub reg: usize,
+    pub expr: Expr,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Target {
+    Block(usize),
+    /// Off either end of the program
+    Halt,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Term {
+    /// Falling into the next block counts too
+    Goto(Target),
+    /// To the first when the condition holds, the second when it doesn't
+    Branch(Expr, Target, Target),
+    /// To wherever the expression says, `likely` are where it goes if the register
+    /// it adds to the pc is a 0 or 1
+    Jump(Expr, Vec<Target>),
+    /// The last instruction names a register that can't be there
+    Fault,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Block {
+    /// Its pcs, `end` not included
+    pub start: usize,
+    pub end: usize,
+    pub stmts: Vec<Stmt>,
+    pub term: Term,
+}
+
+impl Block {
+    pub fn succs(&self) -> Vec<Target> {
+        match &self.term {
+            Term::Goto(t) => vec![*t],
+            Term::Branch(_, t, f) => vec![*t, *f],
+            Term::Jump(_, likely) => likely.clone(),
+            Term::Fault => vec![],
+        }
+    }
+}
+
+/// How an instruction moves the pc, targets are pcs before they're blocks
+enum Flow {
+    Next(Stmt),
+    Goto(i64),
+    Branch(Expr, i64, i64),
+    Jump(Expr, Vec<i64>),
+    Fault,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cfg {
+    pub ip: Option<usize>,
+    /// In pc order, the first is where it starts
+    pub blocks: Vec<Block>,
+}
+
+impl Cfg {
+    /// Jumps that only go to another jump are followed through, so blocks made of a
+    /// single jump usually end up unreachable
+    pub fn new(program: &Program) -> Cfg {
+        let mut cfg = Cfg::unthreaded(program);
+        cfg.thread();
+        cfg
+    }
+
+    /// Every jump going where the instruction says
+    pub fn unthreaded(program: &Program) -> Cfg {
+        let insts = &program.insts;
+        let lift = |jumped: &BTreeSet<usize>| -> Vec<Flow> {
+            (0..insts.len())
+                .map(|pc| flow(program, pc, jumped))
+                .collect()
+        };
+
+        // Where the jumps go doesn't depend on which adds turn out to be branches, so
+        // the first go finds out what gets jumped into for the second
+        let flows = lift(&BTreeSet::new());
+        let mut jumped = BTreeSet::new();
+        for (pc, fl) in flows.iter().enumerate() {
+            let in_range = targets(fl).into_iter().flatten();
+            jumped.extend(in_range.filter(|&t| t != pc + 1 && t < insts.len()));
+        }
+        let flows = lift(&jumped);
+
+        let mut leaders = BTreeSet::new();
+        leaders.insert(0);
+        for (pc, fl) in flows.iter().enumerate() {
+            let targets = match targets(fl) {
+                Some(t) => t,
+                None => continue,
+            };
+
+            leaders.insert(pc + 1);
+            leaders.extend(targets.into_iter().filter(|&t| t < insts.len()));
+        }
+
+        let starts: Vec<usize> = leaders.into_iter().filter(|&l| l < insts.len()).collect();
+        let target = |pc: i64| match usize::try_from(pc).map(|pc| starts.binary_search(&pc)) {
+            Ok(Ok(b)) => Target::Block(b),
+            _ => Target::Halt,
+        };
+
+        let mut blocks = vec![];
+        let mut flows = flows.into_iter();
+
+        for (b, &start) in starts.iter().enumerate() {
+            let end = starts.get(b + 1).copied().unwrap_or(insts.len());
+            let mut stmts = vec![];
+            let mut term = Term::Goto(target(end as i64));
+
+            for _ in start..end {
+                term = match flows.next().unwrap() {
+                    Flow::Next(s) => {
+                        stmts.push(s);
+                        continue;
+                    }
+                    Flow::Goto(t) => Term::Goto(target(t)),
+                    Flow::Branch(c, t, f) => Term::Branch(c, target(t), target(f)),
+                    Flow::Jump(e, likely) => {
+                        Term::Jump(e, likely.into_iter().map(target).collect())
+                    }
+                    Flow::Fault => Term::Fault,
+                };
+            }
+
+            blocks.push(Block {
+                start,
+                end,
+                stmts,
+                term,
+            });
+        }
+
+        Cfg {
+            ip: program.ip,
+            blocks,
+        }
+    }
+
+    fn thread(&mut self) {
+        let through = |blocks: &[Block], mut t: Target| {
+            for _ in 0..blocks.len() {
+                match t {
+                    Target::Block(b) if blocks[b].stmts.is_empty() => match blocks[b].term {
+                        Term::Goto(next) if next != t => t = next,
+                        _ => break,
+                    },
+                    _ => break,
+                }
+            }
+            t
+        };
+
+        for b in 0..self.blocks.len() {
+            let term = match &self.blocks[b].term {
+                Term::Goto(t) => Term::Goto(through(&self.blocks, *t)),
+                Term::Branch(c, t, f) => Term::Branch(
+                    c.clone(),
+                    through(&self.blocks, *t),
+                    through(&self.blocks, *f),
+                ),
+                _ => continue,
+            };
+            self.blocks[b].term = term;
+        }
+    }
+
+    /// Index of the block `pc` is in
+    pub fn block_at(&self, pc: usize) -> Option<usize> {
+        match self.blocks.binary_search_by_key(&pc, |b| b.start) {
+            Ok(b) => Some(b),
+            Err(0) => None,
+            Err(b) if pc < self.blocks[b - 1].end => Some(b - 1),
+            Err(_) => None,
+        }
+    }
+
+    pub fn reachable(&self) -> Vec<bool> {
+        let mut seen = vec![false; self.blocks.len()];
+        let mut todo = vec![0];
+
+        while let Some(b) = todo.pop() {
+            if b >= seen.len() || seen[b] {
+                continue;
+            }
+            seen[b] = true;
+
+            for t in self.blocks[b].succs() {
+                if let Target::Block(n) = t {
+                    todo.push(n);
+                }
+            }
+        }
+
+        seen
+    }
+
+    /// Reachable blocks that go to each block
+    pub fn preds(&self) -> Vec<Vec<usize>> {
+        let reachable = self.reachable();
+        let mut preds = vec![vec![]; self.blocks.len()];
+
+        for (b, block) in self
+            .blocks
+            .iter()
+            .enumerate()
+            .filter(|(b, _)| reachable[*b])
+        {
+            for t in block.succs() {
+                if let Target::Block(n) = t {
+                    if !preds[n].contains(&b) {
+                        preds[n].push(b);
+                    }
+                }
+            }
+        }
+
+        preds
+    }
+
+    /// `dom[b][a]` when every way from the start to `b` goes through `a`
+    pub fn dominators(&self) -> Vec<Vec<bool>> {
+        let n = self.blocks.len();
+        let reachable = self.reachable();
+        let preds = self.preds();
+
+        let mut dom: Vec<Vec<bool>> = (0..n).map(|b| vec![b != 0 && reachable[b]; n]).collect();
+        if n > 0 {
+            dom[0][0] = true;
+        }
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+
+            for b in (1..n).filter(|&b| reachable[b]) {
+                let mut d = vec![true; n];
+                for &p in &preds[b] {
+                    for (x, y) in d.iter_mut().zip(&dom[p]) {
+                        *x &= *y;
+                    }
+                }
+                d[b] = true;
+
+                if d != dom[b] {
+                    dom[b] = d;
+                    changed = true;
+                }
+            }
+        }
+
+        dom
+    }
+}
+
+/// The pcs `fl` can go to that there are, None if it just goes on to the next
+fn targets(fl: &Flow) -> Option<Vec<usize>> {
+    let targets = match fl {
+        Flow::Next(_) => return None,
+        Flow::Goto(t) => vec![*t],
+        Flow::Branch(_, t, f) => vec![*t, *f],
+        Flow::Jump(_, likely) => likely.clone(),
+        Flow::Fault => vec![],
+    };
+    Some(
+        targets
+            .into_iter()
+            .filter_map(|t| usize::try_from(t).ok())
+            .collect(),
+    )
+}
+
+/// Lift the instruction at `pc`, `jumped` being the pcs that jumps go to
+fn flow(program: &Program, pc: usize, jumped: &BTreeSet<usize>) -> Flow {
+    let Inst { op, args } = program.insts[pc];
+    let ip = program.ip;
+
+    let read = |src, v: i32| match src {
+        Src::Reg => match usize::try_from(v) {
+            Ok(r) if Some(r) == ip => Some(Expr::Imm(pc as i64)),
+            Ok(r) => Some(Expr::Reg(r)),
+            Err(_) => None,
+        },
+        Src::Imm => Some(Expr::Imm(i64::from(v))),
+        Src::Ignored => Some(Expr::Imm(0)),
+    };
+
+    let (sa, sb) = op.operands();
+    let (a, b, c) = match (
+        read(sa, args[0]),
+        read(sb, args[1]),
+        usize::try_from(args[2]),
+    ) {
+        (Some(a), Some(b), Ok(c)) => (a, b, c),
+        _ => return Flow::Fault,
+    };
+    let expr = match BinOp::of(op) {
+        Some(bin) => Expr::bin(bin, a, b),
+        None => a,
+    };
+
+    if Some(c) != ip {
+        return Flow::Next(Stmt { pc, reg: c, expr });
+    }
+
+    // The pc ends up one past whatever gets written
+    match expr {
+        Expr::Imm(v) => Flow::Goto(v + 1),
+        Expr::Bin(op, ..) if op.is_compare() => Flow::Branch(expr, 2, 1),
+        Expr::Bin(BinOp::Add, x, y) => match (*x, *y) {
+            (Expr::Imm(k), Expr::Reg(r)) | (Expr::Reg(r), Expr::Imm(k)) => {
+                if is_flag(program, pc, r, jumped) {
+                    Flow::Branch(Expr::Reg(r), k + 2, k + 1)
+                } else {
+                    let to = Expr::bin(BinOp::Add, Expr::Imm(k + 1), Expr::Reg(r));
+                    Flow::Jump(to, vec![k + 1, k + 2])
+                }
+            }
+            (x, y) => Flow::Jump(plus_one(Expr::bin(BinOp::Add, x, y)), vec![]),
+        },
+        e => Flow::Jump(plus_one(e), vec![]),
+    }
+}
+
+fn plus_one(e: Expr) -> Expr {
+    Expr::bin(BinOp::Add, e, Expr::Imm(1))
+}
+
+/// Whether the instruction just before set `r` from a comparison, so it's 0 or 1. With
+/// a jump straight to the add, some other way there could have left anything in `r`.
+fn is_flag(program: &Program, pc: usize, r: usize, jumped: &BTreeSet<usize>) -> bool {
+    let prev = match pc.checked_sub(1).map(|p| program.insts[p]) {
+        Some(i) => i,
+        None => return false,
+    };
+    if jumped.contains(&pc) {
+        return false;
+    }
+
+    usize::try_from(prev.args[2]).ok() == Some(r)
+        && Some(r) != program.ip
+        && BinOp::of(prev.op).is_some_and(BinOp::is_compare)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blocks() {
+        // Counts b up past 5 then halts
+        let p: Program = "#ip 2
+seti 0 0 1
+addi 1 1 1
+gtri 1 5 0
+addr 0 2 2
+seti 0 0 2
+seti 99 0 2"
+            .parse()
+            .unwrap();
+        let cfg = Cfg::new(&p);
+
+        let spans: Vec<(usize, usize)> = cfg.blocks.iter().map(|b| (b.start, b.end)).collect();
+        assert_eq!(spans, vec![(0, 1), (1, 4), (4, 5), (5, 6)]);
+
+        let cond = Expr::Reg(0);
+        assert_eq!(
+            cfg.blocks[1].term,
+            Term::Branch(cond, Target::Halt, Target::Block(1))
+        );
+        assert_eq!(cfg.reachable(), vec![true, true, false, false]);
+        assert_eq!(cfg.preds()[1], vec![0, 1]);
+        assert!(cfg.dominators()[1][0]);
+        assert_eq!(cfg.block_at(3), Some(1));
+    }
+
+    #[test]
+    fn exprs() {
+        let e = Expr::bin(
+            BinOp::Mul,
+            Expr::bin(BinOp::Add, Expr::Reg(5), Expr::Imm(1)),
+            Expr::Imm(256),
+        );
+        let cmp = Expr::bin(BinOp::Gt, e, Expr::Reg(4));
+        assert_eq!(cmp.to_string(), "(f + 1) * 256 > e");
+        assert_eq!((!cmp.clone()).to_string(), "(f + 1) * 256 <= e");
+        assert_eq!(!!Expr::Reg(2), Expr::Reg(2));
+        assert_eq!(cmp.reads(5), 1);
+
+        let sub = Expr::bin(
+            BinOp::Eq,
+            Expr::Reg(0),
+            Expr::bin(BinOp::Eq, Expr::Reg(1), Expr::Imm(0)),
+        );
+        assert_eq!(sub.to_string(), "a == (b == 0)");
+        assert_eq!(
+            Expr::bin(BinOp::Add, Expr::Imm(2), Expr::Imm(3)),
+            Expr::Imm(5)
+        );
+    }
+}
@@ -0,0 +1,485 @@
+//! Two ways to run programs without decoding every instruction every time. One is a
+//! closure per instruction with its operands already worked out, the other is a Rust
+//! program to build and run on its own.
+
+use std::convert::TryFrom;
+use std::fmt::Write;
+
+use crate::op::{Inst, Opcode, Src};
+use crate::optimize::{self, Shortcut};
+use crate::program::Program;
+use crate::vm::{to_pc, Fault, FaultKind};
+use crate::word::{Overflow, Word};
+
+/// Runs one instruction, and says how many that was so shortcuts can count theirs
+type Op<const N: usize, W> = Box<dyn Fn(&mut [W; N]) -> Result<u64, FaultKind>>;
+
+/// An operand once it's known whether it's a register or a value
+#[derive(Clone, Copy)]
+enum Operand<W> {
+    Reg(usize),
+    Imm(W),
+}
+
+/// How a compiled run finished
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Run {
+    pub steps: u64,
+    pub halted: bool,
+    /// Where it got to, off the end once it's halted
+    pub pc: usize,
+}
+
+pub struct Compiled<const N: usize, W: Word> {
+    program: Program,
+    ops: Vec<Op<N, W>>,
+}
+
+impl<const N: usize, W: Word + 'static> Compiled<N, W> {
+    pub fn new(program: Program, mode: Overflow) -> Compiled<N, W> {
+        Compiled::build(program, mode, &[])
+    }
+
+    /// With the loops the optimizer knows run natively
+    pub fn optimized(program: Program, mode: Overflow) -> Compiled<N, W> {
+        let shortcuts = optimize::shortcuts(&program);
+        Compiled::build(program, mode, &shortcuts)
+    }
+
+    fn build(program: Program, mode: Overflow, shortcuts: &[Shortcut]) -> Compiled<N, W> {
+        let ops = (program.insts.iter().enumerate())
+            .map(
+                |(pc, inst)| match shortcuts.iter().find(|s| s.start == pc) {
+                    Some(&s) => shortcut(s, program.ip, compile(inst, mode)),
+                    None => compile(inst, mode),
+                },
+            )
+            .collect();
+
+        Compiled { program, ops }
+    }
+
+    pub fn program(&self) -> &Program {
+        &self.program
+    }
+
+    /// Same as the VM from the registers in `reg`, until it halts or `budget` more
+    /// instructions have run
+    pub fn run(&self, reg: &mut [W; N], budget: Option<u64>) -> Result<Run, Fault> {
+        let ip = self.program.ip;
+        let mut pc = match ip.and_then(|r| reg.get(r)) {
+            Some(&v) => to_pc(v),
+            None => 0,
+        };
+        let mut steps = 0;
+
+        while let Some(op) = self.ops.get(pc) {
+            if budget.is_some_and(|b| steps >= b) {
+                break;
+            }
+
+            let fault = |kind| Fault {
+                pc,
+                inst: self.program.insts[pc],
+                kind,
+            };
+
+            if let Some(r) = ip {
+                let v = W::from_index(pc).ok_or_else(|| fault(FaultKind::Overflow))?;
+                *reg.get_mut(r)
+                    .ok_or_else(|| fault(FaultKind::BadRegister))? = v;
+                steps += op(reg).map_err(fault)?;
+                pc = to_pc(reg[r].add(W::ONE, Overflow::Saturating).unwrap());
+            } else {
+                steps += op(reg).map_err(fault)?;
+                pc += 1;
+            }
+        }
+
+        Ok(Run {
+            steps,
+            halted: pc >= self.ops.len(),
+            pc,
+        })
+    }
+}
+
+/// `plain` is the instruction at the start, for when the loop has to run as it is
+fn shortcut<const N: usize, W: Word + 'static>(
+    s: Shortcut,
+    ip: Option<usize>,
+    plain: Op<N, W>,
+) -> Op<N, W> {
+    // Left as if the loop's last jump out had run
+    let ip = ip.unwrap();
+    let exit = W::from_index(s.exit - 1);
+
+    Box::new(move |reg| match s.idiom.apply(reg)? {
+        Some(steps) => {
+            reg[ip] = exit.ok_or(FaultKind::Overflow)?;
+            Ok(steps)
+        }
+        None => plain(reg),
+    })
+}
+
+fn operand<const N: usize, W: Word>(
+    src: Src,
+    v: i32,
+    mode: Overflow,
+) -> Result<Operand<W>, FaultKind> {
+    match src {
+        Src::Reg => usize::try_from(v)
+            .ok()
+            .filter(|&r| r < N)
+            .map(Operand::Reg)
+            .ok_or(FaultKind::BadRegister),
+        Src::Imm => W::from_imm(v, mode)
+            .map(Operand::Imm)
+            .ok_or(FaultKind::Overflow),
+        Src::Ignored => Ok(Operand::Imm(W::ZERO)),
+    }
+}
+
+/// Whatever can be checked up front is, an instruction that would always fault just
+/// faults
+fn compile<const N: usize, W: Word + 'static>(inst: &Inst, mode: Overflow) -> Op<N, W> {
+    use Opcode::*;
+
+    let (sa, sb) = inst.op.operands();
+    let [a, b, c] = inst.args;
+    let operands = (
+        operand::<N, W>(sa, a, mode),
+        operand::<N, W>(sb, b, mode),
+        operand::<N, W>(Src::Reg, c, mode),
+    );
+    let (a, b, c) = match operands {
+        (Ok(a), Ok(b), Ok(Operand::Reg(c))) => (a, b, c),
+        (Err(e), _, _) | (_, Err(e), _) | (_, _, Err(e)) => return Box::new(move |_| Err(e)),
+        (_, _, Ok(Operand::Imm(_))) => unreachable!(),
+    };
+
+    let flag = |f| if f { W::ONE } else { W::ZERO };
+
+    match inst.op {
+        Addr | Addi => bin(a, b, c, move |x, y| x.add(y, mode)),
+        Mulr | Muli => bin(a, b, c, move |x, y| x.mul(y, mode)),
+        Banr | Bani => bin(a, b, c, |x: W, y| Some(x.and(y))),
+        Borr | Bori => bin(a, b, c, |x: W, y| Some(x.or(y))),
+        Setr | Seti => bin(a, b, c, |x, _| Some(x)),
+        Gtir | Gtri | Gtrr => bin(a, b, c, move |x, y| Some(flag(x > y))),
+        Eqir | Eqri | Eqrr => bin(a, b, c, move |x, y| Some(flag(x == y))),
+    }
+}
+
+/// One closure for each mix of registers and immediates, so running it doesn't have to
+/// look
+fn bin<const N: usize, W, F>(a: Operand<W>, b: Operand<W>, c: usize, f: F) -> Op<N, W>
+where
+    W: Word + 'static,
+    F: Fn(W, W) -> Option<W> + 'static,
+{
+    use Operand::*;
+
+    macro_rules! op {
+        (|$r:ident| $x:expr, $y:expr) => {
+            Box::new(move |$r: &mut [W; N]| {
+                $r[c] = f($x, $y).ok_or(FaultKind::Overflow)?;
+                Ok(1)
+            })
+        };
+    }
+
+    match (a, b) {
+        (Reg(a), Reg(b)) => op!(|r| r[a], r[b]),
+        (Reg(a), Imm(b)) => op!(|r| r[a], b),
+        (Imm(a), Reg(b)) => op!(|r| a, r[b]),
+        (Imm(a), Imm(b)) => op!(|r| a, b),
+    }
+}
+
+/// A Rust program that runs `program` with `N` registers of `W`. It takes the starting
+/// registers as arguments and prints where they ended up, or the fault on stderr.
+pub fn to_rust<const N: usize, W: Word>(program: &Program, mode: Overflow) -> String {
+    let mut out = String::new();
+    let w = W::NAME;
+
+    writeln!(
+        out,
+        "// Generated from an ElfCode program, {} registers of {}",
+        N, w
+    )
+    .unwrap();
+    writeln!(out, "// rustc -O this.rs && ./this [r0 r1 ...]\n").unwrap();
+    // Programs that can't fault never call it
+    writeln!(out, "#![allow(dead_code)]\n").unwrap();
+    writeln!(out, "use std::convert::TryFrom;\nuse std::process::exit;\n").unwrap();
+
+    writeln!(out, "const PROGRAM: [&str; {}] = [", program.len()).unwrap();
+    for inst in &program.insts {
+        writeln!(out, "    \"{}\",", inst).unwrap();
+    }
+    writeln!(out, "];\n").unwrap();
+
+    out.push_str(
+        "fn fault(pc: usize, what: &str) -> ! {
+    eprintln!(\"pc {}: '{}' {}\", pc, PROGRAM[pc], what);
+    exit(1)
+}
+
+",
+    );
+
+    writeln!(out, "fn main() {{").unwrap();
+    writeln!(out, "    let mut r: [{}; {}] = [0; {}];", w, N, N).unwrap();
+    out.push_str(
+        "    for (i, arg) in std::env::args().skip(1).enumerate() {
+        match (r.get_mut(i), arg.parse()) {
+            (Some(v), Ok(a)) => *v = a,
+            _ => {
+                eprintln!(\"bad register r{} = '{}'\", i, arg);
+                exit(2)
+            }
+        }
+    }
+
+",
+    );
+
+    let ip = program.ip.filter(|&ip| ip < N);
+    if program.ip.is_some() && ip.is_none() {
+        // Bound to a register that isn't there, nothing gets to run
+        writeln!(
+            out,
+            "    fault(0, \"uses a register that isn't there\");\n}}"
+        )
+        .unwrap();
+        return out;
+    }
+
+    match ip {
+        Some(ip) => writeln!(
+            out,
+            "    let mut pc = usize::try_from(r[{}]).unwrap_or(usize::MAX);",
+            ip
+        ),
+        None => writeln!(out, "    let mut pc = 0;"),
+    }
+    .unwrap();
+    writeln!(out, "    let mut steps: u64 = 0;\n").unwrap();
+
+    writeln!(out, "    loop {{\n        match pc {{").unwrap();
+    for (pc, inst) in program.insts.iter().enumerate() {
+        writeln!(out, "            {} => {{", pc).unwrap();
+        if let Some(ip) = ip {
+            writeln!(out, "                r[{}] = {};", ip, pc).unwrap();
+        }
+        match statement::<N, W>(inst, mode) {
+            Ok(s) => writeln!(out, "                {}", s),
+            Err(kind) => writeln!(
+                out,
+                "                fault({}, \"{}\");",
+                pc,
+                fault_text(kind)
+            ),
+        }
+        .unwrap();
+        writeln!(out, "            }}").unwrap();
+    }
+    writeln!(out, "            _ => break,\n        }}").unwrap();
+
+    writeln!(out, "        steps += 1;").unwrap();
+    match ip {
+        Some(ip) => writeln!(
+            out,
+            "        pc = usize::try_from(r[{}].saturating_add(1)).unwrap_or(usize::MAX);",
+            ip
+        ),
+        None => writeln!(out, "        pc += 1;"),
+    }
+    .unwrap();
+    writeln!(out, "    }}\n").unwrap();
+
+    writeln!(out, "    println!(\"{{:?}}\", r);").unwrap();
+    writeln!(out, "    eprintln!(\"halted after {{}} steps\", steps);").unwrap();
+    writeln!(out, "}}").unwrap();
+
+    out
+}
+
+fn fault_text(kind: FaultKind) -> &'static str {
+    match kind {
+        FaultKind::BadRegister => "uses a register that isn't there",
+        FaultKind::Overflow => "overflowed",
+    }
+}
+
+/// `inst` as a Rust statement on `r`, with `pc` in scope for the faults
+fn statement<const N: usize, W: Word>(inst: &Inst, mode: Overflow) -> Result<String, FaultKind> {
+    use Opcode::*;
+
+    let (sa, sb) = inst.op.operands();
+    let [a, b, c] = inst.args;
+    let text = |o: Operand<W>| match o {
+        Operand::Reg(r) => format!("r[{}]", r),
+        Operand::Imm(v) => v.to_string(),
+    };
+    let a = text(operand::<N, W>(sa, a, mode)?);
+    let b = text(operand::<N, W>(sb, b, mode)?);
+    let c = text(operand::<N, W>(Src::Reg, c, mode)?);
+
+    let arith = |name: &str| match mode {
+        Overflow::Wrapping => format!("{}.wrapping_{}({})", a, name, b),
+        Overflow::Saturating => format!("{}.saturating_{}({})", a, name, b),
+        Overflow::Checked => format!(
+            "{}.checked_{}({}).unwrap_or_else(|| fault(pc, \"overflowed\"))",
+            a, name, b
+        ),
+    };
+
+    let value = match inst.op {
+        Addr | Addi => arith("add"),
+        Mulr | Muli => arith("mul"),
+        Banr | Bani => format!("{} & {}", a, b),
+        Borr | Bori => format!("{} | {}", a, b),
+        Setr | Seti => a,
+        Gtir | Gtri | Gtrr => format!("({} > {}) as {}", a, b, W::NAME),
+        Eqir | Eqri | Eqrr => format!("({} == {}) as {}", a, b, W::NAME),
+    };
+
+    Ok(format!("{} = {};", c, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::Vm;
+    use common::fuzz::check;
+    use std::fs;
+    use std::process::Command;
+
+    /// Counts r1 up to 3 in a loop, then squares it
+    const LOOP: &str = "#ip 0
+addi 1 1 1
+gtri 1 2 2
+addr 0 2 0
+seti -1 0 0
+mulr 1 1 1";
+
+    fn random(r: &mut common::fuzz::Rng) -> (Program, [i64; 4]) {
+        let insts = (0..r.below(12))
+            .map(|_| {
+                let op = *r.pick(&Opcode::ALL);
+                let mut arg = || r.range(-1, 12) as i32;
+                Inst::new(op, [arg(), arg(), r.range(-1, 4) as i32])
+            })
+            .collect();
+        let ip = Some(r.below(5)).filter(|_| !r.one_in(4));
+        let reg = [
+            r.i32().into(),
+            r.range(-3, 3),
+            r.range(-3, 3),
+            r.range(-3, 3),
+        ];
+        (Program::new(ip, insts), reg)
+    }
+
+    #[test]
+    fn same_as_the_vm() {
+        let p: Program = LOOP.parse().unwrap();
+        let mut reg = [0; 3];
+        let run = Compiled::<3, i32>::new(p, Overflow::Wrapping)
+            .run(&mut reg, None)
+            .unwrap();
+        assert_eq!(reg, [4, 9, 1]);
+        assert_eq!((run.steps, run.halted, run.pc), (12, true, 5));
+
+        // Whatever happens, the same happens in both
+        check(3000, random, |(p, start)| {
+            for mode in [Overflow::Wrapping, Overflow::Checked] {
+                let mut vm = Vm::new(p.clone(), *start).with_overflow(mode);
+                let mut slow = Ok(());
+                while slow.is_ok() && !vm.halted() && vm.steps() < 200 {
+                    slow = vm.step().map(|_| ());
+                }
+
+                let mut reg = *start;
+                let fast = Compiled::<4, i64>::new(p.clone(), mode).run(&mut reg, Some(200));
+
+                match (slow, fast) {
+                    (Ok(()), Ok(run)) => {
+                        assert_eq!(run.steps, vm.steps());
+                        assert_eq!(run.halted, vm.halted());
+                    }
+                    (Err(a), Err(b)) => assert_eq!(a, b),
+                    (a, b) => panic!("{:?} vs {:?}", a, b),
+                }
+                assert_eq!(reg, vm.reg);
+            }
+        });
+    }
+
+    #[test]
+    fn shortcuts() {
+        let p: Program = include_str!("../../day19/input").parse().unwrap();
+        let mut reg = [1, 0, 0, 0, 0, 0];
+        let run = Compiled::<6, i64>::optimized(p, Overflow::Checked)
+            .run(&mut reg, None)
+            .unwrap();
+
+        assert!(run.halted);
+        assert_eq!(reg[0], 17427456);
+    }
+
+    #[test]
+    fn rust_source() {
+        let p: Program = "#ip 0\naddi 1 5 1\nmulr 1 1 2\nseti 7 0 9".parse().unwrap();
+        let src = to_rust::<3, i64>(&p, Overflow::Checked);
+
+        assert!(src.contains(
+            "            1 => {
+                r[0] = 1;
+                r[2] = r[1].checked_mul(r[1]).unwrap_or_else(|| fault(pc, \"overflowed\"));
+            }
+            2 => {
+                r[0] = 2;
+                fault(2, \"uses a register that isn't there\");
+            }"
+        ));
+    }
+
+    /// Builds the generated source, so it needs rustc
+    #[test]
+    #[ignore]
+    fn rust_source_runs() {
+        let dir = std::env::temp_dir().join(format!("elfcode-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let p: Program = LOOP.parse().unwrap();
+        fs::write(
+            dir.join("prog.rs"),
+            to_rust::<3, i64>(&p, Overflow::Wrapping),
+        )
+        .unwrap();
+
+        let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_owned());
+        let built = Command::new(rustc)
+            .arg("prog.rs")
+            .current_dir(&dir)
+            .status()
+            .unwrap();
+        assert!(built.success());
+
+        let out = Command::new(dir.join("prog"))
+            .args(["0", "1"])
+            .output()
+            .unwrap();
+        assert_eq!(String::from_utf8_lossy(&out.stdout), "[4, 9, 1]\n");
+        assert_eq!(
+            String::from_utf8_lossy(&out.stderr),
+            "halted after 8 steps\n"
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,224 @@
+//! Telling a program that never halts from one that's just slow. Its whole state gets
+//! hashed every time it jumps backwards, which any loop has to do, and seeing a hash
+//! again means it's probably going round in circles. A rerun from the start checks that
+//! it really is and finds where the cycle begins.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+use crate::vm::{Fault, Vm};
+use crate::word::Word;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Halted {
+        steps: u64,
+    },
+    /// Once it's run `start` instructions it's at `entry`, and it comes back there with
+    /// the same registers every `period` instructions
+    Cycled {
+        entry: usize,
+        start: u64,
+        period: u64,
+    },
+    Budget {
+        steps: u64,
+        pc: usize,
+    },
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Outcome::Halted { steps } => write!(f, "halted after {} instructions", steps),
+            Outcome::Cycled {
+                entry,
+                start,
+                period,
+            } => write!(
+                f,
+                "never halts, from pc {} after {} instructions it repeats every {}",
+                entry, start, period
+            ),
+            Outcome::Budget { steps, pc } => {
+                write!(
+                    f,
+                    "ran out of budget at pc {} after {} instructions",
+                    pc, steps
+                )
+            }
+        }
+    }
+}
+
+fn state<const N: usize, W: Word>(vm: &Vm<N, W>) -> (usize, [W; N]) {
+    (vm.pc(), vm.reg)
+}
+
+fn fingerprint<const N: usize, W: Word>(vm: &Vm<N, W>) -> u64 {
+    let mut h = DefaultHasher::new();
+    state(vm).hash(&mut h);
+    h.finish()
+}
+
+/// `vm` after `n` more calls to `step`, which it's already been through without faults
+fn advance<const N: usize, W: Word>(mut vm: Vm<N, W>, n: u64) -> Vm<N, W> {
+    for _ in 0..n {
+        vm.step().unwrap();
+    }
+    vm
+}
+
+/// Where the cycle starts, given that `from` gets back to the same state after `len`
+/// calls to `step` from some point on
+fn entry<const N: usize, W: Word>(from: &Vm<N, W>, len: u64) -> Outcome {
+    let mut slow = from.clone();
+    let mut fast = advance(from.clone(), len);
+
+    while state(&slow) != state(&fast) {
+        slow.step().unwrap();
+        fast.step().unwrap();
+    }
+
+    Outcome::Cycled {
+        entry: slow.pc(),
+        start: slow.steps() - from.steps(),
+        period: fast.steps() - slow.steps(),
+    }
+}
+
+/// Run `vm` until it halts, turns out never to or has run `budget` more instructions.
+/// Counts are from where `vm` was, and with shortcuts they count the instructions those
+/// stand in for.
+pub fn run<const N: usize, W: Word>(
+    vm: &mut Vm<N, W>,
+    budget: Option<u64>,
+) -> Result<Outcome, Fault> {
+    let from = vm.clone();
+    let base = vm.steps();
+    // Hash of the state to how many calls to `step` got there, a shortcut is one call
+    let mut seen: HashMap<u64, u64> = HashMap::new();
+    let mut calls = 0;
+
+    loop {
+        let steps = vm.steps() - base;
+        if vm.halted() {
+            return Ok(Outcome::Halted { steps });
+        }
+        if budget.is_some_and(|b| steps >= b) {
+            return Ok(Outcome::Budget { steps, pc: vm.pc() });
+        }
+
+        let pc = vm.pc();
+        vm.step()?;
+        calls += 1;
+
+        if vm.pc() > pc {
+            continue;
+        }
+        if let Some(before) = seen.insert(fingerprint(vm), calls) {
+            // Unless it's two states with the same hash
+            if state(&advance(from.clone(), before)) == state(vm) {
+                return Ok(entry(&from, calls - before));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asm::assemble;
+    use crate::op::{Inst, Opcode};
+    use crate::program::Program;
+    use common::fuzz::check;
+
+    /// Counts r1 down from 10, then goes round adding 1 and 2 to r2 mod 4 forever
+    const SPIN: &str = "\
+#ip 5
+        seti 10 _ r1
+down:   addi r1 -1 r1
+        eqri r1 0 r3
+        addr r3 ip ip
+        jmp down
+spin:   addi r2 1 r2
+        bani r2 3 r2
+        addi r2 2 r2
+        bani r2 3 r2
+        jmp spin
+";
+
+    #[test]
+    fn outcomes() {
+        let p = assemble(SPIN).unwrap();
+        let mut vm = Vm::new(p.clone(), [0_i64; 6]);
+        let out = run(&mut vm, None).unwrap();
+        assert_eq!(
+            out,
+            Outcome::Cycled {
+                entry: 5,
+                start: 1 + 9 * 4 + 3,
+                period: 20
+            }
+        );
+        assert_eq!(
+            out.to_string(),
+            "never halts, from pc 5 after 40 instructions it repeats every 20"
+        );
+
+        let mut vm = Vm::new(p.clone(), [0_i64; 6]);
+        assert_eq!(
+            run(&mut vm, Some(25)).unwrap(),
+            Outcome::Budget { steps: 25, pc: 1 }
+        );
+
+        // Halts once it's past the spin
+        let p = assemble(&SPIN.replace("jmp spin", "seti 0 _ r0")).unwrap();
+        let mut vm = Vm::new(p, [0_i64; 6]);
+        assert_eq!(run(&mut vm, None).unwrap(), Outcome::Halted { steps: 45 });
+    }
+
+    #[test]
+    fn cycles_are_real() {
+        let mut found = 0;
+        check(
+            500,
+            |r| {
+                let insts = (0..1 + r.below(8))
+                    .map(|_| {
+                        let op = *r.pick(&Opcode::ALL);
+                        Inst::new(
+                            op,
+                            [r.below(6) as i32, r.below(6) as i32, r.below(4) as i32],
+                        )
+                    })
+                    .collect();
+                Program::new(Some(r.below(4)), insts)
+            },
+            |p| {
+                let mut vm = Vm::new(p.clone(), [0_i32; 4]);
+                if let Ok(Outcome::Cycled {
+                    entry,
+                    start,
+                    period,
+                }) = run(&mut vm, Some(2000))
+                {
+                    let mut vm = Vm::new(p.clone(), [0; 4]);
+                    while vm.steps() < start {
+                        vm.step().unwrap();
+                    }
+                    assert_eq!(vm.pc(), entry);
+                    let at = vm.reg;
+                    for _ in 0..period {
+                        vm.step().unwrap();
+                    }
+                    assert_eq!((vm.pc(), vm.reg), (entry, at));
+                    found += 1;
+                }
+            },
+        );
+        assert!(found > 20, "only {} cycles", found);
+    }
+}
@@ -0,0 +1,466 @@
+//! Stepping through programs from typed commands, one per line
+
+use std::collections::BTreeMap;
+use std::io::{self, BufRead, Write};
+
+use crate::vm::Vm;
+use crate::word::Word;
+
+pub const HELP: &str = "\
+break <pc>            stop before pc runs            (b)
+watch <reg> [value]   stop when reg changes, or changes to value  (w)
+delete <id>           drop a breakpoint or watchpoint  (d)
+info                  list them
+step [n]              run n instructions, 1 by default  (s)
+continue [n]          run until something stops it, or n at most  (c)
+print [reg]           show the registers, or just one  (p)
+set <reg|pc> <value>  change a register or where it is
+list                  the program with where it is marked  (l)
+quit                  (q)
+
+Registers are r0, r1, ... or just the number. An empty line does the last command again.";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Stop<W> {
+    /// Before the instruction at this pc runs
+    Pc(usize),
+    /// After the register changes, to `value` if there is one
+    Watch { reg: usize, value: Option<W> },
+}
+
+/// Why a run came back
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Why {
+    Done,
+    Halted,
+    Hit(usize),
+}
+
+#[derive(Debug, Clone)]
+pub struct Debugger<const N: usize, W: Word> {
+    pub vm: Vm<N, W>,
+    stops: BTreeMap<usize, Stop<W>>,
+    next_id: usize,
+    last: String,
+}
+
+impl<const N: usize, W: Word> Debugger<N, W> {
+    pub fn new(vm: Vm<N, W>) -> Debugger<N, W> {
+        Debugger {
+            vm,
+            stops: BTreeMap::new(),
+            next_id: 1,
+            last: String::new(),
+        }
+    }
+
+    /// Add one, giving back its id
+    pub fn add(&mut self, stop: Stop<W>) -> usize {
+        let id = self.next_id;
+        self.stops.insert(id, stop);
+        self.next_id += 1;
+        id
+    }
+
+    /// Read commands until quit or the input runs out
+    pub fn repl<R: BufRead, O: Write>(&mut self, input: R, out: &mut O) -> io::Result<()> {
+        let mut lines = input.lines();
+
+        loop {
+            write!(out, "(elf) ")?;
+            out.flush()?;
+
+            let line = match lines.next() {
+                Some(l) => l?,
+                None => return Ok(()),
+            };
+
+            if !self.command(&line, out)? {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Do one command, false once it's time to quit. Mistakes get reported to `out`
+    /// rather than ending things.
+    pub fn command<O: Write>(&mut self, line: &str, out: &mut O) -> io::Result<bool> {
+        let line = if line.trim().is_empty() {
+            self.last.clone()
+        } else {
+            line.trim().to_owned()
+        };
+        self.last = line.clone();
+
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let (cmd, args) = match words.split_first() {
+            Some((c, a)) => (*c, a),
+            None => return Ok(true),
+        };
+
+        let res = match cmd {
+            "break" | "b" => self.add_break(args, out),
+            "watch" | "w" => self.add_watch(args, out),
+            "delete" | "d" => self.delete(args, out),
+            "info" | "i" => self.info(out),
+            "step" | "s" => self.step(args, out),
+            "continue" | "c" => self.cont(args, out),
+            "print" | "p" => self.print(args, out),
+            "set" => self.set(args, out),
+            "list" | "l" => self.list(out),
+            "help" | "h" => writeln!(out, "{}", HELP).map_err(Into::into),
+            "quit" | "q" => return Ok(false),
+            _ => Err(Error::Bad(format!("unknown command '{}', try help", cmd))),
+        };
+
+        match res {
+            Ok(()) => Ok(true),
+            Err(Error::Bad(e)) => writeln!(out, "{}", e).map(|_| true),
+            Err(Error::Io(e)) => Err(e),
+        }
+    }
+
+    fn add_break<O: Write>(&mut self, args: &[&str], out: &mut O) -> Result<(), Error> {
+        let pc = match args {
+            [pc] => number(pc, "pc")?,
+            _ => return Err(Error::usage("break <pc>")),
+        };
+
+        let id = self.add(Stop::Pc(pc));
+        writeln!(out, "breakpoint {} at pc {}", id, pc)?;
+        Ok(())
+    }
+
+    fn add_watch<O: Write>(&mut self, args: &[&str], out: &mut O) -> Result<(), Error> {
+        let (reg, value) = match args {
+            [r] => (register::<N>(r)?, None),
+            [r, v] => (register::<N>(r)?, Some(value(v)?)),
+            _ => return Err(Error::usage("watch <reg> [value]")),
+        };
+
+        let id = self.add(Stop::Watch { reg, value });
+        match value {
+            Some(v) => writeln!(out, "watchpoint {} on r{} == {}", id, reg, v)?,
+            None => writeln!(out, "watchpoint {} on r{}", id, reg)?,
+        }
+        Ok(())
+    }
+
+    fn delete<O: Write>(&mut self, args: &[&str], out: &mut O) -> Result<(), Error> {
+        let id = match args {
+            [id] => number(id, "id")?,
+            _ => return Err(Error::usage("delete <id>")),
+        };
+
+        match self.stops.remove(&id) {
+            Some(_) => writeln!(out, "deleted {}", id)?,
+            None => return Err(Error::Bad(format!("there's nothing with id {}", id))),
+        }
+        Ok(())
+    }
+
+    fn info<O: Write>(&self, out: &mut O) -> Result<(), Error> {
+        if self.stops.is_empty() {
+            writeln!(out, "no breakpoints or watchpoints")?;
+        }
+
+        for (id, s) in &self.stops {
+            match s {
+                Stop::Pc(pc) => writeln!(out, "{}: break at pc {}", id, pc)?,
+                Stop::Watch { reg, value: None } => writeln!(out, "{}: watch r{}", id, reg)?,
+                Stop::Watch {
+                    reg,
+                    value: Some(v),
+                } => writeln!(out, "{}: watch r{} == {}", id, reg, v)?,
+            }
+        }
+        Ok(())
+    }
+
+    fn step<O: Write>(&mut self, args: &[&str], out: &mut O) -> Result<(), Error> {
+        let n = match args {
+            [] => 1,
+            [n] => number(n, "count")?,
+            _ => return Err(Error::usage("step [n]")),
+        };
+
+        let before = self.vm.reg;
+        if n == 1 {
+            if let Some(i) = self.vm.current() {
+                writeln!(out, "{:>4}: {}", self.vm.pc(), i)?;
+            }
+        }
+
+        let why = self.run(Some(n as u64))?;
+        changes(&before, &self.vm.reg, out)?;
+        self.stopped(why, out)
+    }
+
+    fn cont<O: Write>(&mut self, args: &[&str], out: &mut O) -> Result<(), Error> {
+        let limit = match args {
+            [] => None,
+            [n] => Some(number(n, "count")? as u64),
+            _ => return Err(Error::usage("continue [n]")),
+        };
+
+        let why = self.run(limit)?;
+        self.stopped(why, out)
+    }
+
+    /// Step until a stop is hit, it halts or `limit` steps have run. A breakpoint on
+    /// where it starts doesn't count, otherwise continuing from one would go nowhere.
+    fn run(&mut self, limit: Option<u64>) -> Result<Why, Error> {
+        let mut n = 0;
+
+        loop {
+            if self.vm.halted() {
+                return Ok(Why::Halted);
+            }
+            if limit.is_some_and(|l| n >= l) {
+                return Ok(Why::Done);
+            }
+
+            let pc = self.vm.pc();
+            if n > 0 {
+                if let Some(id) = self.hit(|s| *s == Stop::Pc(pc)) {
+                    return Ok(Why::Hit(id));
+                }
+            }
+
+            let before = self.vm.reg;
+            self.vm
+                .step()
+                .map_err(|f| Error::Bad(format!("fault at {}", f)))?;
+            n += 1;
+
+            let after = self.vm.reg;
+            let watched = |s: &Stop<W>| match *s {
+                Stop::Watch { reg, value } => {
+                    before[reg] != after[reg] && value.is_none_or(|v| after[reg] == v)
+                }
+                Stop::Pc(_) => false,
+            };
+            if let Some(id) = self.hit(watched) {
+                return Ok(Why::Hit(id));
+            }
+        }
+    }
+
+    fn hit<F: Fn(&Stop<W>) -> bool>(&self, f: F) -> Option<usize> {
+        self.stops.iter().find(|(_, s)| f(s)).map(|(&id, _)| id)
+    }
+
+    fn stopped<O: Write>(&self, why: Why, out: &mut O) -> Result<(), Error> {
+        match why {
+            Why::Halted => {
+                writeln!(out, "halted after {} steps", self.vm.steps())?;
+                return self.print(&[], out);
+            }
+            Why::Hit(id) => match self.stops[&id] {
+                Stop::Pc(pc) => writeln!(out, "breakpoint {} at pc {}", id, pc)?,
+                Stop::Watch { reg, .. } => {
+                    writeln!(out, "watchpoint {}: r{} = {}", id, reg, self.vm.reg[reg])?
+                }
+            },
+            Why::Done => (),
+        }
+
+        self.location(out)
+    }
+
+    /// What's about to run
+    fn location<O: Write>(&self, out: &mut O) -> Result<(), Error> {
+        match self.vm.current() {
+            Some(i) => writeln!(
+                out,
+                "{:>4}: {}  (step {})",
+                self.vm.pc(),
+                i,
+                self.vm.steps()
+            )?,
+            None => writeln!(out, "halted at pc {}", self.vm.pc())?,
+        }
+        Ok(())
+    }
+
+    fn print<O: Write>(&self, args: &[&str], out: &mut O) -> Result<(), Error> {
+        match args {
+            [] => {
+                let regs: Vec<String> = (self.vm.reg.iter().enumerate())
+                    .map(|(r, v)| format!("r{}={}", r, v))
+                    .collect();
+                writeln!(out, "pc={} {}", self.vm.pc(), regs.join(" "))?;
+            }
+            ["pc"] => writeln!(out, "pc = {}", self.vm.pc())?,
+            [r] => {
+                let r = register::<N>(r)?;
+                writeln!(out, "r{} = {}", r, self.vm.reg[r])?;
+            }
+            _ => return Err(Error::usage("print [reg]")),
+        }
+        Ok(())
+    }
+
+    fn set<O: Write>(&mut self, args: &[&str], out: &mut O) -> Result<(), Error> {
+        match args {
+            ["pc", v] => self.vm.set_pc(number(v, "pc")?),
+            [r, v] => {
+                let r = register::<N>(r)?;
+                self.vm.reg[r] = value(v)?;
+            }
+            _ => return Err(Error::usage("set <reg|pc> <value>")),
+        }
+
+        self.print(&[], out)
+    }
+
+    fn list<O: Write>(&self, out: &mut O) -> Result<(), Error> {
+        for (pc, i) in self.vm.program().insts.iter().enumerate() {
+            let here = if pc == self.vm.pc() { "=>" } else { "  " };
+            let brk = if self.hit(|s| *s == Stop::Pc(pc)).is_some() {
+                '*'
+            } else {
+                ' '
+            };
+            writeln!(out, "{}{}{:>3}: {}", here, brk, pc, i)?;
+        }
+        Ok(())
+    }
+}
+
+/// Registers that aren't what they were
+pub(crate) fn changes<const N: usize, W: Word, O: Write>(
+    before: &[W; N],
+    after: &[W; N],
+    out: &mut O,
+) -> io::Result<()> {
+    for (r, (a, b)) in before.iter().zip(after).enumerate() {
+        if a != b {
+            writeln!(out, "  r{}: {} -> {}", r, a, b)?;
+        }
+    }
+    Ok(())
+}
+
+/// Typos get reported and the session carries on, only failing to write ends it
+pub(crate) enum Error {
+    Bad(String),
+    Io(io::Error),
+}
+
+impl Error {
+    pub(crate) fn usage(s: &str) -> Error {
+        Error::Bad(format!("usage: {}", s))
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+pub(crate) fn number(s: &str, what: &str) -> Result<usize, Error> {
+    s.parse()
+        .map_err(|_| Error::Bad(format!("bad {} '{}'", what, s)))
+}
+
+pub(crate) fn value<W: Word>(s: &str) -> Result<W, Error> {
+    s.parse()
+        .map_err(|_| Error::Bad(format!("bad value '{}'", s)))
+}
+
+/// `r3` or `3`
+pub(crate) fn register<const N: usize>(s: &str) -> Result<usize, Error> {
+    match s.trim_start_matches('r').parse() {
+        Ok(r) if r < N => Ok(r),
+        _ => Err(Error::Bad(format!(
+            "bad register '{}', there are r0 to r{}",
+            s,
+            N - 1
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "#ip 0
+seti 5 0 1
+seti 6 0 2
+addi 0 1 0
+addr 1 2 3
+setr 1 0 0
+seti 8 0 4
+seti 9 0 5";
+
+    /// Run a script, giving back what got printed
+    fn session(script: &str) -> String {
+        let vm = Vm::new(EXAMPLE.parse().unwrap(), [0_i64; 6]);
+        let mut out = vec![];
+        Debugger::new(vm).repl(script.as_bytes(), &mut out).unwrap();
+        String::from_utf8(out).unwrap().replace("(elf) ", "")
+    }
+
+    #[test]
+    fn breaks_and_steps() {
+        // Nothing runs after quitting
+        let out = session("b 4\nc\np\ns\n\nq\nc");
+        assert_eq!(
+            out,
+            "breakpoint 1 at pc 4
+breakpoint 1 at pc 4
+   4: setr 1 0 0  (step 3)
+pc=4 r0=3 r1=5 r2=6 r3=0 r4=0 r5=0
+   4: setr 1 0 0
+  r0: 3 -> 5
+   6: seti 9 0 5  (step 4)
+   6: seti 9 0 5
+  r0: 5 -> 6
+  r5: 0 -> 9
+halted after 5 steps
+pc=7 r0=6 r1=5 r2=6 r3=0 r4=0 r5=9
+"
+        );
+    }
+
+    #[test]
+    fn watches() {
+        // r0 is the ip register so it holds 6 once the last instruction runs
+        let out = session("w r2\nw 0 6\ni\nc\nc\nd 1\nd 1\nc 1\n");
+        assert_eq!(
+            out,
+            "watchpoint 1 on r2
+watchpoint 2 on r0 == 6
+1: watch r2
+2: watch r0 == 6
+watchpoint 1: r2 = 6
+   2: addi 0 1 0  (step 2)
+watchpoint 2: r0 = 6
+halted at pc 7
+deleted 1
+there's nothing with id 1
+halted after 5 steps
+pc=7 r0=6 r1=5 r2=6 r3=0 r4=0 r5=9
+"
+        );
+    }
+
+    #[test]
+    fn set_and_mistakes() {
+        // Jumping straight to the end with the ip register skips the first three
+        let out = session("set pc 5\nset r4 -2\nc\np r4\nset r6 1\nfrob\nb x");
+        assert_eq!(
+            out,
+            "pc=5 r0=0 r1=0 r2=0 r3=0 r4=0 r5=0
+pc=5 r0=0 r1=0 r2=0 r3=0 r4=-2 r5=0
+halted after 2 steps
+pc=7 r0=6 r1=0 r2=0 r3=0 r4=8 r5=9
+r4 = 8
+bad register 'r6', there are r0 to r5
+unknown command 'frob', try help
+bad pc 'x'
+"
+        );
+    }
+}
@@ -0,0 +1,745 @@
+//! Pseudo-code from programs, loops and ifs recovered from the jumps where the layout
+//! allows and gotos where it doesn't
+
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use crate::cfg::{reg_name, BinOp, Block, Cfg, Expr, Target, Term};
+use crate::program::Program;
+
+type Regs = HashSet<usize>;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Assign(usize, Expr),
+    If(Expr, Vec<Node>, Vec<Node>),
+    Loop(Vec<Node>),
+    While(Expr, Vec<Node>),
+    DoWhile(Vec<Node>, Expr),
+    For {
+        init: Box<Node>,
+        cond: Expr,
+        step: Box<Node>,
+        body: Vec<Node>,
+    },
+    Break,
+    Continue,
+    /// To a block's first pc
+    Goto(usize),
+    /// Computed, with the pcs it's likely to go to
+    Jump(Expr, Vec<usize>),
+    Halt,
+    Fault(usize),
+    Label(usize),
+}
+
+pub fn decompile(program: &Program) -> String {
+    let cfg = Cfg::new(program);
+    let blocks = fold(&cfg);
+
+    let mut s = Structurer {
+        blocks,
+        reachable: cfg.reachable(),
+        preds: cfg.preds(),
+        dom: cfg.dominators(),
+        loops: vec![],
+    };
+    let nodes = s.emit(0, s.blocks.len(), Target::Halt, None);
+
+    let mut gotos = HashSet::new();
+    targets(&nodes, &mut gotos);
+    let nodes = tidy(nodes, &gotos);
+
+    let mut out = String::new();
+    if let Some(ip) = program.ip {
+        writeln!(out, "// {} is the ip", reg_name(ip)).unwrap();
+    }
+    render(&nodes, 0, &mut out);
+    out
+}
+
+/// Every register that gets named
+fn all_regs(cfg: &Cfg) -> Regs {
+    let mut all = Regs::new();
+
+    for b in &cfg.blocks {
+        for s in &b.stmts {
+            all.insert(s.reg);
+            s.expr.each_reg(&mut |r| {
+                all.insert(r);
+            });
+        }
+        if let Some(e) = term_expr(&b.term) {
+            e.each_reg(&mut |r| {
+                all.insert(r);
+            });
+        }
+    }
+
+    all
+}
+
+fn term_expr(t: &Term) -> Option<&Expr> {
+    match t {
+        Term::Branch(e, ..) | Term::Jump(e, _) => Some(e),
+        _ => None,
+    }
+}
+
+/// Registers read before they're written, going backwards from `live` after the block
+fn live_in(block: &Block, mut live: Regs) -> Regs {
+    if let Some(e) = term_expr(&block.term) {
+        e.each_reg(&mut |r| {
+            live.insert(r);
+        });
+    }
+
+    for s in block.stmts.iter().rev() {
+        live.remove(&s.reg);
+        s.expr.each_reg(&mut |r| {
+            live.insert(r);
+        });
+    }
+
+    live
+}
+
+/// What's still needed at the end of each block. Anywhere a computed jump could go
+/// needs everything, halting only needs a since that's where the answers are.
+fn live_out(cfg: &Cfg) -> Vec<Regs> {
+    let all = all_regs(cfg);
+    let mut out = vec![Regs::new(); cfg.blocks.len()];
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+
+        for (b, block) in cfg.blocks.iter().enumerate().rev() {
+            let mut live = Regs::new();
+            match &block.term {
+                Term::Jump(..) | Term::Fault => live = all.clone(),
+                _ => (),
+            }
+
+            for t in block.succs() {
+                match t {
+                    Target::Block(n) => live.extend(live_in(&cfg.blocks[n], out[n].clone())),
+                    Target::Halt => {
+                        live.insert(0);
+                    }
+                }
+            }
+
+            if live != out[b] {
+                out[b] = live;
+                changed = true;
+            }
+        }
+    }
+
+    out
+}
+
+/// Fold values that are used once, straight away, into where they're used, so the
+/// temporaries go away and comparisons end up as the branch conditions
+fn fold(cfg: &Cfg) -> Vec<Block> {
+    let outs = live_out(cfg);
+
+    cfg.blocks
+        .iter()
+        .zip(outs)
+        .map(|(block, out)| {
+            let mut stmts = block.stmts.clone();
+            let mut term = block.term.clone();
+
+            // What's live after each statement
+            let mut after = vec![Regs::new(); stmts.len()];
+            let mut live = live_in(
+                &Block {
+                    stmts: vec![],
+                    ..block.clone()
+                },
+                out.clone(),
+            );
+            for (i, s) in stmts.iter().enumerate().rev() {
+                after[i] = live.clone();
+                live.remove(&s.reg);
+                s.expr.each_reg(&mut |r| {
+                    live.insert(r);
+                });
+            }
+
+            let mut i = 0;
+            while i < stmts.len() {
+                let r = stmts[i].reg;
+                let ok = match stmts.get(i + 1) {
+                    Some(next) => {
+                        next.expr.reads(r) == 1 && (next.reg == r || !after[i + 1].contains(&r))
+                    }
+                    None => term_expr(&term).is_some_and(|e| e.reads(r) == 1) && !out.contains(&r),
+                };
+
+                if !ok {
+                    i += 1;
+                    continue;
+                }
+
+                let s = stmts.remove(i);
+                after.remove(i);
+                match stmts.get_mut(i) {
+                    Some(next) => next.expr.substitute(r, &s.expr),
+                    None => match &mut term {
+                        Term::Branch(e, ..) | Term::Jump(e, _) => e.substitute(r, &s.expr),
+                        _ => unreachable!(),
+                    },
+                }
+
+                // What came before might fold into the bigger one now
+                i = i.saturating_sub(1);
+            }
+
+            Block {
+                stmts,
+                term,
+                ..block.clone()
+            }
+        })
+        .collect()
+}
+
+struct Structurer {
+    blocks: Vec<Block>,
+    reachable: Vec<bool>,
+    preds: Vec<Vec<usize>>,
+    dom: Vec<Vec<bool>>,
+    /// Header and where it exits to, innermost last
+    loops: Vec<(usize, Target)>,
+}
+
+impl Structurer {
+    /// What runs after block `i` if nothing jumps, `after` once past `hi`
+    fn next(&self, i: usize, hi: usize, after: Target) -> Target {
+        (i + 1..hi)
+            .find(|&j| self.reachable[j])
+            .map_or(after, Target::Block)
+    }
+
+    /// Nothing if it's where it'd go anyway
+    fn jump(&self, t: Target, next: Target) -> Option<Node> {
+        if t == next {
+            return None;
+        }
+
+        if let Some(&(header, follow)) = self.loops.last() {
+            if t == Target::Block(header) {
+                return Some(Node::Continue);
+            }
+            if t == follow {
+                return Some(Node::Break);
+            }
+        }
+
+        Some(match t {
+            Target::Block(b) => Node::Goto(self.blocks[b].start),
+            Target::Halt => Node::Halt,
+        })
+    }
+
+    /// Blocks `lo` to `hi` as nodes, `after` is what comes next. `header` is the loop
+    /// being emitted, if it starts at `lo`.
+    fn emit(&mut self, lo: usize, hi: usize, after: Target, header: Option<usize>) -> Vec<Node> {
+        let mut nodes = vec![];
+        let mut i = lo;
+
+        while i < hi {
+            if !self.reachable[i] {
+                i += 1;
+                continue;
+            }
+
+            if header != Some(i) {
+                if let Some((latch, follow)) = self.natural_loop(i, hi, after) {
+                    self.loops.push((i, follow));
+                    let body = self.emit(i, latch + 1, Target::Block(i), Some(i));
+                    self.loops.pop();
+
+                    nodes.push(Node::Loop(body));
+                    nodes.extend(self.jump(follow, self.next(latch, hi, after)));
+                    i = latch + 1;
+                    continue;
+                }
+            }
+
+            let block = self.blocks[i].clone();
+            let next = self.next(i, hi, after);
+
+            nodes.push(Node::Label(block.start));
+            nodes.extend(block.stmts.into_iter().map(|s| Node::Assign(s.reg, s.expr)));
+
+            match block.term {
+                Term::Goto(t) => nodes.extend(self.jump(t, next)),
+                Term::Fault => nodes.push(Node::Fault(block.end - 1)),
+                Term::Jump(e, likely) => {
+                    let pcs = likely.into_iter().filter_map(|t| match t {
+                        Target::Block(b) => Some(self.blocks[b].start),
+                        Target::Halt => None,
+                    });
+                    nodes.push(Node::Jump(e, pcs.collect()));
+                }
+                Term::Branch(c, t, f) => {
+                    if let Some((node, resume)) = self.structured_if(i, &c, t, f, hi, after) {
+                        nodes.push(node);
+                        i = resume;
+                        continue;
+                    }
+
+                    match (self.jump(t, next), self.jump(f, next)) {
+                        (None, None) => (),
+                        (Some(j), None) => nodes.push(Node::If(c, vec![j], vec![])),
+                        (None, Some(j)) => nodes.push(Node::If(!c, vec![j], vec![])),
+                        (Some(tj), Some(fj)) => {
+                            nodes.push(Node::If(c, vec![tj], vec![]));
+                            nodes.push(fj);
+                        }
+                    }
+                }
+            }
+
+            i += 1;
+        }
+
+        nodes
+    }
+
+    /// The last block that jumps back to `b` and where the loop exits to, if `b` heads
+    /// a loop laid out as one run of blocks
+    fn natural_loop(&self, b: usize, hi: usize, after: Target) -> Option<(usize, Target)> {
+        let latches: Vec<usize> = (self.preds[b].iter().copied())
+            .filter(|&p| p >= b && self.dom[p][b])
+            .collect();
+        let latch = *latches.iter().max()?;
+        if latch >= hi {
+            return None;
+        }
+
+        let mut body = HashSet::new();
+        body.insert(b);
+        let mut todo = latches;
+        while let Some(x) = todo.pop() {
+            if body.insert(x) {
+                todo.extend(&self.preds[x]);
+            }
+        }
+
+        let inside = |x: &usize| (b..=latch).contains(x);
+        if !body.iter().all(inside) || (b..=latch).any(|x| self.reachable[x] && !body.contains(&x))
+        {
+            return None;
+        }
+
+        let mut exits: Vec<Target> = (body.iter())
+            .flat_map(|&x| self.blocks[x].succs())
+            .filter(|t| !matches!(t, Target::Block(n) if body.contains(n)))
+            .collect();
+        exits.sort();
+        exits.dedup();
+
+        let follow = match exits[..] {
+            [t] => t,
+            _ => self.next(latch, hi, after),
+        };
+        Some((latch, follow))
+    }
+
+    /// Only ever entered at `lo`, and there only from `from`
+    fn single_entry(&self, lo: usize, hi: usize, from: usize) -> bool {
+        (lo..hi).filter(|&j| self.reachable[j]).all(|j| {
+            (self.preds[j].iter()).all(|&p| (lo..hi).contains(&p) || (j == lo && p == from))
+        })
+    }
+
+    /// An if, or an if/else, when one way is the next block and the other skips over
+    /// some. Gives back where to carry on from.
+    fn structured_if(
+        &mut self,
+        b: usize,
+        cond: &Expr,
+        t: Target,
+        f: Target,
+        hi: usize,
+        after: Target,
+    ) -> Option<(Node, usize)> {
+        let next = self.next(b, hi, after);
+        let (near, far, cond) = if t == next {
+            (t, f, cond.clone())
+        } else if f == next {
+            (f, t, !cond.clone())
+        } else {
+            return None;
+        };
+
+        // Falling out of a loop body is going back to the top, that's no if
+        let x = match near {
+            Target::Block(x) if b < x && x < hi => x,
+            _ => return None,
+        };
+        let end = match far {
+            // Whatever's after `hi` goes after the if too
+            Target::Block(y) if x < y && y < hi => y,
+            _ if far == after => hi,
+            _ => return None,
+        };
+        if !self.single_entry(x, end, b) {
+            return None;
+        }
+
+        // The then part jumps over the else
+        let last = (x..end).rev().find(|&j| self.reachable[j])?;
+        if let (Target::Block(y), Term::Goto(join)) = (far, &self.blocks[last].term) {
+            let join = *join;
+            let z = match join {
+                Target::Block(z) if y < z && z < hi => Some(z),
+                _ if join == after && y < hi => Some(hi),
+                _ => None,
+            };
+
+            if let Some(z) = z.filter(|&z| self.preds[y] == [b] && self.single_entry(y, z, b)) {
+                let then = self.emit(x, y, join, None);
+                let els = self.emit(y, z, join, None);
+                return Some((Node::If(cond, then, els), z));
+            }
+        }
+
+        let then = self.emit(x, end, far, None);
+        Some((Node::If(cond, then, vec![]), end))
+    }
+}
+
+/// Pcs that something jumps to, they need labels
+fn targets(nodes: &[Node], out: &mut HashSet<usize>) {
+    for n in nodes {
+        match n {
+            Node::Goto(pc) => {
+                out.insert(*pc);
+            }
+            Node::Jump(_, pcs) => out.extend(pcs),
+            Node::If(_, t, e) => {
+                targets(t, out);
+                targets(e, out);
+            }
+            Node::Loop(body) => targets(body, out),
+            _ => (),
+        }
+    }
+}
+
+/// Drop labels nobody needs and turn loops into whiles and fors where they fit
+fn tidy(nodes: Vec<Node>, gotos: &HashSet<usize>) -> Vec<Node> {
+    let mut out: Vec<Node> = vec![];
+
+    for n in nodes {
+        let n = match n {
+            Node::Label(pc) if !gotos.contains(&pc) => continue,
+            Node::If(c, t, e) => Node::If(c, tidy(t, gotos), tidy(e, gotos)),
+            Node::Loop(body) => shape_loop(tidy(body, gotos)),
+            n => n,
+        };
+
+        match (out.last(), n) {
+            (Some(Node::Assign(r, _)), Node::While(cond, mut body))
+                if cond.reads(*r) > 0 && is_step(body.last(), *r) && !has_continue(&body) =>
+            {
+                let step = Box::new(body.pop().unwrap());
+                let init = Box::new(out.pop().unwrap());
+                out.push(Node::For {
+                    init,
+                    cond,
+                    step,
+                    body,
+                });
+            }
+            (_, n) => out.push(n),
+        }
+    }
+
+    out
+}
+
+fn is_break(nodes: &[Node]) -> bool {
+    nodes == [Node::Break]
+}
+
+/// A loop checking at the top is a while, at the bottom a do/while, but `continue`
+/// skips the check at the bottom so not those
+fn shape_loop(mut body: Vec<Node>) -> Node {
+    if body.last() == Some(&Node::Continue) {
+        body.pop();
+    }
+
+    if let Some(Node::If(c, t, e)) = body.first() {
+        if is_break(t) && e.is_empty() {
+            let c = !c.clone();
+            body.remove(0);
+            return Node::While(c, body);
+        }
+    }
+
+    if let Some(Node::If(c, t, e)) = body.last() {
+        if is_break(t) && e.is_empty() && !has_continue(&body) {
+            let c = !c.clone();
+            body.pop();
+            return Node::DoWhile(body, c);
+        }
+    }
+
+    Node::Loop(body)
+}
+
+/// Whether `r` gets changed based on itself
+fn is_step(n: Option<&Node>, r: usize) -> bool {
+    matches!(n, Some(Node::Assign(x, e)) if *x == r && e.reads(r) > 0)
+}
+
+/// Continues for this loop, not ones inside it
+fn has_continue(nodes: &[Node]) -> bool {
+    nodes.iter().any(|n| match n {
+        Node::Continue => true,
+        Node::If(_, t, e) => has_continue(t) || has_continue(e),
+        _ => false,
+    })
+}
+
+/// `a = a + 1` as `a += 1`
+fn assign(r: usize, e: &Expr) -> String {
+    let name = reg_name(r);
+
+    if let Expr::Bin(op, a, b) = e {
+        if matches!(op, BinOp::Add | BinOp::Mul | BinOp::And | BinOp::Or) {
+            let other = match (&**a, &**b) {
+                (Expr::Reg(x), other) if *x == r => Some(other),
+                (other, Expr::Reg(x)) if *x == r => Some(other),
+                _ => None,
+            };
+            if let Some(other) = other {
+                return format!("{} {}= {}", name, op.symbol(), other);
+            }
+        }
+    }
+
+    format!("{} = {}", name, e)
+}
+
+/// A break or the like, short enough to go on the same line as its if
+fn one_liner(n: &Node) -> Option<String> {
+    match n {
+        Node::Break => Some("break;".to_owned()),
+        Node::Continue => Some("continue;".to_owned()),
+        Node::Goto(pc) => Some(format!("goto L{};", pc)),
+        Node::Halt => Some("halt;".to_owned()),
+        _ => None,
+    }
+}
+
+fn render(nodes: &[Node], depth: usize, out: &mut String) {
+    let ind = "    ".repeat(depth);
+    let block = |head: String, body: &[Node], tail: &str, out: &mut String| {
+        if body.is_empty() {
+            writeln!(out, "{}{} {{}}{}", ind, head, tail).unwrap();
+        } else {
+            writeln!(out, "{}{} {{", ind, head).unwrap();
+            render(body, depth + 1, out);
+            writeln!(out, "{}}}{}", ind, tail).unwrap();
+        }
+    };
+
+    for n in nodes {
+        if let Some(s) = one_liner(n) {
+            writeln!(out, "{}{}", ind, s).unwrap();
+            continue;
+        }
+
+        match n {
+            Node::Assign(r, e) => writeln!(out, "{}{};", ind, assign(*r, e)).unwrap(),
+            Node::If(c, t, e) if e.is_empty() && t.len() == 1 && one_liner(&t[0]).is_some() => {
+                writeln!(out, "{}if ({}) {}", ind, c, one_liner(&t[0]).unwrap()).unwrap()
+            }
+            Node::If(c, t, e) if e.is_empty() => block(format!("if ({})", c), t, "", out),
+            Node::If(c, t, e) => {
+                writeln!(out, "{}if ({}) {{", ind, c).unwrap();
+                render(t, depth + 1, out);
+                writeln!(out, "{}}} else {{", ind).unwrap();
+                render(e, depth + 1, out);
+                writeln!(out, "{}}}", ind).unwrap();
+            }
+            Node::Loop(body) => block("while (true)".to_owned(), body, "", out),
+            Node::While(c, body) => block(format!("while ({})", c), body, "", out),
+            Node::DoWhile(body, c) => {
+                block("do".to_owned(), body, &format!(" while ({});", c), out)
+            }
+            Node::For {
+                init,
+                cond,
+                step,
+                body,
+            } => {
+                let text = |n: &Node| match n {
+                    Node::Assign(r, e) => assign(*r, e),
+                    _ => unreachable!(),
+                };
+                let head = format!("for ({}; {}; {})", text(init), cond, text(step));
+                block(head, body, "", out);
+            }
+            Node::Jump(e, _) => writeln!(out, "{}goto {};", ind, e).unwrap(),
+            Node::Fault(pc) => writeln!(
+                out,
+                "{}fault;  // pc {} names a register that can't be there",
+                ind, pc
+            )
+            .unwrap(),
+            Node::Label(pc) => writeln!(out, "L{}:", pc).unwrap(),
+            Node::Break | Node::Continue | Node::Goto(_) | Node::Halt => unreachable!(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::op::{Inst, Opcode};
+    use common::fuzz::check;
+
+    fn dec(s: &str) -> String {
+        decompile(&s.parse().unwrap())
+    }
+
+    #[test]
+    fn loops() {
+        // The divisor sum from day 19, shifted up one
+        let p = "#ip 2
+seti 1 1 3
+seti 1 7 5
+mulr 3 5 4
+eqrr 4 1 4
+addr 4 2 2
+addi 2 1 2
+addr 3 0 0
+addi 5 1 5
+gtrr 5 1 4
+addr 2 4 2
+seti 1 3 2
+addi 3 1 3
+gtrr 3 1 4
+addr 4 2 2
+seti 0 9 2
+mulr 2 2 2";
+
+        assert_eq!(
+            dec(p),
+            "// c is the ip
+d = 1;
+do {
+    f = 1;
+    do {
+        if (d * f == b) {
+            a += d;
+        }
+        f += 1;
+    } while (f <= b);
+    d += 1;
+} while (d <= b);
+"
+        );
+
+        // The inner loop from day 21, checking at the top
+        let p = "#ip 2
+seti 0 1 5
+addi 5 1 1
+muli 1 256 1
+gtrr 1 4 1
+addr 1 2 2
+addi 2 1 2
+seti 8 1 2
+addi 5 1 5
+seti 0 8 2
+setr 5 2 0";
+
+        assert_eq!(
+            dec(p),
+            "// c is the ip
+for (f = 0; (f + 1) * 256 <= e; f += 1) {}
+a = f;
+"
+        );
+    }
+
+    #[test]
+    fn if_else() {
+        let p = "#ip 5
+eqri 0 0 1
+addr 1 5 5
+seti 4 0 5
+seti 7 0 2
+seti 5 0 5
+seti 9 0 2
+setr 2 0 3";
+
+        assert_eq!(
+            dec(p),
+            "// f is the ip
+if (a == 0) {
+    c = 7;
+} else {
+    c = 9;
+}
+d = c;
+"
+        );
+    }
+
+    #[test]
+    fn gotos() {
+        // Where it goes depends on a, so the places it could go get labels
+        assert_eq!(
+            dec("#ip 1\naddr 1 0 1\nseti 5 0 2\nseti 6 0 3"),
+            "// b is the ip\ngoto 1 + a;\nL1:\nc = 5;\nL2:\nd = 6;\n"
+        );
+
+        // The add is jumped to from past the compare, so b needn't be 0 or 1
+        assert_eq!(
+            dec("#ip 5\nseti 4 0 1\nseti 2 0 5\ngtri 0 5 1\naddr 1 5 5\nseti 7 0 2\nseti 9 0 3"),
+            "// f is the ip\nb = 4;\ngoto 4 + b;\nL4:\nc = 7;\nL5:\nd = 9;\n"
+        );
+
+        // Landing on the compare still leaves b a 0 or 1, so it's an if
+        assert_eq!(
+            dec("#ip 5\nseti 4 0 1\nseti 2 0 5\nseti 9 0 4\ngtri 0 5 1\naddr 1 5 5\nseti 7 0 2\nseti 9 0 3"),
+            "// f is the ip\nb = 4;\nif (a <= 5) {\n    c = 7;\n}\nd = 9;\n"
+        );
+
+        // No binding means no jumps
+        assert_eq!(dec("addi 0 1 0\nmulr 0 0 1"), "a += 1;\nb = a * a;\n");
+        assert_eq!(
+            dec("seti 1 0 -1"),
+            "fault;  // pc 0 names a register that can't be there\n"
+        );
+    }
+
+    #[test]
+    fn any_program() {
+        // Small registers and jumps so there's plenty of control flow to get confused by
+        let gen = |r: &mut common::fuzz::Rng| {
+            let insts = (0..r.below(24))
+                .map(|_| {
+                    let op = *r.pick(&Opcode::ALL);
+                    let mut arg = || r.range(-1, 24) as i32;
+                    Inst::new(op, [arg(), arg(), r.range(0, 4) as i32])
+                })
+                .collect();
+            Program::new(Some(r.below(5)), insts)
+        };
+
+        check(2000, gen, |p| {
+            decompile(p);
+        });
+    }
+}
@@ -0,0 +1,326 @@
+//! The control flow graph as Graphviz DOT, `dot -Tsvg` draws it. Every write to the ip
+//! register ends a block, with an edge to each place it can go when that's known and a
+//! dashed one when it's worked out at run time. Given a profile, edges say how often
+//! they were taken and the hot ones are drawn thicker.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use crate::cfg::{Cfg, Target, Term};
+use crate::profile::Profile;
+use crate::program::Program;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Node {
+    Block(usize),
+    Halt,
+    Fault,
+    /// Where a computed jump goes, before it's been seen
+    Unknown,
+}
+
+impl Node {
+    fn id(self) -> String {
+        match self {
+            Node::Block(b) => format!("b{}", b),
+            Node::Halt => "halt".to_owned(),
+            Node::Fault => "fault".to_owned(),
+            Node::Unknown => "unknown".to_owned(),
+        }
+    }
+}
+
+impl From<Target> for Node {
+    fn from(t: Target) -> Node {
+        match t {
+            Target::Block(b) => Node::Block(b),
+            Target::Halt => Node::Halt,
+        }
+    }
+}
+
+struct Edge {
+    from: usize,
+    to: Node,
+    label: String,
+    dashed: bool,
+}
+
+/// The edges out of each block the program says are there
+fn edges(cfg: &Cfg) -> Vec<Edge> {
+    let mut edges = vec![];
+
+    for (b, block) in cfg.blocks.iter().enumerate() {
+        let mut edge = |to: Node, label: String, dashed| {
+            edges.push(Edge {
+                from: b,
+                to,
+                label,
+                dashed,
+            })
+        };
+
+        match &block.term {
+            Term::Goto(t) => edge((*t).into(), String::new(), false),
+            Term::Branch(c, t, f) => {
+                edge((*t).into(), c.to_string(), false);
+                edge((*f).into(), "else".to_owned(), false);
+            }
+            Term::Jump(e, likely) if likely.is_empty() => {
+                edge(Node::Unknown, format!("pc = {}", e), true)
+            }
+            Term::Jump(e, likely) => {
+                for &t in likely {
+                    edge(t.into(), format!("pc = {}", e), true);
+                }
+            }
+            Term::Fault => edge(Node::Fault, String::new(), false),
+        }
+    }
+
+    edges
+}
+
+/// Times each block went to each place, from where the pc went after its last
+/// instruction. Shortcuts in the profile have already been spread over the jumps of
+/// the loops they stand for.
+fn taken(cfg: &Cfg, profile: &Profile) -> BTreeMap<(usize, Node), u64> {
+    let node = |pc: usize| match cfg.block_at(pc) {
+        Some(b) => Node::Block(b),
+        None => Node::Halt,
+    };
+    let mut taken = BTreeMap::new();
+
+    for (b, block) in cfg.blocks.iter().enumerate() {
+        let last = block.end - 1;
+        let mut jumped = 0;
+
+        for (&(from, to), &n) in &profile.jumps {
+            if from == last {
+                *taken.entry((b, node(to))).or_insert(0) += n;
+                jumped += n;
+            }
+        }
+
+        let fell = profile.hits[last] - jumped;
+        if fell > 0 {
+            *taken.entry((b, node(block.end))).or_insert(0) += fell;
+        }
+    }
+
+    taken
+}
+
+/// The graph of `program`, with how often edges were taken if there's a `profile` of it
+pub fn to_dot(program: &Program, profile: Option<&Profile>) -> String {
+    let cfg = Cfg::unthreaded(program);
+    let mut edges = edges(&cfg);
+    let mut taken = profile.map(|p| taken(&cfg, p)).unwrap_or_default();
+    let mut out = String::new();
+
+    writeln!(out, "digraph elfcode {{").unwrap();
+    writeln!(out, "    node [shape=box fontname=monospace];").unwrap();
+    writeln!(out, "    edge [fontname=monospace];").unwrap();
+
+    for (b, block) in cfg.blocks.iter().enumerate() {
+        let mut label = String::new();
+        if let Some(p) = profile {
+            write!(label, "ran {} times\\l", p.hits[block.start]).unwrap();
+        }
+        for pc in block.start..block.end {
+            write!(label, "{:>3}: {}\\l", pc, program.insts[pc]).unwrap();
+        }
+        writeln!(out, "    {} [label=\"{}\"];", Node::Block(b).id(), label).unwrap();
+    }
+
+    // Only the ones something goes to
+    let mut ends: Vec<Node> = (edges.iter().map(|e| e.to))
+        .chain(taken.keys().map(|&(_, to)| to))
+        .filter(|to| !matches!(to, Node::Block(_)))
+        .collect();
+    ends.sort();
+    ends.dedup();
+    for end in ends {
+        let attrs = match end {
+            Node::Halt => "shape=doublecircle",
+            Node::Fault => "shape=octagon color=red",
+            _ => "shape=plaintext label=\"?\"",
+        };
+        writeln!(out, "    {} [{}];", end.id(), attrs).unwrap();
+    }
+
+    // Where computed jumps went, that the program doesn't say
+    for (&(from, to), _) in taken.iter() {
+        if !edges.iter().any(|e| (e.from, e.to) == (from, to)) {
+            edges.push(Edge {
+                from,
+                to,
+                label: String::new(),
+                dashed: true,
+            });
+        }
+    }
+
+    let most = taken.values().copied().max().unwrap_or(0);
+    for e in &edges {
+        let mut attrs = vec![];
+        let mut label = e.label.clone();
+
+        if profile.is_some() {
+            // An edge that's there twice, like a branch to the next block either way,
+            // gets its count the once
+            let n = taken.remove(&(e.from, e.to)).unwrap_or(0);
+            if n == 0 {
+                attrs.push("color=gray".to_owned());
+            } else {
+                let sep = if label.is_empty() { "" } else { ", " };
+                label = format!("{}{}{}x", label, sep, n);
+                attrs.push(format!(
+                    "penwidth={:.1}",
+                    1.0 + 4.0 * n as f64 / most as f64
+                ));
+            }
+        }
+        if !label.is_empty() {
+            attrs.push(format!("label=\"{}\"", label));
+        }
+        if e.dashed {
+            attrs.push("style=dashed".to_owned());
+        }
+
+        let attrs = if attrs.is_empty() {
+            String::new()
+        } else {
+            format!(" [{}]", attrs.join(" "))
+        };
+        writeln!(
+            out,
+            "    {} -> {}{};",
+            Node::Block(e.from).id(),
+            e.to.id(),
+            attrs
+        )
+        .unwrap();
+    }
+
+    writeln!(out, "}}").unwrap();
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::Vm;
+
+    /// Counts r1 up to 3 in a loop, then squares it
+    const LOOP: &str = "#ip 0
+addi 1 1 1
+gtri 1 2 2
+addr 0 2 0
+seti -1 0 0
+mulr 1 1 1";
+
+    #[test]
+    fn graph() {
+        let p: Program = LOOP.parse().unwrap();
+        assert_eq!(
+            to_dot(&p, None),
+            r#"digraph elfcode {
+    node [shape=box fontname=monospace];
+    edge [fontname=monospace];
+    b0 [label="  0: addi 1 1 1\l  1: gtri 1 2 2\l  2: addr 0 2 0\l"];
+    b1 [label="  3: seti -1 0 0\l"];
+    b2 [label="  4: mulr 1 1 1\l"];
+    halt [shape=doublecircle];
+    b0 -> b2 [label="c"];
+    b0 -> b1 [label="else"];
+    b1 -> b0;
+    b2 -> halt;
+}
+"#
+        );
+    }
+
+    #[test]
+    fn onto_compare() {
+        // Pc 1 jumps over pc 2 onto the compare, the add still only goes two ways
+        let p: Program = "#ip 5
+seti 4 0 1
+seti 2 0 5
+seti 9 0 4
+gtri 0 5 1
+addr 1 5 5
+seti 7 0 2
+seti 9 0 3"
+            .parse()
+            .unwrap();
+        let dot = to_dot(&p, None);
+
+        assert!(dot.contains("b2 [label=\"  3: gtri 0 5 1\\l  4: addr 1 5 5\\l\"];"));
+        assert!(dot.contains("b2 -> b4 [label=\"b\"];"));
+        assert!(dot.contains("b2 -> b3 [label=\"else\"];"));
+        assert_eq!(dot.matches("b2 ->").count(), 2);
+    }
+
+    #[test]
+    fn counts() {
+        let p: Program = LOOP.parse().unwrap();
+        let mut vm = Vm::new(p.clone(), [0; 3]);
+        let profile = Profile::run(&mut vm, None);
+        let dot = to_dot(&p, Some(&profile));
+
+        assert!(dot.contains("b0 [label=\"ran 3 times\\l  0: addi"));
+        assert!(dot.contains("b0 -> b2 [penwidth=3.0 label=\"c, 1x\"];"));
+        assert!(dot.contains("b0 -> b1 [penwidth=5.0 label=\"else, 2x\"];"));
+        assert!(dot.contains("b1 -> b0 [penwidth=5.0 label=\"2x\"];"));
+        assert!(dot.contains("b2 -> halt [penwidth=3.0 label=\"1x\"];"));
+
+        // Computed jumps only get their targets from a run
+        let p: Program = "#ip 0\nsetr 1 0 0\nseti 5 0 1\nseti 0 0 2".parse().unwrap();
+        let dot = to_dot(&p, None);
+        assert!(dot.contains("unknown [shape=plaintext label=\"?\"];"));
+        assert!(dot.contains("b0 -> unknown [label=\"pc = b + 1\" style=dashed];"));
+
+        let mut vm = Vm::new(p.clone(), [0, 1, 0]);
+        let dot = to_dot(&p, Some(&Profile::run(&mut vm, None)));
+        assert!(dot.contains("b0 -> unknown [color=gray label=\"pc = b + 1\" style=dashed];"));
+        assert!(dot.contains("b0 -> b1 [penwidth=5.0 label=\"1x\" style=dashed];"));
+    }
+
+    #[test]
+    fn shortcuts() {
+        // Day 19's loops, which the optimizer runs in one go from pc 0
+        let p: Program = "#ip 2
+seti 1 1 3
+seti 1 7 5
+mulr 3 5 4
+eqrr 4 1 4
+addr 4 2 2
+addi 2 1 2
+addr 3 0 0
+addi 5 1 5
+gtrr 5 1 4
+addr 2 4 2
+seti 1 3 2
+addi 3 1 3
+gtrr 3 1 4
+addr 4 2 2
+seti 0 9 2
+seti 99 0 2"
+            .parse()
+            .unwrap();
+        let reg = [0, 12, 0, 0, 0, 0];
+
+        let mut slow = Vm::new(p.clone(), reg);
+        let mut fast = Vm::new(p.clone(), reg).optimized();
+        let dot = to_dot(&p, Some(&Profile::run(&mut fast, None)));
+        assert_eq!(dot, to_dot(&p, Some(&Profile::run(&mut slow, None))));
+
+        // 12 has 6 divisors
+        assert!(dot.contains("b2 -> b4 [penwidth=1.2 label=\"e, 6x\"];"));
+        assert!(dot.contains("b2 -> b3 [penwidth=5.0 label=\"else, 138x\"];"));
+        assert!(dot.contains("b6 -> b2 [penwidth=4.8 label=\"132x\"];"));
+        assert!(dot.contains("b8 -> b1 [penwidth=1.3 label=\"11x\"];"));
+        assert!(dot.contains("b9 -> halt [penwidth=1.0 label=\"1x\"];"));
+    }
+}
@@ -0,0 +1,285 @@
+//! Which values of r0 make a program halt, for programs like day 21's that only read r0
+//! in an `eqrr` whose true side runs off the end. Those are the values the other
+//! register has each time it gets to the check, so one run with the check forced false
+//! finds them all, until the state at the check repeats.
+
+use std::collections::HashSet;
+use std::convert::TryFrom;
+use std::error;
+use std::fmt;
+
+use crate::op::{Opcode, Src};
+use crate::program::Program;
+use crate::vm::{Fault, Vm};
+use crate::word::{Overflow, Word};
+
+/// `eqrr` at `pc` comparing `reg` with r0, that halts after `tail` more instructions
+/// when they're equal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Check {
+    pub pc: usize,
+    pub reg: usize,
+    pub tail: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    NoCheck,
+    /// r0 gets used somewhere other than a check, at this pc
+    UsesR0(usize),
+    Fault(Fault),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::NoCheck => write!(f, "no eqrr against r0 that halts the program"),
+            Error::UsesR0(pc) => write!(f, "pc {} uses r0 outside a halting check", pc),
+            Error::Fault(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+/// r0 = `value` halts after `steps` instructions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Halt<W> {
+    pub value: W,
+    pub steps: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Halts<W> {
+    pub checks: Vec<Check>,
+    /// Each new value in the order they come up, so quickest first
+    pub values: Vec<Halt<W>>,
+    /// How the search finished, only a cycle means there aren't any more
+    pub end: End,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum End {
+    Cycled,
+    /// It halted without r0 mattering, every value halts it that way from here
+    Halted,
+    Budget,
+}
+
+impl<W> Halts<W> {
+    pub fn fastest(&self) -> Option<&Halt<W>> {
+        self.values.first()
+    }
+
+    /// The one that takes longest, if the values ran out
+    pub fn last(&self) -> Option<&Halt<W>> {
+        self.values.last().filter(|_| self.end == End::Cycled)
+    }
+}
+
+/// Instructions from `pc` until it runs off the end, following jumps to fixed places.
+/// None if it could go anywhere else.
+fn straight_to_end(program: &Program, mut pc: usize) -> Option<u64> {
+    let ip = i32::try_from(program.ip?).ok()?;
+
+    // Longer than that and it's going round in circles
+    for steps in 0..=program.len() as u64 {
+        let inst = match program.insts.get(pc) {
+            Some(i) => i,
+            None => return Some(steps),
+        };
+        let [a, b, c] = inst.args;
+
+        pc = match inst.op {
+            _ if c != ip => pc + 1,
+            Opcode::Seti => usize::try_from(i64::from(a) + 1).ok()?,
+            Opcode::Addi if a == ip => usize::try_from(pc as i64 + i64::from(b) + 1).ok()?,
+            _ => return None,
+        };
+    }
+
+    None
+}
+
+/// The checks in `program`, which mustn't use r0 anywhere else
+pub fn checks(program: &Program) -> Result<Vec<Check>, Error> {
+    let ip = match program.ip.and_then(|ip| i32::try_from(ip).ok()) {
+        Some(ip) => ip,
+        None => return Err(Error::NoCheck),
+    };
+    let mut found = vec![];
+
+    for (pc, inst) in program.insts.iter().enumerate() {
+        let [a, b, c] = inst.args;
+        let (sa, sb) = inst.op.operands();
+        let reads_r0 = (sa == Src::Reg && a == 0) || (sb == Src::Reg && b == 0);
+
+        if !reads_r0 && c != 0 {
+            continue;
+        }
+
+        // The flag goes straight into a skip, and the instruction it skips to halts
+        let other = if a == 0 { b } else { a };
+        let skip = program.insts.get(pc + 1).filter(|j| {
+            let [ja, jb, jc] = j.args;
+            j.op == Opcode::Addr && jc == ip && ((ja, jb) == (c, ip) || (ja, jb) == (ip, c))
+        });
+        let check = match (inst.op, skip) {
+            (Opcode::Eqrr, Some(_)) if c != 0 && c != ip && other != 0 && other != ip => {
+                straight_to_end(program, pc + 3).and_then(|tail| {
+                    Some(Check {
+                        pc,
+                        reg: usize::try_from(other).ok()?,
+                        tail: tail + 1,
+                    })
+                })
+            }
+            _ => None,
+        };
+
+        found.push(check.ok_or(Error::UsesR0(pc))?);
+    }
+
+    if found.is_empty() {
+        return Err(Error::NoCheck);
+    }
+    Ok(found)
+}
+
+/// Run `vm` with every check failing, collecting the values that would have passed
+/// until the registers at a check repeat or `budget` more instructions have run. What's
+/// in r0 to start with doesn't matter.
+pub fn halting_values<const N: usize, W: Word>(
+    mut vm: Vm<N, W>,
+    budget: Option<u64>,
+) -> Result<Halts<W>, Error> {
+    let checks = checks(vm.program())?;
+    let mut states = HashSet::new();
+    let mut seen = HashSet::new();
+    let mut values = vec![];
+    let base = vm.steps();
+
+    let end = loop {
+        if vm.halted() {
+            break End::Halted;
+        }
+        if budget.is_some_and(|b| vm.steps() - base >= b) {
+            break End::Budget;
+        }
+
+        let pc = vm.pc();
+        if let Some(check) = checks.iter().find(|c| c.pc == pc) {
+            let value = *vm.reg.get(check.reg).ok_or_else(|| {
+                // Let the VM say what's wrong with it
+                Error::Fault(vm.clone().step().unwrap_err())
+            })?;
+
+            vm.reg[0] = W::ZERO;
+            if !states.insert((pc, vm.reg)) {
+                break End::Cycled;
+            }
+            if seen.insert(value) {
+                values.push(Halt {
+                    value,
+                    steps: vm.steps() + 1 + check.tail,
+                });
+            }
+
+            // Anything else fails the check
+            vm.reg[0] = value.add(W::ONE, Overflow::Wrapping).unwrap();
+        }
+
+        vm.step().map_err(Error::Fault)?;
+    };
+
+    Ok(Halts {
+        checks,
+        values,
+        end,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asm::assemble;
+
+    /// Steps r1 through 5, 3, 1, 4, 2 and round again, halting if r0 matches
+    const MOD: &str = "\
+#ip 5
+.reg x 1
+.reg t 2
+        seti 5 _ x
+top:    eqrr x r0 t
+        addr t ip ip
+        jmp next
+        jmp done
+next:   addi x 3 x
+        seti 5 _ t
+        gtrr x t t
+        addr ip t ip
+        jmp top
+        addi x -5 x
+        jmp top
+done:   seti 99 _ r3
+";
+
+    #[test]
+    fn finds_values() {
+        let p = assemble(MOD).unwrap();
+        assert_eq!(
+            checks(&p).unwrap(),
+            vec![Check {
+                pc: 1,
+                reg: 1,
+                tail: 3
+            }]
+        );
+
+        let h = halting_values(Vm::new(p.clone(), [0_i64; 6]), None).unwrap();
+        assert_eq!(h.end, End::Cycled);
+        let values: Vec<i64> = h.values.iter().map(|h| h.value).collect();
+        assert_eq!(values, vec![5, 3, 1, 4, 2]);
+
+        // Each count is how long it really takes
+        for halt in &h.values {
+            let mut vm = Vm::new(p.clone(), [halt.value, 0, 0, 0, 0, 0]);
+            vm.run().unwrap();
+            assert_eq!(vm.steps(), halt.steps, "r0 = {}", halt.value);
+        }
+        assert_eq!(h.fastest().unwrap().value, 5);
+        assert_eq!(h.last().unwrap().value, 2);
+
+        // Out of budget there's no knowing which is last
+        let h = halting_values(Vm::new(p.clone(), [0_i64; 6]), Some(20)).unwrap();
+        assert_eq!(h.end, End::Budget);
+        assert_eq!(h.last(), None);
+
+        // The budget counts from wherever the VM has got to
+        let mut vm = Vm::new(p, [0_i64; 6]);
+        for _ in 0..25 {
+            vm.step().unwrap();
+        }
+        let h = halting_values(vm, Some(20)).unwrap();
+        assert_eq!(h.end, End::Budget);
+        let values: Vec<i64> = h.values.iter().map(|h| h.value).collect();
+        assert_eq!(values, vec![4, 2, 5]);
+    }
+
+    #[test]
+    fn rejects() {
+        // r0 read somewhere else too
+        let p = assemble(&MOD.replace("seti 5 _ t", "setr r0 _ t")).unwrap();
+        assert_eq!(checks(&p), Err(Error::UsesR0(6)));
+
+        // Equal doesn't halt
+        let p = assemble(&MOD.replace("jmp done", "jmp top")).unwrap();
+        assert_eq!(checks(&p), Err(Error::UsesR0(1)));
+
+        let p = assemble("#ip 5\naddi r1 1 r1").unwrap();
+        assert_eq!(
+            checks(&p).unwrap_err().to_string(),
+            "no eqrr against r0 that halts the program"
+        );
+    }
+}
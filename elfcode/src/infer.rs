@@ -0,0 +1,343 @@
+//! Working out which opcode number is which from day 16's samples. Each number gets
+//! the opcodes every one of its samples agrees with, then a search with the settled
+//! ones crossed off everywhere else finds the mappings that fit.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::op::{Args, Opcode};
+
+pub type Reg = [i32; 4];
+
+/// The registers either side of one instruction whose opcode is only a number
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sample {
+    pub before: Reg,
+    pub op: i32,
+    pub args: Args,
+    pub after: Reg,
+}
+
+impl Sample {
+    /// Opcodes that would have done it
+    pub fn candidates(&self) -> Vec<Opcode> {
+        ops(self.mask())
+    }
+
+    fn mask(&self) -> u16 {
+        (Opcode::ALL.iter().enumerate())
+            .filter(|(_, op)| op.apply(&self.before, self.args).as_ref() == Some(&self.after))
+            .fold(0, |m, (i, _)| m | 1 << i)
+    }
+}
+
+fn ops(mask: u16) -> Vec<Opcode> {
+    (Opcode::ALL.iter().enumerate())
+        .filter(|(i, _)| mask & 1 << i != 0)
+        .map(|(_, &op)| op)
+        .collect()
+}
+
+pub type Mapping = BTreeMap<i32, Opcode>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Solution {
+    Unique(Mapping),
+    /// Up to the limit asked for, `more` if there were others past it
+    Ambiguous {
+        mappings: Vec<Mapping>,
+        more: bool,
+    },
+    /// Indices of samples that can't all be right, but any fewer of them can
+    Unsat(Vec<usize>),
+}
+
+/// What each opcode number could be, by what its samples allow
+fn domains(samples: &[(i32, u16)]) -> (Vec<i32>, Vec<u16>) {
+    let mut doms: BTreeMap<i32, u16> = BTreeMap::new();
+    for &(op, mask) in samples {
+        *doms.entry(op).or_insert(u16::MAX) &= mask;
+    }
+    doms.into_iter().unzip()
+}
+
+/// Cross settled opcodes off the others until nothing changes, false when a number is
+/// left with nothing
+fn propagate(doms: &mut [u16]) -> bool {
+    let mut settled = 0;
+
+    loop {
+        let mut changed = false;
+
+        for i in 0..doms.len() {
+            let d = doms[i];
+            if d == 0 {
+                return false;
+            }
+            if d.count_ones() > 1 || settled & d != 0 {
+                continue;
+            }
+
+            settled |= d;
+            for (j, other) in doms.iter_mut().enumerate() {
+                if j != i && *other & d != 0 {
+                    *other &= !d;
+                    changed = true;
+                }
+            }
+        }
+
+        if !changed {
+            return true;
+        }
+    }
+}
+
+/// Every way to settle `doms`, up to `limit` of them
+fn search(mut doms: Vec<u16>, limit: usize, out: &mut Vec<Vec<u16>>) {
+    if out.len() >= limit || !propagate(&mut doms) {
+        return;
+    }
+
+    // Branch on whichever has fewest left
+    let open = (0..doms.len())
+        .filter(|&i| doms[i].count_ones() > 1)
+        .min_by_key(|&i| doms[i].count_ones());
+
+    let i = match open {
+        Some(i) => i,
+        None => return out.push(doms),
+    };
+
+    for bit in (0..16).map(|b| 1 << b).filter(|b| doms[i] & b != 0) {
+        let mut next = doms.clone();
+        next[i] = bit;
+        search(next, limit, out);
+        if out.len() >= limit {
+            return;
+        }
+    }
+}
+
+fn solutions(samples: &[(i32, u16)], limit: usize) -> Vec<Mapping> {
+    let (nums, doms) = domains(samples);
+    let mut found = vec![];
+    search(doms, limit, &mut found);
+
+    found
+        .into_iter()
+        .map(|doms| {
+            let op = |d: u16| Opcode::ALL[d.trailing_zeros() as usize];
+            nums.iter().copied().zip(doms.into_iter().map(op)).collect()
+        })
+        .collect()
+}
+
+/// Drop samples one at a time while what's left still contradicts itself
+fn minimal_unsat(samples: &[(i32, u16)]) -> Vec<usize> {
+    let mut keep: Vec<usize> = (0..samples.len()).collect();
+    let mut i = 0;
+
+    while i < keep.len() {
+        let without: Vec<usize> = (keep.iter().enumerate())
+            .filter(|&(j, _)| j != i)
+            .map(|(_, &k)| k)
+            .collect();
+        let picked: Vec<_> = without.iter().map(|&k| samples[k]).collect();
+
+        if solutions(&picked, 1).is_empty() {
+            keep = without;
+        } else {
+            i += 1;
+        }
+    }
+
+    keep
+}
+
+/// The mapping if there's only one, otherwise up to `limit` of them or the samples
+/// that rule them all out
+pub fn solve(samples: &[Sample], limit: usize) -> Solution {
+    let masks: Vec<(i32, u16)> = samples.iter().map(|s| (s.op, s.mask())).collect();
+    let mut found = solutions(&masks, limit.max(1) + 1);
+
+    match found.len() {
+        0 => Solution::Unsat(minimal_unsat(&masks)),
+        1 => Solution::Unique(found.pop().unwrap()),
+        n => {
+            found.truncate(limit);
+            Solution::Ambiguous {
+                mappings: found,
+                more: n > limit,
+            }
+        }
+    }
+}
+
+fn names(ops: impl IntoIterator<Item = Opcode>) -> String {
+    let names: Vec<_> = ops.into_iter().map(|o| o.name()).collect();
+    names.join("/")
+}
+
+impl fmt::Display for Solution {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Solution::Unique(m) => {
+                write!(f, "one mapping:")?;
+                for (n, op) in m {
+                    write!(f, " {}={}", n, op)?;
+                }
+                Ok(())
+            }
+            Solution::Ambiguous { mappings, more } => {
+                let plus = if *more { " or more" } else { "" };
+                write!(f, "{}{} mappings fit, undecided:", mappings.len(), plus)?;
+
+                // Numbers that differ between the mappings found
+                let mut could: BTreeMap<i32, Vec<Opcode>> = BTreeMap::new();
+                for (&n, &op) in mappings.iter().flatten() {
+                    let ops = could.entry(n).or_default();
+                    if !ops.contains(&op) {
+                        ops.push(op);
+                    }
+                }
+                for (n, ops) in could.into_iter().filter(|(_, ops)| ops.len() > 1) {
+                    write!(f, " {}={}", n, names(ops))?;
+                }
+                Ok(())
+            }
+            Solution::Unsat(bad) => {
+                let bad: Vec<_> = bad.iter().map(|i| i.to_string()).collect();
+                write!(f, "no mapping fits samples {} together", bad.join(", "))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::fuzz::{check, Rng};
+
+    fn sample(op: i32, real: Opcode, rng: &mut Rng) -> Sample {
+        let mut reg = || rng.below(4) as i32;
+        let before = [reg(), reg(), reg(), reg()];
+        let args = [reg(), reg(), reg()];
+
+        Sample {
+            before,
+            op,
+            args,
+            after: real.apply(&before, args).unwrap(),
+        }
+    }
+
+    #[test]
+    fn example() {
+        let s = Sample {
+            before: [3, 2, 1, 1],
+            op: 9,
+            args: [2, 1, 2],
+            after: [3, 2, 2, 1],
+        };
+        let names: Vec<_> = s.candidates().iter().map(|o| o.name()).collect();
+        assert_eq!(names, ["addi", "mulr", "seti"]);
+    }
+
+    #[test]
+    fn finds_the_real_one() {
+        // Enough samples usually pin it down, and whatever comes out has the real
+        // mapping in it
+        check(
+            100,
+            |r| {
+                let mut ops = Opcode::ALL.to_vec();
+                for i in (1..ops.len()).rev() {
+                    ops.swap(i, r.below(i + 1));
+                }
+                let samples = (0..r.below(200))
+                    .map(|_| {
+                        let n = r.below(16);
+                        sample(n as i32, ops[n], r)
+                    })
+                    .collect::<Vec<_>>();
+                (ops, samples)
+            },
+            |(ops, samples)| {
+                let real: Mapping = (samples.iter())
+                    .map(|s| (s.op, ops[s.op as usize]))
+                    .collect();
+
+                match solve(samples, 1000) {
+                    Solution::Unique(m) => assert_eq!(m, real),
+                    Solution::Ambiguous { mappings, more } => {
+                        assert!(more || mappings.contains(&real));
+                        assert!(mappings.iter().all(|m| m.keys().eq(real.keys())));
+                    }
+                    Solution::Unsat(_) => panic!("the real one fits"),
+                }
+            },
+        );
+    }
+
+    #[test]
+    fn ambiguous() {
+        // 1 + 0, 1 * 1, 1 | 0 and the rest all give 1, and nothing says which number is
+        // which
+        let s = |op| Sample {
+            before: [1, 0, 0, 0],
+            op,
+            args: [0, 1, 2],
+            after: [1, 0, 1, 0],
+        };
+        let samples = [s(4), s(7)];
+        let n = samples[0].candidates().len();
+
+        let sol = solve(&samples, 100);
+        match &sol {
+            Solution::Ambiguous { mappings, more } => {
+                assert!(!more);
+                assert_eq!(mappings.len(), n * (n - 1));
+            }
+            s => panic!("{}", s),
+        }
+        assert!(sol
+            .to_string()
+            .starts_with("72 mappings fit, undecided: 4=addr/"));
+
+        match solve(&samples, 1) {
+            Solution::Ambiguous { mappings, more } => {
+                assert_eq!((mappings.len(), more), (1, true))
+            }
+            s => panic!("{}", s),
+        }
+    }
+
+    #[test]
+    fn unsat() {
+        let mut rng = Rng::new(16);
+        let mut samples: Vec<Sample> = (0..40)
+            .map(|i| sample(i % 4, Opcode::ALL[(i % 4) as usize], &mut rng))
+            .collect();
+
+        // Two numbers that can only be seti, then something nothing could have done
+        let seti = |op| Sample {
+            before: [0, 0, 0, 0],
+            op,
+            args: [7, 0, 3],
+            after: [0, 0, 0, 7],
+        };
+        samples.insert(10, seti(12));
+        samples.insert(25, seti(13));
+
+        assert_eq!(solve(&samples, 10), Solution::Unsat(vec![10, 25]));
+        assert_eq!(
+            solve(&samples, 10).to_string(),
+            "no mapping fits samples 10, 25 together"
+        );
+
+        samples.remove(25);
+        samples[3].after = [9, 9, 9, 9];
+        assert_eq!(solve(&samples, 10), Solution::Unsat(vec![3]));
+    }
+}
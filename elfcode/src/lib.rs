@@ -0,0 +1,25 @@
+//! ElfCode, the little register machine from days 16, 19 and 21
+
+pub mod asm;
+pub mod cfg;
+pub mod compile;
+pub mod cycle;
+pub mod debug;
+pub mod decompile;
+pub mod dot;
+pub mod halt;
+pub mod infer;
+pub mod manual;
+pub mod op;
+pub mod optimize;
+pub mod profile;
+pub mod program;
+pub mod repl;
+pub mod snapshot;
+pub mod vm;
+pub mod word;
+
+pub use crate::op::{Args, Inst, Opcode};
+pub use crate::program::Program;
+pub use crate::vm::{Fault, FaultKind, Vm};
+pub use crate::word::{Overflow, Word};
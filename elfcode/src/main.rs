@@ -0,0 +1,508 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::process;
+
+use elfcode::asm::{assemble, disassemble};
+use elfcode::compile::{to_rust, Compiled};
+use elfcode::cycle;
+use elfcode::debug::Debugger;
+use elfcode::decompile::decompile;
+use elfcode::dot::to_dot;
+use elfcode::halt::{self, halting_values};
+use elfcode::infer::{solve, Solution};
+use elfcode::manual::Manual;
+use elfcode::profile::Profile;
+use elfcode::repl::Repl;
+use elfcode::snapshot::Log;
+use elfcode::{Overflow, Program, Vm};
+
+const USAGE: &str = "\
+Usage: elfcode run [--budget <STEPS>] [<VM OPTIONS>] <PROGRAM>
+       elfcode compile [--regs <N>] [--overflow <MODE>] <PROGRAM>
+       elfcode debug [<VM OPTIONS>] <PROGRAM>
+       elfcode repl [<VM OPTIONS>] [<PROGRAM>]
+       elfcode profile [--budget <STEPS>] [--loops <N>] [<VM OPTIONS>] <PROGRAM>
+       elfcode halts [--budget <STEPS>] [<VM OPTIONS>] <PROGRAM>
+       elfcode terminates [--budget <STEPS>] [<VM OPTIONS>] <PROGRAM>
+       elfcode record [--every <STEPS>] [--budget <STEPS>] [<VM OPTIONS>] <PROGRAM>
+       elfcode replay --log <FILE> [--from <N>] [--regs <N>] <PROGRAM>
+       elfcode dot [--counts] [--budget <STEPS>] [<VM OPTIONS>] <PROGRAM>
+       elfcode decompile <PROGRAM>
+       elfcode asm <PROGRAM>
+       elfcode disasm <PROGRAM>
+       elfcode infer <SAMPLES>
+
+VM options:
+  --regs <N>          how many registers, 4 when the program doesn't bind #ip and 6
+                      otherwise or when there's no program
+  --overflow <MODE>   wrapping, checked or saturating, wrapping by default
+  --set <REG>=<V>     start with a register set, like --set r0=1, can be repeated
+  --optimize          run the loops it knows, like day 19's divisor sum, natively
+  --resume <FILE>     carry on from the last snapshot in a file from record, with the
+                      registers and overflow mode it had then
+
+Registers are i64. run compiles the program to closures first and prints the
+registers it ends with, compile prints it as a Rust program that takes the
+registers as arguments. debug reads commands from stdin, type help to see them.
+repl runs instructions as they're typed and shows what they change, with no
+program to start with unless it's given one.
+profile runs until it halts or has used up the budget, then prints the program
+with how often each instruction ran and the hottest loops, 5 of them by default.
+halts finds where r0 gets compared to decide whether to stop, and which values of
+r0 would stop it, with the quickest and the last before they repeat.
+terminates runs it until it halts, goes round in exactly the same states again or
+runs out of budget, and says which, with where the cycle starts and how long it is.
+record runs it and prints a snapshot of the VM every so many steps, a million by
+default, and where it stopped. replay carries on from one of them, the first by
+default, and checks it gets to all the others.
+dot prints the basic blocks and the jumps between them for Graphviz, with
+--counts it runs the program first and says how often each jump was taken.
+decompile prints C-like pseudo-code with the registers named a, b, c, ...
+Programs ending in .asm are assembled first, asm prints them as plain ElfCode and
+disasm goes the other way, with labels and comments.
+infer works out the opcode numbers from a file of day 16 samples, or lists the
+ways they could go, or the fewest samples that contradict each other.";
+
+const LOOPS: usize = 5;
+const EVERY: u64 = 1_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Cmd {
+    Run,
+    Compile,
+    Debug,
+    Repl,
+    Profile,
+    Halts,
+    Terminates,
+    Record,
+    Replay,
+    Dot,
+    Decompile,
+    Asm,
+    Disasm,
+    Infer,
+}
+
+#[derive(Debug)]
+struct Opts {
+    cmd: Cmd,
+    regs: Option<usize>,
+    overflow: Overflow,
+    set: Vec<(usize, i64)>,
+    optimize: bool,
+    counts: bool,
+    budget: Option<u64>,
+    loops: Option<usize>,
+    resume: Option<String>,
+    every: Option<u64>,
+    log: Option<String>,
+    from: Option<usize>,
+    path: Option<String>,
+}
+
+fn parse_args(args: &[String]) -> Result<Opts, String> {
+    let mut it = args.iter();
+
+    let cmd = match it.next().map(|s| s.as_str()) {
+        Some("run") => Cmd::Run,
+        Some("compile") => Cmd::Compile,
+        Some("debug") => Cmd::Debug,
+        Some("repl") => Cmd::Repl,
+        Some("profile") => Cmd::Profile,
+        Some("halts") => Cmd::Halts,
+        Some("terminates") => Cmd::Terminates,
+        Some("record") => Cmd::Record,
+        Some("replay") => Cmd::Replay,
+        Some("dot") => Cmd::Dot,
+        Some("decompile") => Cmd::Decompile,
+        Some("asm") => Cmd::Asm,
+        Some("disasm") => Cmd::Disasm,
+        Some("infer") => Cmd::Infer,
+        Some(cmd) => return Err(format!("unknown command '{}'", cmd)),
+        None => return Err("missing command".to_owned()),
+    };
+
+    let mut opts = Opts {
+        cmd,
+        regs: None,
+        overflow: Overflow::default(),
+        set: vec![],
+        optimize: false,
+        counts: false,
+        budget: None,
+        loops: None,
+        resume: None,
+        every: None,
+        log: None,
+        from: None,
+        path: None,
+    };
+
+    while let Some(arg) = it.next() {
+        let mut value = |name: &str| {
+            it.next()
+                .cloned()
+                .ok_or_else(|| format!("{} needs a value", name))
+        };
+
+        match arg.as_str() {
+            "--regs" | "-r" => {
+                let v = value(arg)?;
+                opts.regs = Some(
+                    v.parse()
+                        .map_err(|_| format!("bad register count '{}'", v))?,
+                );
+            }
+            "--overflow" | "-o" => opts.overflow = value(arg)?.parse()?,
+            "--set" | "-s" => {
+                let v = value(arg)?;
+                opts.set
+                    .push(assignment(&v).ok_or_else(|| format!("bad --set '{}'", v))?);
+            }
+            "--optimize" | "-O" => opts.optimize = true,
+            "--counts" | "-c" => opts.counts = true,
+            "--budget" | "-b" => {
+                let v = value(arg)?;
+                opts.budget = Some(v.parse().map_err(|_| format!("bad budget '{}'", v))?);
+            }
+            "--loops" | "-l" => {
+                let v = value(arg)?;
+                opts.loops = Some(v.parse().map_err(|_| format!("bad loop count '{}'", v))?);
+            }
+            "--resume" => opts.resume = Some(value(arg)?),
+            "--every" | "-e" => {
+                let v = value(arg)?;
+                opts.every = Some(v.parse().map_err(|_| format!("bad step count '{}'", v))?);
+            }
+            "--log" => opts.log = Some(value(arg)?),
+            "--from" => {
+                let v = value(arg)?;
+                opts.from = Some(v.parse().map_err(|_| format!("bad snapshot '{}'", v))?);
+            }
+            x if x.starts_with('-') => return Err(format!("unknown option '{}'", x)),
+            x if opts.path.is_none() => opts.path = Some(x.to_owned()),
+            x => return Err(format!("only one program, what's '{}'", x)),
+        }
+    }
+
+    if opts.path.is_none() && cmd != Cmd::Repl {
+        return Err("missing program".to_owned());
+    }
+
+    if cmd != Cmd::Profile && opts.loops.is_some() {
+        return Err("--loops is only for profile".to_owned());
+    }
+    let budgeted = [
+        Cmd::Run,
+        Cmd::Profile,
+        Cmd::Halts,
+        Cmd::Terminates,
+        Cmd::Record,
+        Cmd::Dot,
+    ];
+    if !budgeted.contains(&cmd) && opts.budget.is_some() {
+        return Err(
+            "--budget is only for run, profile, halts, terminates, record and dot".to_owned(),
+        );
+    }
+    if cmd != Cmd::Dot && opts.counts {
+        return Err("--counts is only for dot".to_owned());
+    }
+    if cmd != Cmd::Record && opts.every.is_some() {
+        return Err("--every is only for record".to_owned());
+    }
+    if cmd == Cmd::Replay && opts.log.is_none() {
+        return Err("replay needs a --log".to_owned());
+    }
+    if cmd != Cmd::Replay && (opts.log.is_some() || opts.from.is_some()) {
+        return Err("--log and --from are only for replay".to_owned());
+    }
+    if opts.resume.is_some()
+        && (!opts.set.is_empty() || matches!(cmd, Cmd::Run | Cmd::Compile | Cmd::Replay))
+    {
+        return Err("--resume can't go with --set, run, compile or replay".to_owned());
+    }
+    if cmd == Cmd::Compile && (opts.optimize || !opts.set.is_empty()) {
+        return Err("compile only takes --regs and --overflow".to_owned());
+    }
+
+    Ok(opts)
+}
+
+fn read(path: &str) -> Result<String, String> {
+    fs::read_to_string(path).map_err(|e| format!("can't read {}: {}", path, e))
+}
+
+fn load(path: &str) -> Result<Program, String> {
+    let text = read(path)?;
+    let program = if path.ends_with(".asm") {
+        assemble(&text)
+    } else {
+        text.parse()
+    };
+    program.map_err(|e| format!("can't parse {}\n{}", path, e.report(&text)))
+}
+
+/// `r0=1` or `0=1`
+fn assignment(s: &str) -> Option<(usize, i64)> {
+    let (r, v) = s.split_once('=')?;
+    Some((r.trim_start_matches('r').parse().ok()?, v.parse().ok()?))
+}
+
+fn run_with<const N: usize>(program: Program, opts: &Opts) -> Result<(), String> {
+    let mut reg = [0_i64; N];
+    for &(r, v) in &opts.set {
+        *reg.get_mut(r)
+            .ok_or_else(|| format!("there's no r{}, only r0 to r{}", r, N - 1))? = v;
+    }
+
+    match opts.cmd {
+        Cmd::Replay => {
+            let path = opts.log.as_ref().unwrap();
+            let text = read(path)?;
+            let log: Log<N, i64> = (text.parse()).map_err(|e: common::ParseError| {
+                format!("can't parse {}\n{}", path, e.report(&text))
+            })?;
+
+            let from = opts.from.unwrap_or(0);
+            let n = log.replay(&program, from).map_err(|e| e.to_string())?;
+            println!("all {} snapshots after snapshot {} match", n, from);
+            return Ok(());
+        }
+        Cmd::Compile => {
+            print!("{}", to_rust::<N, i64>(&program, opts.overflow));
+            return Ok(());
+        }
+        Cmd::Run => {
+            let compiled = if opts.optimize {
+                let c = Compiled::optimized(program.clone(), opts.overflow);
+                for s in elfcode::optimize::shortcuts(&program) {
+                    eprintln!("shortcut for {}", s);
+                }
+                c
+            } else {
+                Compiled::new(program, opts.overflow)
+            };
+
+            let run = compiled
+                .run(&mut reg, opts.budget)
+                .map_err(|e| e.to_string())?;
+            println!("{:?}", reg);
+            if run.halted {
+                println!("halted after {} instructions", run.steps);
+            } else {
+                println!(
+                    "out of budget at pc {} after {} instructions",
+                    run.pc, run.steps
+                );
+            }
+            return Ok(());
+        }
+        _ => (),
+    }
+
+    let mut vm = match &opts.resume {
+        Some(path) => {
+            let text = read(path)?;
+            let log: Log<N, i64> = (text.parse()).map_err(|e: common::ParseError| {
+                format!("can't parse {}\n{}", path, e.report(&text))
+            })?;
+            let last = (log.snapshots.last()).ok_or_else(|| format!("no snapshots in {}", path))?;
+            Vm::restore(program, last).map_err(|e| e.to_string())?
+        }
+        None => Vm::new(program, reg).with_overflow(opts.overflow),
+    };
+    if opts.optimize {
+        vm = vm.optimized();
+        for s in vm.shortcuts() {
+            eprintln!("shortcut for {}", s);
+        }
+    }
+
+    match opts.cmd {
+        Cmd::Debug => {
+            let stdin = io::stdin();
+            Debugger::new(vm)
+                .repl(stdin.lock(), &mut io::stdout())
+                .map_err(|e| e.to_string())
+        }
+        Cmd::Repl => {
+            let stdin = io::stdin();
+            Repl::new(vm)
+                .repl(stdin.lock(), &mut io::stdout())
+                .map_err(|e| e.to_string())
+        }
+        Cmd::Profile => {
+            let p = Profile::run(&mut vm, opts.budget);
+            println!("{}", p.report(vm.program(), opts.loops.unwrap_or(LOOPS)));
+            Ok(())
+        }
+        Cmd::Halts => {
+            let h = halting_values(vm, opts.budget).map_err(|e| e.to_string())?;
+            for c in &h.checks {
+                println!("check at pc {} halts when r0 == r{}", c.pc, c.reg);
+            }
+
+            if let Some(f) = h.fastest() {
+                println!(
+                    "fastest: r0 = {} halts after {} instructions",
+                    f.value, f.steps
+                );
+            }
+            if let Some(l) = h.last() {
+                println!(
+                    "last: r0 = {} halts after {} instructions",
+                    l.value, l.steps
+                );
+            }
+
+            let end = match h.end {
+                halt::End::Cycled => "then it goes round again",
+                halt::End::Halted => "then it halted whatever r0 is",
+                halt::End::Budget => "then it ran out of budget",
+            };
+            println!("values found: {}, {}", h.values.len(), end);
+            Ok(())
+        }
+        Cmd::Dot => {
+            let profile = Profile::run(&mut vm, opts.budget);
+            print!("{}", to_dot(vm.program(), Some(&profile)));
+            Ok(())
+        }
+        Cmd::Terminates => {
+            let out = cycle::run(&mut vm, opts.budget).map_err(|e| e.to_string())?;
+            println!("{}", out);
+            Ok(())
+        }
+        Cmd::Record => {
+            let log = Log::record(&mut vm, opts.every.unwrap_or(EVERY), opts.budget);
+            print!("{}", log.map_err(|e| e.to_string())?);
+            Ok(())
+        }
+        Cmd::Run
+        | Cmd::Compile
+        | Cmd::Replay
+        | Cmd::Decompile
+        | Cmd::Asm
+        | Cmd::Disasm
+        | Cmd::Infer => unreachable!(),
+    }
+}
+
+/// How many of the mappings to show when there's more than one
+const MAPPINGS: usize = 10;
+
+fn infer(path: &str) -> Result<(), String> {
+    let text = read(path)?;
+    let manual: Manual = (text.parse())
+        .map_err(|e: common::ParseError| format!("can't parse {}\n{}", path, e.report(&text)))?;
+
+    let solution = solve(&manual.samples, MAPPINGS);
+    println!("{}", solution);
+
+    match &solution {
+        Solution::Unique(_) => (),
+        Solution::Ambiguous { mappings, .. } => {
+            for m in mappings {
+                let m: Vec<_> = m.iter().map(|(n, op)| format!("{}={}", n, op)).collect();
+                println!("  {}", m.join(" "));
+            }
+        }
+        Solution::Unsat(bad) => {
+            for &i in bad {
+                let s = &manual.samples[i];
+                let ops: Vec<_> = s.candidates().iter().map(|o| o.name()).collect();
+                println!(
+                    "  {}: {:?} {} {:?} {:?} could be {}",
+                    i,
+                    s.before,
+                    s.op,
+                    s.args,
+                    s.after,
+                    if ops.is_empty() {
+                        "nothing".to_owned()
+                    } else {
+                        ops.join("/")
+                    }
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn run(opts: &Opts) -> Result<(), String> {
+    if opts.cmd == Cmd::Infer {
+        return infer(opts.path.as_ref().unwrap());
+    }
+
+    let program = match &opts.path {
+        Some(path) => load(path)?,
+        None => Program::default(),
+    };
+
+    // These don't need a VM
+    match opts.cmd {
+        Cmd::Decompile => {
+            print!("{}", decompile(&program));
+            return Ok(());
+        }
+        Cmd::Dot if !opts.counts => {
+            print!("{}", to_dot(&program, None));
+            return Ok(());
+        }
+        Cmd::Asm => {
+            print!("{}", program);
+            return Ok(());
+        }
+        Cmd::Disasm => {
+            print!("{}", disassemble(&program));
+            return Ok(());
+        }
+        Cmd::Run
+        | Cmd::Compile
+        | Cmd::Debug
+        | Cmd::Repl
+        | Cmd::Profile
+        | Cmd::Halts
+        | Cmd::Terminates
+        | Cmd::Record
+        | Cmd::Replay
+        | Cmd::Dot
+        | Cmd::Infer => (),
+    }
+
+    let regs = opts
+        .regs
+        .unwrap_or(if program.ip.is_none() && opts.path.is_some() {
+            4
+        } else {
+            6
+        });
+
+    match regs {
+        4 => run_with::<4>(program, opts),
+        6 => run_with::<6>(program, opts),
+        n => Err(format!("{} registers isn't supported, only 4 or 6", n)),
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let opts = match parse_args(&args) {
+        Ok(o) => o,
+        Err(e) => {
+            eprintln!("elfcode: {}\n\n{}", e, USAGE);
+            process::exit(2);
+        }
+    };
+
+    if let Err(e) = run(&opts) {
+        eprintln!("elfcode: {}", e);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,324 @@
+//! Day 16's input: samples like
+//!
+//! ```text
+//! Before: [3, 2, 1, 1]
+//! 9 2 1 2
+//! After:  [3, 2, 2, 1]
+//! ```
+//!
+//! with blank lines between them, then more blank lines and a program whose opcodes are
+//! still numbers.
+
+use std::fmt;
+use std::str::FromStr;
+
+use common::parse::{ErrorKind, Token, Tokens};
+use common::ParseError;
+
+use crate::infer::{Mapping, Reg, Sample};
+use crate::op::{Args, Inst};
+use crate::program::Program;
+
+/// An instruction before anyone knows which opcode its number is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Numbered {
+    pub op: i32,
+    pub args: Args,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Manual {
+    pub samples: Vec<Sample>,
+    pub program: Vec<Numbered>,
+}
+
+impl Manual {
+    /// The program with real opcodes, or the first number `mapping` doesn't have
+    pub fn decode(&self, mapping: &Mapping) -> Result<Program, i32> {
+        let insts = (self.program.iter())
+            .map(|n| Ok(Inst::new(*mapping.get(&n.op).ok_or(n.op)?, n.args)))
+            .collect::<Result<_, i32>>()?;
+        Ok(Program::new(None, insts))
+    }
+}
+
+fn expected(what: &'static str, col: usize, text: &str) -> ParseError {
+    ParseError::new(ErrorKind::Expected(what), col, text)
+}
+
+fn col(line: &str, at: usize) -> usize {
+    line[..at].chars().count() + 1
+}
+
+/// `Before: [3, 2, 1, 1]`, with `label` being `Before:`
+fn registers(line: &str, label: &'static str) -> Result<Reg, ParseError> {
+    let rest = match line.strip_prefix(label) {
+        Some(r) => r,
+        None => {
+            let first = line.split_whitespace().next().unwrap_or("");
+            return Err(expected(label, 1, first));
+        }
+    };
+    let start = line.len() - rest.len();
+
+    let open = rest.find(|c: char| !c.is_whitespace());
+    let open = match open.filter(|&i| rest[i..].starts_with('[')) {
+        Some(i) => start + i,
+        None => return Err(ParseError::missing("'['", line)),
+    };
+    let close = match line[open..].find(']') {
+        Some(i) => open + i,
+        None => return Err(ParseError::missing("']'", line)),
+    };
+
+    let mut reg = [0; 4];
+    let mut at = open + 1;
+
+    for (i, piece) in line[open + 1..close].split(',').enumerate() {
+        let lead = piece.len() - piece.trim_start().len();
+        let tok = Token {
+            col: col(line, at + lead),
+            text: piece.trim(),
+        };
+
+        if i >= reg.len() {
+            return Err(expected("4 registers", tok.col, tok.text));
+        }
+        if tok.text.is_empty() {
+            return Err(ParseError::new(ErrorKind::Missing("register"), tok.col, ""));
+        }
+        reg[i] = tok.parse()?;
+        at += piece.len() + 1;
+    }
+
+    if line[open + 1..close].split(',').count() < reg.len() {
+        return Err(expected("4 registers", col(line, close), "]"));
+    }
+
+    let tail = &line[close + 1..];
+    if let Some(i) = tail.find(|c: char| !c.is_whitespace()) {
+        let at = close + 1 + i;
+        return Err(expected("end of line", col(line, at), tail[i..].trim_end()));
+    }
+
+    Ok(reg)
+}
+
+/// `9 2 1 2`
+fn numbered(line: &str) -> Result<Numbered, ParseError> {
+    let mut words = Tokens::words(line);
+    let op = words.parse_next("opcode")?;
+    let args = [
+        words.parse_next("a")?,
+        words.parse_next("b")?,
+        words.parse_next("c")?,
+    ];
+
+    match words.next() {
+        Some(t) => Err(ParseError::expected("end of line", t)),
+        None => Ok(Numbered { op, args }),
+    }
+}
+
+impl FromStr for Manual {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut manual = Manual::default();
+        let mut lines = s.lines().enumerate().map(|(n, l)| (n + 1, l)).peekable();
+        let at = |n| move |e: ParseError| e.at_line(n);
+
+        // Samples, each with a blank line or the end after it
+        while let Some(&(n, line)) = lines.peek() {
+            if line.trim().is_empty() {
+                lines.next();
+                continue;
+            }
+            if !line.starts_with("Before:") {
+                break;
+            }
+            lines.next();
+            let before = registers(line, "Before:").map_err(at(n))?;
+
+            // Pointing at the blank line, or just past the end
+            let mut next = |what, prev: usize| match lines.next() {
+                Some((n, l)) if !l.trim().is_empty() => Ok((n, l)),
+                Some((n, _)) => Err(ParseError::new(ErrorKind::Missing(what), 1, "").at_line(n)),
+                None => Err(ParseError::new(ErrorKind::Missing(what), 1, "").at_line(prev + 1)),
+            };
+            let (n, line) = next("instruction", n)?;
+            let inst = numbered(line).map_err(at(n))?;
+            let (n, line) = next("'After:'", n)?;
+            let after = registers(line, "After:").map_err(at(n))?;
+
+            if let Some((n, line)) = lines.next_if(|(_, l)| !l.trim().is_empty()) {
+                let first = line.split_whitespace().next().unwrap();
+                return Err(expected("a blank line after the sample", 1, first).at_line(n));
+            }
+
+            manual.samples.push(Sample {
+                before,
+                op: inst.op,
+                args: inst.args,
+                after,
+            });
+        }
+
+        // Then the program, blank lines are only allowed at the end
+        let mut blanks = None;
+        for (n, line) in lines {
+            if line.trim().is_empty() {
+                blanks.get_or_insert(n);
+                continue;
+            }
+            if let Some(b) = blanks.filter(|_| !manual.program.is_empty()) {
+                return Err(ParseError::new(
+                    ErrorKind::Expected("no blank lines in the program"),
+                    1,
+                    "",
+                )
+                .at_line(b));
+            }
+            if line.starts_with("Before:") {
+                return Err(expected("instruction, samples go first", 1, "Before:").at_line(n));
+            }
+            manual.program.push(numbered(line).map_err(at(n))?);
+        }
+
+        Ok(manual)
+    }
+}
+
+fn write_regs(f: &mut fmt::Formatter, label: &str, r: &Reg) -> fmt::Result {
+    writeln!(f, "{:<8}[{}, {}, {}, {}]", label, r[0], r[1], r[2], r[3])
+}
+
+impl fmt::Display for Numbered {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let [a, b, c] = self.args;
+        write!(f, "{} {} {} {}", self.op, a, b, c)
+    }
+}
+
+/// Laid out the same as the puzzle input
+impl fmt::Display for Manual {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for s in &self.samples {
+            write_regs(f, "Before:", &s.before)?;
+            let inst = Numbered {
+                op: s.op,
+                args: s.args,
+            };
+            writeln!(f, "{}", inst)?;
+            write_regs(f, "After:", &s.after)?;
+            writeln!(f)?;
+        }
+
+        if !self.program.is_empty() {
+            if !self.samples.is_empty() {
+                write!(f, "\n\n")?;
+            }
+            for n in &self.program {
+                writeln!(f, "{}", n)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::fuzz::fuzz;
+
+    const EXAMPLE: &str = "\
+Before: [3, 2, 1, 1]
+9 2 1 2
+After:  [3, 2, 2, 1]
+
+Before: [0, -1, 0, 0]
+4 1 0 3
+After:  [0, -1, 0, -1]
+
+
+
+9 0 0 1
+4 1 1 0
+";
+
+    #[test]
+    fn round_trip() {
+        let m: Manual = EXAMPLE.parse().unwrap();
+        assert_eq!(m.samples.len(), 2);
+        assert_eq!(m.samples[1].before, [0, -1, 0, 0]);
+        assert_eq!(
+            m.program[1],
+            Numbered {
+                op: 4,
+                args: [1, 1, 0]
+            }
+        );
+        assert_eq!(m.to_string(), EXAMPLE);
+
+        let mapping = [(9, crate::op::Opcode::Seti), (4, crate::op::Opcode::Addr)];
+        let p = m.decode(&mapping.iter().copied().collect()).unwrap();
+        assert_eq!(p.to_string(), "seti 0 0 1\naddr 1 1 0\n");
+        assert_eq!(m.decode(&mapping[..1].iter().copied().collect()), Err(4));
+    }
+
+    #[test]
+    fn errors() {
+        let err = |s: &str| s.parse::<Manual>().unwrap_err().to_string();
+
+        assert_eq!(
+            err("Before: [3, 2, 1, 1]\n9 2 1 2\n\nAfter:  [3, 2, 2, 1]"),
+            "line 3, column 1: missing 'After:'"
+        );
+        assert_eq!(
+            err("Before: [3, 2, 1, 1]\nAfter:  [3, 2, 2, 1]\n9 2 1 2"),
+            "line 2, column 1: invalid digit found in string at 'After:'"
+        );
+        assert_eq!(
+            err("Before: [3, 2, 1]\n9 2 1 2\nAfter:  [3, 2, 2, 1]"),
+            "line 1, column 17: expected 4 registers at ']'"
+        );
+        assert_eq!(
+            err("Before: [3, 2, 1, 1, 5]\n9 2 1 2\nAfter:  [3, 2, 2, 1]"),
+            "line 1, column 22: expected 4 registers at '5'"
+        );
+        assert_eq!(
+            err("Before: [3, x, 1, 1]\n9 2 1 2\nAfter:  [3, 2, 2, 1]"),
+            "line 1, column 13: invalid digit found in string at 'x'"
+        );
+        assert_eq!(
+            err("Before: [3, 2, 1, 1]\n9 2 1 2\nAfter:  [3, 2, 2, 1]\n9 2 1 2"),
+            "line 4, column 1: expected a blank line after the sample at '9'"
+        );
+        assert_eq!(
+            err("Before: [3, 2, 1, 1]\n9 2 1 2\nAfter:  [3, 2, 2, 1] 4"),
+            "line 3, column 22: expected end of line at '4'"
+        );
+        assert_eq!(
+            err("9 2 1 2 7"),
+            "line 1, column 9: expected end of line at '7'"
+        );
+        assert_eq!(
+            err("9 2 1 2\n\n9 2 1 2"),
+            "line 2, column 1: expected no blank lines in the program"
+        );
+        assert_eq!(
+            err("9 2 1 2\nBefore: [3, 2, 1, 1]"),
+            "line 2, column 1: expected instruction, samples go first at 'Before:'"
+        );
+    }
+
+    #[test]
+    fn fuzz_parse() {
+        fuzz(&[EXAMPLE], 2000, |s| {
+            if let Ok(m) = s.parse::<Manual>() {
+                assert_eq!(m.to_string().parse::<Manual>().unwrap(), m);
+            }
+        });
+    }
+}
@@ -0,0 +1,256 @@
+//! The sixteen instructions
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+use common::parse::Tokens;
+use common::ParseError;
+
+use crate::vm::FaultKind;
+use crate::word::{Overflow, Word};
+
+pub type Args = [i32; 3];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Opcode {
+    Addr,
+    Addi,
+    Mulr,
+    Muli,
+    Banr,
+    Bani,
+    Borr,
+    Bori,
+    Setr,
+    Seti,
+    Gtir,
+    Gtri,
+    Gtrr,
+    Eqir,
+    Eqri,
+    Eqrr,
+}
+
+/// How an operand gets read
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Src {
+    Reg,
+    Imm,
+    Ignored,
+}
+
+impl Opcode {
+    pub const ALL: [Opcode; 16] = [
+        Opcode::Addr,
+        Opcode::Addi,
+        Opcode::Mulr,
+        Opcode::Muli,
+        Opcode::Banr,
+        Opcode::Bani,
+        Opcode::Borr,
+        Opcode::Bori,
+        Opcode::Setr,
+        Opcode::Seti,
+        Opcode::Gtir,
+        Opcode::Gtri,
+        Opcode::Gtrr,
+        Opcode::Eqir,
+        Opcode::Eqri,
+        Opcode::Eqrr,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Opcode::Addr => "addr",
+            Opcode::Addi => "addi",
+            Opcode::Mulr => "mulr",
+            Opcode::Muli => "muli",
+            Opcode::Banr => "banr",
+            Opcode::Bani => "bani",
+            Opcode::Borr => "borr",
+            Opcode::Bori => "bori",
+            Opcode::Setr => "setr",
+            Opcode::Seti => "seti",
+            Opcode::Gtir => "gtir",
+            Opcode::Gtri => "gtri",
+            Opcode::Gtrr => "gtrr",
+            Opcode::Eqir => "eqir",
+            Opcode::Eqri => "eqri",
+            Opcode::Eqrr => "eqrr",
+        }
+    }
+
+    pub fn from_name(s: &str) -> Option<Opcode> {
+        Opcode::ALL.iter().copied().find(|o| o.name() == s)
+    }
+
+    /// Where a and b come from, c is always a register
+    pub fn operands(self) -> (Src, Src) {
+        use Opcode::*;
+
+        match self {
+            Addr | Mulr | Banr | Borr | Gtrr | Eqrr => (Src::Reg, Src::Reg),
+            Addi | Muli | Bani | Bori | Gtri | Eqri => (Src::Reg, Src::Imm),
+            Gtir | Eqir => (Src::Imm, Src::Reg),
+            Setr => (Src::Reg, Src::Ignored),
+            Seti => (Src::Imm, Src::Ignored),
+        }
+    }
+
+    /// Run on the registers in place
+    pub fn exec<W: Word, const N: usize>(
+        self,
+        reg: &mut [W; N],
+        args: Args,
+        mode: Overflow,
+    ) -> Result<(), FaultKind> {
+        use Opcode::*;
+
+        let (sa, sb) = self.operands();
+        let read = |src, v: i32| match src {
+            Src::Reg => register(reg, v).copied(),
+            Src::Imm => W::from_imm(v, mode).ok_or(FaultKind::Overflow),
+            Src::Ignored => Ok(W::ZERO),
+        };
+        let (a, b) = (read(sa, args[0])?, read(sb, args[1])?);
+        let flag = |f| if f { W::ONE } else { W::ZERO };
+
+        let val = match self {
+            Addr | Addi => a.add(b, mode).ok_or(FaultKind::Overflow)?,
+            Mulr | Muli => a.mul(b, mode).ok_or(FaultKind::Overflow)?,
+            Banr | Bani => a.and(b),
+            Borr | Bori => a.or(b),
+            Setr | Seti => a,
+            Gtir | Gtri | Gtrr => flag(a > b),
+            Eqir | Eqri | Eqrr => flag(a == b),
+        };
+
+        *register_mut(reg, args[2])? = val;
+        Ok(())
+    }
+
+    /// Same as `exec` but on a copy and wrapping, None if it names a register that
+    /// isn't there
+    pub fn apply<W: Word, const N: usize>(self, reg: &[W; N], args: Args) -> Option<[W; N]> {
+        let mut out = *reg;
+        self.exec(&mut out, args, Overflow::Wrapping).ok()?;
+        Some(out)
+    }
+}
+
+fn register<W, const N: usize>(reg: &[W; N], r: i32) -> Result<&W, FaultKind> {
+    usize::try_from(r)
+        .ok()
+        .and_then(|r| reg.get(r))
+        .ok_or(FaultKind::BadRegister)
+}
+
+fn register_mut<W, const N: usize>(reg: &mut [W; N], r: i32) -> Result<&mut W, FaultKind> {
+    usize::try_from(r)
+        .ok()
+        .and_then(move |r| reg.get_mut(r))
+        .ok_or(FaultKind::BadRegister)
+}
+
+impl fmt::Display for Opcode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Inst {
+    pub op: Opcode,
+    pub args: Args,
+}
+
+impl Inst {
+    pub fn new(op: Opcode, args: Args) -> Inst {
+        Inst { op, args }
+    }
+}
+
+impl FromStr for Inst {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // addi 0 1 0
+        let mut words = Tokens::words(s);
+        let tok = words.next_tok("opcode")?;
+        let op = Opcode::from_name(tok.text).ok_or_else(|| ParseError::expected("opcode", tok))?;
+
+        let args = [
+            words.parse_next("a")?,
+            words.parse_next("b")?,
+            words.parse_next("c")?,
+        ];
+
+        match words.next() {
+            Some(t) => Err(ParseError::expected("end of line", t)),
+            None => Ok(Inst { op, args }),
+        }
+    }
+}
+
+impl fmt::Display for Inst {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let [a, b, c] = self.args;
+        write!(f, "{} {} {} {}", self.op, a, b, c)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ops() {
+        let reg = [3, 2, 1, 1];
+        let run = |op: Opcode, args| op.apply(&reg, args).unwrap()[0];
+
+        // The example from day 16 matches these three
+        let matches: Vec<Opcode> = Opcode::ALL
+            .iter()
+            .copied()
+            .filter(|o| o.apply(&reg, [2, 1, 2]) == Some([3, 2, 2, 1]))
+            .collect();
+        assert_eq!(matches, vec![Opcode::Addi, Opcode::Mulr, Opcode::Seti]);
+
+        assert_eq!(run(Opcode::Addr, [0, 1, 0]), 5);
+        assert_eq!(run(Opcode::Muli, [0, 7, 0]), 21);
+        assert_eq!(run(Opcode::Banr, [0, 1, 0]), 2);
+        assert_eq!(run(Opcode::Bori, [1, 4, 0]), 6);
+        assert_eq!(run(Opcode::Setr, [1, 99, 0]), 2);
+        assert_eq!(run(Opcode::Gtir, [3, 0, 0]), 0);
+        assert_eq!(run(Opcode::Gtri, [0, 2, 0]), 1);
+        assert_eq!(run(Opcode::Eqrr, [2, 3, 0]), 1);
+        assert_eq!(run(Opcode::Eqir, [1, 2, 0]), 1);
+    }
+
+    #[test]
+    fn bad_registers() {
+        let reg = [0; 4];
+        assert_eq!(Opcode::Addr.apply(&reg, [4, 0, 0]), None);
+        assert_eq!(
+            Opcode::Addr.exec(&mut [0; 4], [-1, 0, 0], Overflow::Checked),
+            Err(FaultKind::BadRegister)
+        );
+        assert_eq!(Opcode::Addi.apply(&reg, [0, 4, 0]), Some([4, 0, 0, 0]));
+        assert_eq!(Opcode::Seti.apply(&reg, [0, 0, -1]), None);
+        assert_eq!(Opcode::Setr.apply(&reg, [0, -7, 1]), Some(reg));
+    }
+
+    #[test]
+    fn parse() {
+        let i: Inst = "gtrr 1 2 3".parse().unwrap();
+        assert_eq!(i, Inst::new(Opcode::Gtrr, [1, 2, 3]));
+        assert_eq!(i.to_string(), "gtrr 1 2 3");
+
+        let e = "addx 1 2 3".parse::<Inst>().unwrap_err();
+        assert_eq!(e.to_string(), "column 1: expected opcode at 'addx'");
+
+        let e = "addi 1 2 3 4".parse::<Inst>().unwrap_err();
+        assert_eq!(e.to_string(), "column 12: expected end of line at '4'");
+    }
+}
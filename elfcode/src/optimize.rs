@@ -0,0 +1,513 @@
+//! Loops that are really one operation, found by their shape and run natively. When
+//! something a loop works out on the way wouldn't fit in a register its shortcut
+//! stands aside, and the loop runs and overflows however the mode says.
+
+use std::convert::TryFrom;
+use std::fmt;
+
+use crate::cfg::reg_name;
+use crate::op::Opcode;
+use crate::program::Program;
+use crate::vm::FaultKind;
+use crate::word::{Overflow, Word};
+
+/// What a loop works out, the fields are registers
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Idiom {
+    /// Day 19, `acc` goes up by the sum of the divisors of `n`
+    DivisorSum {
+        acc: usize,
+        n: usize,
+        d: usize,
+        f: usize,
+        tmp: usize,
+    },
+    /// Day 21, `q` counts up until `(q + 1) * by` is past `n`
+    Divide {
+        q: usize,
+        n: usize,
+        by: i32,
+        tmp: usize,
+    },
+}
+
+/// Run `idiom` when the pc gets to `start` and carry on from `exit`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Shortcut {
+    pub start: usize,
+    pub exit: usize,
+    pub idiom: Idiom,
+}
+
+/// How often each instruction of a loop runs and the jumps it takes, by offset from
+/// its start with the exit one past the end
+#[derive(Debug, Clone, PartialEq)]
+pub struct Counts {
+    pub hits: Vec<u64>,
+    /// From, to and how many times, never to the next one along
+    pub jumps: Vec<(usize, usize, u64)>,
+}
+
+/// Registers and what goes in them
+type Sets = Vec<(usize, i128)>;
+
+fn count(n: i128) -> u64 {
+    u64::try_from(n).unwrap_or(u64::MAX)
+}
+
+impl Idiom {
+    /// The registers the loop sets with what to, and how many instructions that takes.
+    /// None if it would make a value along the way that doesn't fit in a `W`.
+    fn outcome<W: Word, const N: usize>(
+        &self,
+        reg: &[W; N],
+    ) -> Result<Option<(Sets, i128)>, FaultKind> {
+        let get = |r: usize| {
+            reg.get(r)
+                .map(|v| v.to_i128())
+                .ok_or(FaultKind::BadRegister)
+        };
+
+        let (sets, steps, biggest) = match *self {
+            Idiom::DivisorSum { acc, n, d, f, tmp } => {
+                let (a, n) = (get(acc)?, get(n)?);
+
+                // Both counters go one past n, or past 1 when n is smaller than that. Each
+                // d is 8 per f plus 3, and all but the last take the jump back.
+                let m = n.max(1);
+                let a = a + divisors(n).1;
+                let sets = vec![(acc, a), (d, m + 1), (f, m + 1), (tmp, 1)];
+                // The divisors only ever add to acc, and d * f goes up to m * m
+                (sets, 8 * m * m + 4 * m, vec![a, m + 1, m * m])
+            }
+            Idiom::Divide { q, n, by, tmp } => {
+                // 7 a time round, then 6 for the setup and the way out
+                let quot = get(n)?.max(0) / i128::from(by);
+                let last = (quot + 1) * i128::from(by);
+                (vec![(q, quot), (tmp, 1)], 7 * quot + 6, vec![last])
+            }
+        };
+
+        let fits = |v| W::from_i128(v, Overflow::Checked).is_some();
+        if biggest.into_iter().all(fits) {
+            Ok(Some((sets, steps)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Leaves the registers how the loop would have, and says how many instructions
+    /// that took it. None, with the registers left alone, if the loop has to run to
+    /// overflow the way it would.
+    pub fn apply<W: Word, const N: usize>(
+        &self,
+        reg: &mut [W; N],
+    ) -> Result<Option<u64>, FaultKind> {
+        let (sets, steps) = match self.outcome(reg)? {
+            Some(o) => o,
+            None => return Ok(None),
+        };
+
+        for (r, v) in sets {
+            let v = W::from_i128(v, Overflow::Checked).ok_or(FaultKind::Overflow)?;
+            *reg.get_mut(r).ok_or(FaultKind::BadRegister)? = v;
+        }
+        Ok(Some(count(steps)))
+    }
+
+    /// What `apply` stands for instruction by instruction, for the registers before it.
+    /// None if they aren't all there or it wouldn't apply.
+    pub fn counts<W: Word, const N: usize>(&self, reg: &[W; N]) -> Option<Counts> {
+        self.outcome(reg).ok()??;
+        let get = |r: usize| reg.get(r).map(|v| v.to_i128());
+
+        let (hits, jumps) = match *self {
+            Idiom::DivisorSum { n, .. } => {
+                // f goes round m times for each of m values of d, and the add is skipped
+                // over for all but the k that multiply to n
+                let n = get(n)?;
+                let m = n.max(1);
+                let (k, _) = divisors(n);
+                let mm = m * m;
+                (
+                    vec![
+                        1,
+                        m,
+                        mm,
+                        mm,
+                        mm,
+                        mm - k,
+                        k,
+                        mm,
+                        mm,
+                        mm,
+                        mm - m,
+                        m,
+                        m,
+                        m,
+                        m - 1,
+                    ],
+                    vec![
+                        (4, 6, k),
+                        (5, 7, mm - k),
+                        (9, 11, m),
+                        (10, 2, mm - m),
+                        (13, 15, 1),
+                        (14, 1, m - 1),
+                    ],
+                )
+            }
+            Idiom::Divide { n, by, .. } => {
+                let q = get(n)?.max(0) / i128::from(by);
+                (
+                    vec![1, q + 1, q + 1, q + 1, q + 1, q, 1, q, q],
+                    vec![(4, 6, 1), (5, 7, q), (6, 9, 1), (8, 1, q)],
+                )
+            }
+        };
+
+        Some(Counts {
+            hits: hits.into_iter().map(count).collect(),
+            jumps: (jumps.into_iter())
+                .filter(|&(_, _, n)| n > 0)
+                .map(|(from, to, n)| (from, to, count(n)))
+                .collect(),
+        })
+    }
+}
+
+/// How many divisors `n` has and what they add up to, none for anything below 1
+fn divisors(n: i128) -> (i128, i128) {
+    let (mut count, mut sum) = (0, 0);
+    let mut d = 1;
+
+    while d * d <= n {
+        if n % d == 0 {
+            count += 1;
+            sum += d;
+            if d * d != n {
+                count += 1;
+                sum += n / d;
+            }
+        }
+        d += 1;
+    }
+
+    (count, sum)
+}
+
+impl fmt::Display for Shortcut {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "pc {} to {}: ", self.start, self.exit - 1)?;
+
+        match self.idiom {
+            Idiom::DivisorSum { acc, n, .. } => write!(
+                f,
+                "{} += sum of the divisors of {}",
+                reg_name(acc),
+                reg_name(n)
+            ),
+            Idiom::Divide { q, n, by, .. } => {
+                write!(f, "{} = {} / {}", reg_name(q), reg_name(n), by)
+            }
+        }
+    }
+}
+
+/// How an instruction's operand has to look
+#[derive(Debug, Clone, Copy)]
+enum Arg {
+    /// The same register everywhere it's used, and not the same as any other
+    R(usize),
+    Ip,
+    Imm(i32),
+    /// An immediate, the same everywhere it's used
+    Param(usize),
+    /// Jumping here makes it go to this instruction of the pattern
+    To(usize),
+    Any,
+}
+
+use Arg::*;
+
+type Pattern = [(Opcode, [Arg; 3])];
+
+/// a, n, d, f, tmp
+const DIVISOR_SUM: &Pattern = &[
+    (Opcode::Seti, [Imm(1), Any, R(2)]),
+    (Opcode::Seti, [Imm(1), Any, R(3)]),
+    (Opcode::Mulr, [R(2), R(3), R(4)]),
+    (Opcode::Eqrr, [R(4), R(1), R(4)]),
+    (Opcode::Addr, [R(4), Ip, Ip]),
+    (Opcode::Addi, [Ip, Imm(1), Ip]),
+    (Opcode::Addr, [R(2), R(0), R(0)]),
+    (Opcode::Addi, [R(3), Imm(1), R(3)]),
+    (Opcode::Gtrr, [R(3), R(1), R(4)]),
+    (Opcode::Addr, [Ip, R(4), Ip]),
+    (Opcode::Seti, [To(2), Any, Ip]),
+    (Opcode::Addi, [R(2), Imm(1), R(2)]),
+    (Opcode::Gtrr, [R(2), R(1), R(4)]),
+    (Opcode::Addr, [R(4), Ip, Ip]),
+    (Opcode::Seti, [To(1), Any, Ip]),
+];
+
+/// q, n, tmp, and the divisor
+const DIVIDE: &Pattern = &[
+    (Opcode::Seti, [Imm(0), Any, R(0)]),
+    (Opcode::Addi, [R(0), Imm(1), R(2)]),
+    (Opcode::Muli, [R(2), Param(0), R(2)]),
+    (Opcode::Gtrr, [R(2), R(1), R(2)]),
+    (Opcode::Addr, [R(2), Ip, Ip]),
+    (Opcode::Addi, [Ip, Imm(1), Ip]),
+    (Opcode::Seti, [To(9), Any, Ip]),
+    (Opcode::Addi, [R(0), Imm(1), R(0)]),
+    (Opcode::Seti, [To(1), Any, Ip]),
+];
+
+/// What a match found, the registers then the immediates
+#[derive(Debug, Clone, Copy, Default)]
+struct Binding {
+    regs: [Option<usize>; 5],
+    params: [Option<i32>; 1],
+}
+
+impl Binding {
+    fn reg(&mut self, k: usize, v: i32) -> bool {
+        let v = match usize::try_from(v) {
+            Ok(v) => v,
+            Err(_) => return false,
+        };
+
+        match self.regs[k] {
+            Some(r) => r == v,
+            None if self.regs.contains(&Some(v)) => false,
+            None => {
+                self.regs[k] = Some(v);
+                true
+            }
+        }
+    }
+
+    fn param(&mut self, k: usize, v: i32) -> bool {
+        *self.params[k].get_or_insert(v) == v
+    }
+}
+
+/// Whether `inst`'s `a` and `b` can be swapped without changing it
+fn commutes(op: Opcode) -> bool {
+    use Opcode::*;
+    matches!(op, Addr | Mulr | Banr | Borr | Eqrr)
+}
+
+/// Match `pattern` against the program at `start`
+fn matches(program: &Program, start: usize, pattern: &Pattern) -> Option<Binding> {
+    let ip = i32::try_from(program.ip?).ok()?;
+    let insts = program.insts.get(start..start + pattern.len())?;
+    let mut b = Binding::default();
+
+    let arg = |b: &mut Binding, want: Arg, v: i32| match want {
+        R(k) => v != ip && b.reg(k, v),
+        Ip => v == ip,
+        Imm(x) => v == x,
+        Param(k) => b.param(k, v),
+        To(k) => i64::from(v) == (start + k) as i64 - 1,
+        Any => true,
+    };
+
+    for (inst, &(op, want)) in insts.iter().zip(pattern) {
+        if inst.op != op {
+            return None;
+        }
+
+        let [a, bb, c] = inst.args;
+        let mut orders = vec![(a, bb)];
+        if commutes(op) {
+            orders.push((bb, a));
+        }
+
+        b = orders.into_iter().find_map(|(a, bb)| {
+            let mut try_b = b;
+            let ok = arg(&mut try_b, want[0], a)
+                && arg(&mut try_b, want[1], bb)
+                && arg(&mut try_b, want[2], c);
+            Some(try_b).filter(|_| ok)
+        })?;
+    }
+
+    Some(b)
+}
+
+/// Every loop in `program` there's a shortcut for
+pub fn shortcuts(program: &Program) -> Vec<Shortcut> {
+    let mut found = vec![];
+
+    for start in 0..program.len() {
+        let r = |b: &Binding, k: usize| b.regs[k].unwrap();
+
+        if let Some(b) = matches(program, start, DIVISOR_SUM) {
+            found.push(Shortcut {
+                start,
+                exit: start + DIVISOR_SUM.len(),
+                idiom: Idiom::DivisorSum {
+                    acc: r(&b, 0),
+                    n: r(&b, 1),
+                    d: r(&b, 2),
+                    f: r(&b, 3),
+                    tmp: r(&b, 4),
+                },
+            });
+        }
+
+        if let Some(b) = matches(program, start, DIVIDE) {
+            // A divisor that's not positive never gets out
+            let by = b.params[0].unwrap();
+            if by > 0 {
+                found.push(Shortcut {
+                    start,
+                    exit: start + DIVIDE.len(),
+                    idiom: Idiom::Divide {
+                        q: r(&b, 0),
+                        n: r(&b, 1),
+                        by,
+                        tmp: r(&b, 2),
+                    },
+                });
+            }
+        }
+    }
+
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::Vm;
+    use common::fuzz::check;
+
+    /// Day 19's loops, shifted up one with the init swapped for setting n
+    const DIVISORS: &str = "#ip 2
+seti 1 1 3
+seti 1 7 5
+mulr 3 5 4
+eqrr 4 1 4
+addr 4 2 2
+addi 2 1 2
+addr 3 0 0
+addi 5 1 5
+gtrr 5 1 4
+addr 2 4 2
+seti 1 3 2
+addi 3 1 3
+gtrr 3 1 4
+addr 4 2 2
+seti 0 9 2";
+
+    /// Day 21's, with registers swapped around and the a and b of the add turned round
+    const DIVIDE_LOOP: &str = "#ip 4
+seti 0 1 0
+addi 0 1 1
+muli 1 7 1
+gtrr 1 3 1
+addr 4 1 4
+addi 4 1 4
+seti 8 1 4
+addi 0 1 0
+seti 0 8 4";
+
+    /// Registers and steps without and then with the shortcuts
+    fn both(program: &str, reg: [i64; 6]) -> (([i64; 6], u64), ([i64; 6], u64)) {
+        let p: Program = program.parse().unwrap();
+
+        let mut slow = Vm::new(p.clone(), reg);
+        slow.run().unwrap();
+        let mut fast = Vm::new(p, reg).optimized();
+        assert!(fast.step().unwrap());
+        assert!(fast.halted());
+
+        ((slow.reg, slow.steps()), (fast.reg, fast.steps()))
+    }
+
+    #[test]
+    fn found() {
+        let s = shortcuts(&DIVISORS.parse().unwrap());
+        assert_eq!(s.len(), 1);
+        assert_eq!(
+            s[0].to_string(),
+            "pc 0 to 14: a += sum of the divisors of b"
+        );
+
+        let s = shortcuts(&DIVIDE_LOOP.parse().unwrap());
+        assert_eq!(s.len(), 1);
+        assert_eq!(s[0].to_string(), "pc 0 to 8: a = d / 7");
+
+        // One register where there should be two
+        let p = DIVIDE_LOOP.replace("addi 0 1 1", "addi 0 1 0");
+        assert!(shortcuts(&p.parse().unwrap()).is_empty());
+    }
+
+    #[test]
+    fn same_as_running() {
+        // One step that counts as all of them
+        let (slow, fast) = both(DIVISORS, [3, 12, 0, 0, 0, 0]);
+        assert_eq!(slow, fast);
+        assert_eq!(fast.0[0], 3 + 28);
+
+        check(
+            200,
+            |r| (r.range(-3, 40), r.range(-20, 200)),
+            |&(n, e)| {
+                let (slow, fast) = both(DIVISORS, [1, n, 0, 0, 0, 0]);
+                assert_eq!(slow, fast);
+
+                let (slow, fast) = both(DIVIDE_LOOP, [0, 0, 0, e, 0, 0]);
+                assert_eq!(slow, fast);
+            },
+        );
+    }
+
+    #[test]
+    fn overflowing() {
+        // Up to 1000 steps, and how that went
+        let run = |vm: &mut Vm<6, i32>| {
+            let mut end = Ok(true);
+            while vm.steps() < 1000 && end == Ok(true) {
+                end = vm.step();
+            }
+            (end, vm.reg, vm.steps(), vm.pc())
+        };
+        let same = |program: &str, reg: [i32; 6], mode: Overflow| {
+            let p: Program = program.parse().unwrap();
+            let mut slow = Vm::new(p.clone(), reg).with_overflow(mode);
+            let mut fast = Vm::new(p, reg).with_overflow(mode).optimized();
+            assert_eq!(fast.shortcuts().count(), 1);
+            let out = run(&mut slow);
+            assert_eq!(out, run(&mut fast));
+            out
+        };
+
+        // The sum of the divisors of 6 doesn't fit on top
+        let start = [i32::MAX - 5, 6, 0, 0, 0, 0];
+        let (end, reg, ..) = same(DIVISORS, start, Overflow::Wrapping);
+        assert_eq!((end, reg[0]), (Ok(false), (i32::MAX - 5).wrapping_add(12)));
+        let (end, reg, ..) = same(DIVISORS, start, Overflow::Saturating);
+        assert_eq!((end, reg[0]), (Ok(false), i32::MAX));
+        let (end, ..) = same(DIVISORS, start, Overflow::Checked);
+        assert_eq!(end.unwrap_err().kind, FaultKind::Overflow);
+
+        // Twice 2^30 wraps round to below n, so that never gets out
+        let big = DIVIDE_LOOP.replace("muli 1 7 1", "muli 1 1073741824 1");
+        let start = [0, 0, 0, i32::MAX - 1, 0, 0];
+        let (end, _, steps, _) = same(&big, start, Overflow::Wrapping);
+        assert_eq!((end, steps), (Ok(true), 1000));
+        let (end, reg, ..) = same(&big, start, Overflow::Saturating);
+        assert_eq!((end, reg[0]), (Ok(false), 1));
+        let (end, ..) = same(&big, start, Overflow::Checked);
+        assert_eq!(end.unwrap_err().pc, 2);
+
+        // Just fitting, where the shortcut gets to run
+        let start = [0, 0, 0, (1 << 30) - 1, 0, 0];
+        let (end, reg, steps, _) = same(&big, start, Overflow::Checked);
+        assert_eq!((end, reg[0], steps), (Ok(false), 0, 6));
+    }
+}
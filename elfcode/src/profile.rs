@@ -0,0 +1,274 @@
+//! Where a program spends its time, counted per instruction
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use crate::program::Program;
+use crate::vm::{Fault, Vm};
+use crate::word::Word;
+
+/// How the profiled run finished
+#[derive(Debug, Clone, PartialEq)]
+pub enum End {
+    Halted,
+    /// Ran out of steps first, the pc is where it got to
+    Budget(usize),
+    Fault(Fault),
+}
+
+/// A jump to an earlier (or the same) pc, so the bottom of a loop
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BackEdge {
+    pub from: usize,
+    pub to: usize,
+    pub taken: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Profile {
+    /// Times each pc ran
+    pub hits: Vec<u64>,
+    /// Backwards jumps and how often they were taken
+    pub back_edges: HashMap<(usize, usize), u64>,
+    /// Every time the pc didn't just go to the next one, off the end included
+    pub jumps: HashMap<(usize, usize), u64>,
+    pub steps: u64,
+    pub end: End,
+}
+
+impl Profile {
+    /// Run `vm` until it halts, faults or `budget` more instructions have gone by. A
+    /// shortcut counts as every instruction of the loop it stands for.
+    pub fn run<const N: usize, W: Word>(vm: &mut Vm<N, W>, budget: Option<u64>) -> Profile {
+        let mut hits = vec![0; vm.program().len()];
+        let mut back_edges = HashMap::new();
+        let mut jumps = HashMap::new();
+        let mut steps = 0;
+
+        let mut jump = |from: usize, to: usize, n: u64| {
+            if to != from + 1 {
+                *jumps.entry((from, to)).or_insert(0) += n;
+            }
+            if to <= from {
+                *back_edges.entry((from, to)).or_insert(0) += n;
+            }
+        };
+
+        let end = loop {
+            let pc = vm.pc();
+            if vm.halted() {
+                break End::Halted;
+            }
+            if budget.is_some_and(|b| steps >= b) {
+                break End::Budget(pc);
+            }
+
+            let before = vm.steps();
+            let counts = (vm.shortcut_at(pc)).and_then(|s| s.idiom.counts(&vm.reg));
+            if let Err(f) = vm.step() {
+                break End::Fault(f);
+            }
+            steps += vm.steps() - before;
+
+            match counts {
+                Some(c) => {
+                    for (i, n) in c.hits.into_iter().enumerate() {
+                        hits[pc + i] += n;
+                    }
+                    for (from, to, n) in c.jumps {
+                        jump(pc + from, pc + to, n);
+                    }
+                }
+                None => {
+                    hits[pc] += 1;
+                    jump(pc, vm.pc(), 1);
+                }
+            }
+        };
+
+        Profile {
+            hits,
+            back_edges,
+            jumps,
+            steps,
+            end,
+        }
+    }
+
+    /// Back-edges most taken first, ties by where they jump from
+    pub fn hot_loops(&self) -> Vec<BackEdge> {
+        let mut loops: Vec<BackEdge> = (self.back_edges.iter())
+            .map(|(&(from, to), &taken)| BackEdge { from, to, taken })
+            .collect();
+        loops.sort_by_key(|e| (std::cmp::Reverse(e.taken), e.from, e.to));
+        loops
+    }
+
+    fn percent(&self, n: u64) -> f64 {
+        if self.steps == 0 {
+            0.0
+        } else {
+            n as f64 * 100.0 / self.steps as f64
+        }
+    }
+
+    /// The listing with counts down the side, then the `loops` hottest loops and how
+    /// it ended
+    pub fn report(&self, program: &Program, loops: usize) -> String {
+        let mut out = String::new();
+        let width = self.steps.to_string().len().max(4);
+
+        if let Some(ip) = program.ip {
+            // Lined up with the instructions
+            writeln!(out, "{:w$}#ip {}", "", ip, w = width + 13).unwrap();
+        }
+
+        for (pc, (i, &n)) in program.insts.iter().zip(&self.hits).enumerate() {
+            let pct = if n == 0 {
+                String::new()
+            } else {
+                format!("{:.1}%", self.percent(n))
+            };
+            writeln!(out, "{:>w$} {:>6} {:>3}: {}", n, pct, pc, i, w = width).unwrap();
+        }
+
+        let hot = self.hot_loops();
+        if !hot.is_empty() {
+            writeln!(out, "\nhot loops:").unwrap();
+        }
+        for e in hot.iter().take(loops) {
+            writeln!(
+                out,
+                "  {:>3} -> {:<3} taken {} times, {} to {} is {:.1}% of the steps",
+                e.from,
+                e.to,
+                e.taken,
+                e.to,
+                e.from,
+                self.percent(self.hits[e.to..=e.from].iter().sum()),
+            )
+            .unwrap();
+        }
+
+        let end = match &self.end {
+            End::Halted => "halted".to_owned(),
+            End::Budget(pc) => format!("ran out of budget at pc {}", pc),
+            End::Fault(f) => format!("stopped by a fault at {}", f),
+        };
+        write!(out, "\n{} after {} steps", end, self.steps).unwrap();
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Counts r1 up to 3 in a loop, then doubles it
+    const LOOP: &str = "#ip 0
+addi 1 1 1
+gtri 1 2 2
+addr 0 2 0
+seti -1 0 0
+mulr 1 1 1";
+
+    #[test]
+    fn counts() {
+        let program: Program = LOOP.parse().unwrap();
+        let mut vm = Vm::new(program.clone(), [0; 3]);
+        let p = Profile::run(&mut vm, None);
+
+        assert_eq!(p.end, End::Halted);
+        assert_eq!(p.hits, vec![3, 3, 3, 2, 1]);
+        assert_eq!(p.steps, 12);
+        assert_eq!(vm.reg[1], 9);
+        assert_eq!(p.jumps.len(), 2);
+        assert_eq!((p.jumps[&(2, 4)], p.jumps[&(3, 0)]), (1, 2));
+        assert_eq!(
+            p.hot_loops(),
+            vec![BackEdge {
+                from: 3,
+                to: 0,
+                taken: 2
+            }]
+        );
+
+        assert_eq!(
+            p.report(&program, 5),
+            "                 #ip 0
+   3  25.0%   0: addi 1 1 1
+   3  25.0%   1: gtri 1 2 2
+   3  25.0%   2: addr 0 2 0
+   2  16.7%   3: seti -1 0 0
+   1   8.3%   4: mulr 1 1 1
+
+hot loops:
+    3 -> 0   taken 2 times, 0 to 3 is 91.7% of the steps
+
+halted after 12 steps"
+        );
+    }
+
+    #[test]
+    fn budget() {
+        // Never gets out
+        let program: Program = "#ip 0\nseti -1 0 0".parse().unwrap();
+        let mut vm = Vm::new(program, [0; 1]);
+        let p = Profile::run(&mut vm, Some(1000));
+
+        assert_eq!(p.end, End::Budget(0));
+        assert_eq!(p.hits, vec![1000]);
+        assert_eq!(p.back_edges[&(0, 0)], 1000);
+    }
+
+    #[test]
+    fn shortcuts() {
+        // Day 19's loops, counting what they stand for
+        let program: Program = "#ip 2
+seti 1 1 3
+seti 1 7 5
+mulr 3 5 4
+eqrr 4 1 4
+addr 4 2 2
+addi 2 1 2
+addr 3 0 0
+addi 5 1 5
+gtrr 5 1 4
+addr 2 4 2
+seti 1 3 2
+addi 3 1 3
+gtrr 3 1 4
+addr 4 2 2
+seti 0 9 2
+seti 99 0 2"
+            .parse()
+            .unwrap();
+
+        // And the loops that divide, with day 21's registers
+        let divide: Program = "#ip 4
+seti 0 1 0
+addi 0 1 1
+muli 1 7 1
+gtrr 1 3 1
+addr 4 1 4
+addi 4 1 4
+seti 8 1 4
+addi 0 1 0
+seti 0 8 4"
+            .parse()
+            .unwrap();
+
+        let same = |program: &Program, reg: [i64; 6]| {
+            let mut slow = Vm::new(program.clone(), reg);
+            let mut fast = Vm::new(program.clone(), reg).optimized();
+            assert_eq!(fast.shortcuts().count(), 1);
+            assert_eq!(Profile::run(&mut slow, None), Profile::run(&mut fast, None));
+        };
+
+        for n in [-2, 0, 1, 12, 16, 29] {
+            same(&program, [0, n, 0, 0, 0, 0]);
+            same(&divide, [0, 0, 0, n * 5, 0, 0]);
+        }
+    }
+}
@@ -0,0 +1,115 @@
+//! Programs in the text format, an optional `#ip N` line then one instruction per line
+
+use std::fmt;
+use std::str::FromStr;
+
+use common::parse::Tokens;
+use common::ParseError;
+
+use crate::op::Inst;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Program {
+    /// Register the instruction pointer is bound to
+    pub ip: Option<usize>,
+    pub insts: Vec<Inst>,
+}
+
+impl Program {
+    pub fn new(ip: Option<usize>, insts: Vec<Inst>) -> Program {
+        Program { ip, insts }
+    }
+
+    pub fn len(&self) -> usize {
+        self.insts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.insts.is_empty()
+    }
+
+    /// FNV-1a of the text, the same from one build to the next unlike `Hash`
+    pub fn fingerprint(&self) -> u64 {
+        (self.to_string().bytes()).fold(0xcbf2_9ce4_8422_2325, |h, b| {
+            (h ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+        })
+    }
+}
+
+impl FromStr for Program {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut program = Program::default();
+
+        for (n, line) in s.lines().enumerate() {
+            let line_no = n + 1;
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            // Anything starting with '#' should be the binding, and only up top
+            if line.trim_start().starts_with('#') && program.ip.is_none() && program.is_empty() {
+                let mut words = Tokens::words(line);
+                let ip = words
+                    .next_tok("'#ip'")
+                    .and_then(|t| t.expect("#ip"))
+                    .and_then(|_| words.parse_next("ip register"))
+                    .map_err(|e| e.at_line(line_no))?;
+                program.ip = Some(ip);
+                continue;
+            }
+
+            let inst = line.parse().map_err(|e: ParseError| e.at_line(line_no))?;
+            program.insts.push(inst);
+        }
+
+        Ok(program)
+    }
+}
+
+impl fmt::Display for Program {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(ip) = self.ip {
+            writeln!(f, "#ip {}", ip)?;
+        }
+
+        for i in &self.insts {
+            writeln!(f, "{}", i)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::op::Opcode;
+
+    const EXAMPLE: &str = "#ip 0\nseti 5 0 1\nseti 6 0 2\naddi 0 1 0\n";
+
+    #[test]
+    fn round_trip() {
+        let p: Program = EXAMPLE.parse().unwrap();
+
+        assert_eq!(p.ip, Some(0));
+        assert_eq!(p.insts[2], Inst::new(Opcode::Addi, [0, 1, 0]));
+        assert_eq!(p.to_string(), EXAMPLE);
+    }
+
+    #[test]
+    fn errors() {
+        let e = "#ix 0\nseti 5 0 1".parse::<Program>().unwrap_err();
+        assert_eq!(e.to_string(), "line 1, column 1: expected #ip at '#ix'");
+
+        let e = "#ip 0\nseti 5 0".parse::<Program>().unwrap_err();
+        assert_eq!(e.to_string(), "line 2, column 9: missing c");
+
+        // No binding is fine, but only at the start
+        let p: Program = "seti 5 0 1".parse().unwrap();
+        assert_eq!(p.ip, None);
+        assert!("seti 5 0 1\n#ip 0".parse::<Program>().is_err());
+    }
+}
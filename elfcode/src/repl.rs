@@ -0,0 +1,386 @@
+//! A scratchpad for trying opcodes out: type instructions and see what they do to the
+//! registers, load a program and run bits of it, or ask which opcodes fit a day 16
+//! sample
+
+use std::fs;
+use std::io::{self, BufRead, Write};
+
+use common::parse::Tokens;
+
+use crate::asm::assemble;
+use crate::debug::{changes, number, register, value, Error};
+use crate::infer::Sample;
+use crate::op::{Inst, Opcode};
+use crate::program::Program;
+use crate::vm::Vm;
+use crate::word::Word;
+
+pub const HELP: &str = "\
+<op> <a> <b> <c>       run one instruction at the pc, like addr 1 2 3
+regs [v0 v1 ...]       show the registers, or set them all  (r)
+set <reg|pc> <value>   change a register or the pc
+ip [reg|none]          show or change the register the ip is bound to
+load <file>            a program to run, .asm files get assembled
+list                   the program with the pc marked  (l)
+run [from] [to]        run the program from a pc, the one it's at by default, until
+                       it gets to another or halts
+match <before> [op] <a> <b> <c> <after>
+                       which opcodes turn the registers before into the ones after
+quit                   (q)
+
+Registers are r0, r1, ... or just the number. match takes day 16 samples pasted
+in, like match [3, 2, 1, 1] 9 2 1 2 [3, 2, 2, 1].";
+
+/// Most a `run` goes before giving up, in case it never gets there
+const LIMIT: u64 = 10_000_000;
+
+#[derive(Debug, Clone)]
+pub struct Repl<const N: usize, W: Word> {
+    pub vm: Vm<N, W>,
+}
+
+impl<const N: usize, W: Word> Repl<N, W> {
+    pub fn new(vm: Vm<N, W>) -> Repl<N, W> {
+        Repl { vm }
+    }
+
+    /// Read commands until quit or the input runs out
+    pub fn repl<R: BufRead, O: Write>(&mut self, input: R, out: &mut O) -> io::Result<()> {
+        let mut lines = input.lines();
+
+        loop {
+            write!(out, "elf> ")?;
+            out.flush()?;
+
+            let line = match lines.next() {
+                Some(l) => l?,
+                None => return Ok(()),
+            };
+
+            if !self.command(&line, out)? {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Do one command, false once it's time to quit. Mistakes get reported to `out`.
+    pub fn command<O: Write>(&mut self, line: &str, out: &mut O) -> io::Result<bool> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let (cmd, args) = match words.split_first() {
+            Some((c, a)) => (*c, a),
+            None => return Ok(true),
+        };
+
+        let res = match cmd {
+            op if Opcode::from_name(op).is_some() => self.exec(line, out),
+            "regs" | "r" => self.regs(args, out),
+            "set" => self.set(args, out),
+            "ip" => self.ip(args, out),
+            "load" => self.load(args, out),
+            "list" | "l" => self.list(out),
+            "run" => self.run(args, out),
+            "match" | "m" => self.matches(line, out),
+            "help" | "h" => writeln!(out, "{}", HELP).map_err(Into::into),
+            "quit" | "q" => return Ok(false),
+            _ => Err(Error::Bad(format!("unknown command '{}', try help", cmd))),
+        };
+
+        match res {
+            Ok(()) => Ok(true),
+            Err(Error::Bad(e)) => writeln!(out, "{}", e).map(|_| true),
+            Err(Error::Io(e)) => Err(e),
+        }
+    }
+
+    fn exec<O: Write>(&mut self, line: &str, out: &mut O) -> Result<(), Error> {
+        let inst: Inst = line.parse().map_err(|e| Error::Bad(format!("{}", e)))?;
+        let (pc, before) = (self.vm.pc(), self.vm.reg);
+
+        (self.vm.exec(inst)).map_err(|f| Error::Bad(format!("fault at {}", f)))?;
+        changes(&before, &self.vm.reg, out)?;
+        if self.vm.program().ip.is_some() {
+            writeln!(out, "  pc: {} -> {}", pc, self.vm.pc())?;
+        }
+        Ok(())
+    }
+
+    fn print<O: Write>(&self, out: &mut O) -> Result<(), Error> {
+        let regs: Vec<String> = (self.vm.reg.iter().enumerate())
+            .map(|(r, v)| format!("r{}={}", r, v))
+            .collect();
+        writeln!(out, "pc={} {}", self.vm.pc(), regs.join(" "))?;
+        Ok(())
+    }
+
+    fn regs<O: Write>(&mut self, args: &[&str], out: &mut O) -> Result<(), Error> {
+        match args.len() {
+            0 => (),
+            n if n == N => {
+                for (r, v) in self.vm.reg.iter_mut().zip(args) {
+                    *r = value(v)?;
+                }
+            }
+            _ => return Err(Error::Bad(format!("usage: regs [v0 .. v{}]", N - 1))),
+        }
+        self.print(out)
+    }
+
+    fn set<O: Write>(&mut self, args: &[&str], out: &mut O) -> Result<(), Error> {
+        match args {
+            ["pc", v] => self.vm.set_pc(number(v, "pc")?),
+            [r, v] => {
+                let r = register::<N>(r)?;
+                self.vm.reg[r] = value(v)?;
+            }
+            _ => return Err(Error::usage("set <reg|pc> <value>")),
+        }
+        self.print(out)
+    }
+
+    /// The same program and registers with another ip binding
+    fn ip<O: Write>(&mut self, args: &[&str], out: &mut O) -> Result<(), Error> {
+        let ip = match args {
+            [] => self.vm.program().ip,
+            ["none"] => None,
+            [r] => Some(register::<N>(r)?),
+            _ => return Err(Error::usage("ip [reg|none]")),
+        };
+
+        if ip != self.vm.program().ip {
+            let program = Program::new(ip, self.vm.program().insts.clone());
+            self.replace(program, self.vm.pc());
+        }
+        match ip {
+            Some(r) => writeln!(out, "the ip is r{}", r)?,
+            None => writeln!(out, "the ip isn't bound")?,
+        }
+        Ok(())
+    }
+
+    /// Keeping the registers, overflow mode and shortcuts
+    fn replace(&mut self, program: Program, pc: usize) {
+        let mut vm = Vm::new(program, self.vm.reg).with_overflow(self.vm.overflow());
+        if self.vm.is_optimized() {
+            vm = vm.optimized();
+        }
+        self.vm = vm;
+        self.vm.set_pc(pc);
+    }
+
+    fn load<O: Write>(&mut self, args: &[&str], out: &mut O) -> Result<(), Error> {
+        let path = match args {
+            [p] => *p,
+            _ => return Err(Error::usage("load <file>")),
+        };
+
+        let text = fs::read_to_string(path)
+            .map_err(|e| Error::Bad(format!("can't read {}: {}", path, e)))?;
+        let program = if path.ends_with(".asm") {
+            assemble(&text)
+        } else {
+            text.parse()
+        };
+        let program = program.map_err(|e| Error::Bad(e.report(&text)))?;
+
+        writeln!(out, "{} instructions", program.len())?;
+        self.replace(program, 0);
+        Ok(())
+    }
+
+    fn list<O: Write>(&self, out: &mut O) -> Result<(), Error> {
+        if let Some(ip) = self.vm.program().ip {
+            writeln!(out, "     #ip {}", ip)?;
+        }
+        for (pc, i) in self.vm.program().insts.iter().enumerate() {
+            let here = if pc == self.vm.pc() { "=>" } else { "  " };
+            writeln!(out, "{}{:>3}: {}", here, pc, i)?;
+        }
+        Ok(())
+    }
+
+    fn run<O: Write>(&mut self, args: &[&str], out: &mut O) -> Result<(), Error> {
+        let (from, to) = match args {
+            [] => (None, None),
+            [f] => (Some(number(f, "pc")?), None),
+            [f, t] => (Some(number(f, "pc")?), Some(number(t, "pc")?)),
+            _ => return Err(Error::usage("run [from] [to]")),
+        };
+        if let Some(pc) = from {
+            self.vm.set_pc(pc);
+        }
+
+        let before = self.vm.reg;
+        let start = self.vm.steps();
+        let ran = |vm: &Vm<N, W>| vm.steps() - start;
+
+        while !self.vm.halted() && ran(&self.vm) < LIMIT {
+            if ran(&self.vm) > 0 && Some(self.vm.pc()) == to {
+                break;
+            }
+            (self.vm.step()).map_err(|f| Error::Bad(format!("fault at {}", f)))?;
+        }
+
+        changes(&before, &self.vm.reg, out)?;
+        let n = ran(&self.vm);
+        if self.vm.halted() {
+            writeln!(out, "halted at pc {} after {} steps", self.vm.pc(), n)?;
+        } else if Some(self.vm.pc()) == to {
+            writeln!(out, "at pc {} after {} steps", self.vm.pc(), n)?;
+        } else {
+            writeln!(out, "gave up at pc {} after {} steps", self.vm.pc(), n)?;
+        }
+        Ok(())
+    }
+
+    /// The numbers in the line, so brackets and commas don't matter
+    fn matches<O: Write>(&self, line: &str, out: &mut O) -> Result<(), Error> {
+        let usage = || Error::usage("match <before> [op] <a> <b> <c> <after>");
+        let rest = line
+            .trim_start()
+            .split_once(char::is_whitespace)
+            .ok_or_else(usage)?
+            .1;
+        let nums = Tokens::numbers(rest)
+            .map(|t| t.parse::<i32>().map_err(|e| Error::Bad(e.to_string())))
+            .collect::<Result<Vec<i32>, Error>>()?;
+
+        let reg = |s: &[i32]| [s[0], s[1], s[2], s[3]];
+        let (before, op, args, after) = match nums.len() {
+            11 => (reg(&nums[..4]), -1, &nums[4..7], reg(&nums[7..])),
+            12 => (reg(&nums[..4]), nums[4], &nums[5..8], reg(&nums[8..])),
+            _ => return Err(usage()),
+        };
+
+        let sample = Sample {
+            before,
+            op,
+            args: [args[0], args[1], args[2]],
+            after,
+        };
+        let ops: Vec<_> = sample.candidates().iter().map(|o| o.name()).collect();
+        match ops.len() {
+            0 => writeln!(out, "none of them")?,
+            n => writeln!(out, "{} of them: {}", n, ops.join(" "))?,
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Run a script, giving back what got printed
+    fn session(script: &str) -> String {
+        let vm = Vm::new(Program::default(), [0_i64; 4]);
+        let mut out = vec![];
+        Repl::new(vm).repl(script.as_bytes(), &mut out).unwrap();
+        String::from_utf8(out).unwrap().replace("elf> ", "")
+    }
+
+    #[test]
+    fn instructions() {
+        // Once r3 is the ip, addi 3 2 3 at pc 2 jumps to 5
+        let out =
+            session("seti 5 0 1\nmuli 1 3 2\nregs 1 2 3 4\nip 3\naddi 3 2 3\nlist\nip none\nr");
+        assert_eq!(
+            out,
+            "  r1: 0 -> 5
+  r2: 0 -> 15
+pc=2 r0=1 r1=2 r2=3 r3=4
+the ip is r3
+  pc: 2 -> 5
+     #ip 3
+the ip isn't bound
+pc=5 r0=1 r1=2 r2=3 r3=4
+"
+        );
+    }
+
+    #[test]
+    fn programs() {
+        let path = std::env::temp_dir().join(format!("repl-{}.asm", std::process::id()));
+        fs::write(
+            &path,
+            "#ip 3\n    seti 3 _ r1\ntop: addi r1 -1 r1\n    eqri r1 0 r2\n    addr r2 ip ip\n    jmp top\n",
+        )
+        .unwrap();
+
+        let out = session(&format!(
+            "load {}\nl\nrun 0 1\nrun 1 1\nrun\nrun 9\nset pc 2\nrun",
+            path.display()
+        ));
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            out,
+            "5 instructions
+     #ip 3
+=>  0: seti 3 0 1
+    1: addi 1 -1 1
+    2: eqri 1 0 2
+    3: addr 2 3 3
+    4: seti 0 0 3
+  r1: 0 -> 3
+at pc 1 after 1 steps
+  r1: 3 -> 2
+at pc 1 after 4 steps
+  r1: 2 -> 0
+  r2: 0 -> 1
+  r3: 0 -> 4
+halted at pc 5 after 7 steps
+halted at pc 9 after 0 steps
+pc=2 r0=0 r1=0 r2=1 r3=4
+halted at pc 5 after 2 steps
+"
+        );
+    }
+
+    #[test]
+    fn shortcuts() {
+        // Day 19's loops, loaded into a repl started with nothing
+        let path = std::env::temp_dir().join(format!("repl-{}.txt", std::process::id()));
+        fs::write(
+            &path,
+            "#ip 2\nseti 1 1 3\nseti 1 7 5\nmulr 3 5 4\neqrr 4 1 4\naddr 4 2 2\naddi 2 1 2\naddr 3 0 0\naddi 5 1 5\ngtrr 5 1 4\naddr 2 4 2\nseti 1 3 2\naddi 3 1 3\ngtrr 3 1 4\naddr 4 2 2\nseti 0 9 2\nseti 99 0 2\n",
+        )
+        .unwrap();
+        let script = format!("load {}\nip 2\nset r1 12\nrun 0", path.display());
+
+        let mut outs = vec![];
+        for optimize in [false, true] {
+            let mut vm = Vm::new(Program::default(), [0_i64; 6]);
+            if optimize {
+                vm = vm.optimized();
+            }
+            let mut repl = Repl::new(vm);
+            let mut out = vec![];
+            repl.repl(script.as_bytes(), &mut out).unwrap();
+
+            assert_eq!(repl.vm.shortcuts().count(), optimize as usize);
+            outs.push(String::from_utf8(out).unwrap());
+        }
+        fs::remove_file(&path).unwrap();
+
+        // 1 + 2 + 3 + 4 + 6 + 12
+        assert!(outs[1].contains("  r0: 0 -> 28\n"), "{}", outs[1]);
+        assert_eq!(outs[0], outs[1]);
+    }
+
+    #[test]
+    fn matches_and_mistakes() {
+        let out = session(
+            "match [3, 2, 1, 1] 9 2 1 2 [3, 2, 2, 1]\nm 0 0 0 0 / 7 0 0 / 9 9 9 9\nm 1 2 3\naddr 1 9 0\naddr x\nfrob",
+        );
+        assert_eq!(
+            out,
+            "3 of them: addi mulr seti
+none of them
+usage: match <before> [op] <a> <b> <c> <after>
+fault at pc 0: 'addr 1 9 0' uses a register that isn't there
+column 6: invalid digit found in string at 'x'
+unknown command 'frob', try help
+"
+        );
+    }
+}
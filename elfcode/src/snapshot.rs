@@ -0,0 +1,443 @@
+//! Saving where a VM has got to and carrying on from there later, and logs of snapshots
+//! along a run that can be replayed from any of them to check it still ends up in the
+//! same places. In text they look like
+//!
+//! ```text
+//! program 3f1c0e5a9b27d846
+//! ip 5
+//! pc 17
+//! steps 1024
+//! overflow checked
+//! optimized no
+//! reg 0 4 1 17 0 3
+//! ```
+//!
+//! with a blank line between the snapshots of a log.
+
+use std::error;
+use std::fmt;
+use std::str::FromStr;
+
+use common::parse::{ErrorKind, Token, Tokens};
+use common::ParseError;
+
+use crate::program::Program;
+use crate::vm::{Fault, Vm};
+use crate::word::{Overflow, Word};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Snapshot<const N: usize, W: Word> {
+    /// The program's fingerprint
+    pub program: u64,
+    pub ip: Option<usize>,
+    pub pc: usize,
+    pub steps: u64,
+    pub overflow: Overflow,
+    /// Whether it was running loops natively
+    pub optimized: bool,
+    pub reg: [W; N],
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    /// Taken running some other program
+    Program {
+        snapshot: u64,
+        program: u64,
+    },
+    Ip {
+        snapshot: Option<usize>,
+        program: Option<usize>,
+    },
+    /// There aren't that many in the log
+    NoSnapshot(usize),
+    Fault(Fault),
+    /// The replay didn't match snapshot `at`
+    Diverged {
+        at: usize,
+        what: String,
+    },
+}
+
+fn bound(ip: &Option<usize>) -> String {
+    match ip {
+        Some(r) => format!("r{}", r),
+        None => "nothing".to_owned(),
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Program { snapshot, program } => write!(
+                f,
+                "the snapshot is of program {:016x}, this one is {:016x}",
+                snapshot, program
+            ),
+            Error::Ip { snapshot, program } => write!(
+                f,
+                "the snapshot has the ip bound to {}, the program to {}",
+                bound(snapshot),
+                bound(program)
+            ),
+            Error::NoSnapshot(i) => write!(f, "the log has no snapshot {}", i),
+            Error::Fault(e) => write!(f, "{}", e),
+            Error::Diverged { at, what } => write!(f, "snapshot {} differs, {}", at, what),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+/// The first thing that's different, with `want` what the log has
+fn difference<const N: usize, W: Word>(want: &Snapshot<N, W>, got: &Snapshot<N, W>) -> String {
+    if got.steps != want.steps {
+        return format!("steps are {}, the log has {}", got.steps, want.steps);
+    }
+    if got.pc != want.pc {
+        return format!("pc is {}, the log has {}", got.pc, want.pc);
+    }
+    match (0..N).find(|&r| got.reg[r] != want.reg[r]) {
+        Some(r) => format!("r{} is {}, the log has {}", r, got.reg[r], want.reg[r]),
+        None => "its settings changed".to_owned(),
+    }
+}
+
+/// Snapshots in the order they were taken
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Log<const N: usize, W: Word> {
+    pub snapshots: Vec<Snapshot<N, W>>,
+}
+
+impl<const N: usize, W: Word> Log<N, W> {
+    /// Run `vm` until it halts or has taken `budget` more steps, snapshotting at the
+    /// start, about every `every` steps and at the end
+    pub fn record(vm: &mut Vm<N, W>, every: u64, budget: Option<u64>) -> Result<Log<N, W>, Fault> {
+        let every = every.max(1);
+        let mut snapshots = vec![vm.snapshot()];
+        let base = vm.steps();
+        let mut next = base + every;
+
+        while !vm.halted() && budget.is_none_or(|b| vm.steps() - base < b) {
+            vm.step()?;
+
+            // Shortcuts can jump right past a multiple
+            if vm.steps() >= next {
+                snapshots.push(vm.snapshot());
+                next = vm.steps() + every;
+            }
+        }
+
+        if snapshots.last().is_some_and(|s| s.steps != vm.steps()) {
+            snapshots.push(vm.snapshot());
+        }
+
+        Ok(Log { snapshots })
+    }
+
+    /// Carry on from snapshot `from` and check it gets to all the ones after it. Says how
+    /// many it checked.
+    pub fn replay(&self, program: &Program, from: usize) -> Result<usize, Error> {
+        let start = self.snapshots.get(from).ok_or(Error::NoSnapshot(from))?;
+        let mut vm = Vm::restore(program.clone(), start)?;
+
+        for (at, want) in self.snapshots.iter().enumerate().skip(from + 1) {
+            while vm.steps() < want.steps && !vm.halted() {
+                vm.step().map_err(Error::Fault)?;
+            }
+
+            let got = vm.snapshot();
+            if got != *want {
+                let what = difference(want, &got);
+                return Err(Error::Diverged { at, what });
+            }
+        }
+
+        Ok(self.snapshots.len() - from - 1)
+    }
+}
+
+impl<const N: usize, W: Word> fmt::Display for Snapshot<N, W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "program {:016x}", self.program)?;
+        match self.ip {
+            Some(r) => writeln!(f, "ip {}", r)?,
+            None => writeln!(f, "ip none")?,
+        }
+        writeln!(f, "pc {}", self.pc)?;
+        writeln!(f, "steps {}", self.steps)?;
+        writeln!(f, "overflow {}", self.overflow)?;
+        writeln!(f, "optimized {}", if self.optimized { "yes" } else { "no" })?;
+
+        write!(f, "reg")?;
+        for r in &self.reg {
+            write!(f, " {}", r)?;
+        }
+        writeln!(f)
+    }
+}
+
+impl<const N: usize, W: Word> fmt::Display for Log<N, W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, s) in self.snapshots.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", s)?;
+        }
+        Ok(())
+    }
+}
+
+fn expected(what: &'static str, tok: Token) -> ParseError {
+    ParseError::expected(what, tok)
+}
+
+/// The next line, which has to start with `key`, and the rest of its words
+fn field<'a>(
+    lines: &mut impl Iterator<Item = (usize, &'a str)>,
+    key: &'static str,
+    prev: usize,
+) -> Result<(usize, Tokens<'a>), ParseError> {
+    let (n, line) = lines
+        .next()
+        .ok_or_else(|| ParseError::new(ErrorKind::Missing(key), 1, "").at_line(prev + 1))?;
+    let mut words = Tokens::words(line);
+    words
+        .next_tok(key)
+        .and_then(|t| t.expect(key))
+        .map_err(|e| e.at_line(n))?;
+    Ok((n, words))
+}
+
+/// `f` on the one value after the key
+fn value<'a, T>(
+    (n, mut words): (usize, Tokens<'a>),
+    what: &'static str,
+    f: impl FnOnce(Token<'a>) -> Result<T, ParseError>,
+) -> Result<(usize, T), ParseError> {
+    let v = words.next_tok(what).and_then(f).map_err(|e| e.at_line(n))?;
+    match words.next() {
+        Some(t) => Err(expected("end of line", t).at_line(n)),
+        None => Ok((n, v)),
+    }
+}
+
+/// One snapshot from the lines of `lines`, which has to have exactly its 7
+fn parse<const N: usize, W: Word>(
+    lines: &[(usize, &str)],
+    prev: usize,
+) -> Result<Snapshot<N, W>, ParseError> {
+    let mut it = lines.iter().copied();
+    let it = &mut it;
+
+    let hex =
+        |t: Token| u64::from_str_radix(t.text, 16).map_err(|e| ParseError::new(e, t.col, t.text));
+    let (n, program) = value(field(it, "program", prev)?, "fingerprint", hex)?;
+    let (n, ip) = value(field(it, "ip", n)?, "ip register", |t| match t.text {
+        "none" => Ok(None),
+        _ => t.parse().map(Some),
+    })?;
+    let (n, pc) = value(field(it, "pc", n)?, "pc", |t| t.parse())?;
+    let (n, steps) = value(field(it, "steps", n)?, "step count", |t| t.parse())?;
+    let (n, overflow) = value(field(it, "overflow", n)?, "overflow mode", |t| {
+        (t.text.parse()).map_err(|_| expected("wrapping, checked or saturating", t))
+    })?;
+    let (n, optimized) = value(field(it, "optimized", n)?, "yes or no", |t| match t.text {
+        "yes" => Ok(true),
+        "no" => Ok(false),
+        _ => Err(expected("yes or no", t)),
+    })?;
+
+    let (n, mut words) = field(it, "reg", n)?;
+    let mut reg = [W::ZERO; N];
+    for r in reg.iter_mut() {
+        let tok = words.next_tok("register").map_err(|e| e.at_line(n))?;
+        let v: i128 = tok.parse().map_err(|e| e.at_line(n))?;
+        *r = W::from_i128(v, Overflow::Checked)
+            .ok_or_else(|| expected("a value that fits a register", tok).at_line(n))?;
+    }
+    if let Some(t) = words.next() {
+        return Err(expected("end of line", t).at_line(n));
+    }
+
+    if let Some((n, line)) = it.next() {
+        let first = Tokens::words(line).next().unwrap();
+        return Err(expected("a blank line after the snapshot", first).at_line(n));
+    }
+
+    Ok(Snapshot {
+        program,
+        ip,
+        pc,
+        steps,
+        overflow,
+        optimized,
+        reg,
+    })
+}
+
+impl<const N: usize, W: Word> FromStr for Log<N, W> {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lines: Vec<(usize, &str)> = s.lines().enumerate().map(|(n, l)| (n + 1, l)).collect();
+        let mut snapshots = vec![];
+
+        for group in lines.split(|(_, l)| l.trim().is_empty()) {
+            if let Some(&(n, _)) = group.first() {
+                snapshots.push(parse(group, n - 1)?);
+            }
+        }
+
+        Ok(Log { snapshots })
+    }
+}
+
+impl<const N: usize, W: Word> FromStr for Snapshot<N, W> {
+    type Err = ParseError;
+
+    /// Just the one, a log with more has to be read as a `Log`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lines: Vec<(usize, &str)> = (s.lines().enumerate())
+            .map(|(n, l)| (n + 1, l))
+            .filter(|(_, l)| !l.trim().is_empty())
+            .collect();
+        parse(&lines, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asm::assemble;
+
+    /// Adds up 1 to 20 into r1, one at a time
+    const SUM: &str = "\
+#ip 5
+        seti 20 _ r2
+top:    addr r1 r2 r1
+        addi r2 -1 r2
+        gtri r2 0 r3
+        addr r3 ip ip
+        jmp done
+        jmp top
+done:   seti 7 _ r4
+";
+
+    #[test]
+    fn resumes() {
+        let p = assemble(SUM).unwrap();
+        let mut whole = Vm::new(p.clone(), [0_i64; 6]).with_overflow(Overflow::Checked);
+        whole.run().unwrap();
+
+        let mut vm = Vm::new(p.clone(), [0_i64; 6]).with_overflow(Overflow::Checked);
+        for _ in 0..37 {
+            vm.step().unwrap();
+        }
+        let text = vm.snapshot().to_string();
+        let snap: Snapshot<6, i64> = text.parse().unwrap();
+        assert_eq!(snap, vm.snapshot());
+        assert!(text.contains("\npc 2\nsteps 37\noverflow checked\noptimized no\nreg 0 "));
+
+        let mut resumed = Vm::restore(p.clone(), &snap).unwrap();
+        resumed.run().unwrap();
+        assert_eq!(resumed.snapshot(), whole.snapshot());
+        assert_eq!(resumed.reg[1], 210);
+
+        // Only for the program it came from
+        let other = assemble(&SUM.replace("seti 7", "seti 8")).unwrap();
+        assert!(matches!(
+            Vm::restore(other, &snap),
+            Err(Error::Program { .. })
+        ));
+        let unbound = Program::new(None, p.insts);
+        assert_eq!(
+            Vm::restore(unbound, &snap).unwrap_err().to_string(),
+            "the snapshot has the ip bound to r5, the program to nothing"
+        );
+    }
+
+    #[test]
+    fn replays() {
+        let p = assemble(SUM).unwrap();
+        let mut vm = Vm::new(p.clone(), [0_i64; 6]);
+        let log = Log::record(&mut vm, 25, None).unwrap();
+
+        let steps: Vec<u64> = log.snapshots.iter().map(|s| s.steps).collect();
+        assert_eq!(steps, [0, 25, 50, 75, 100, 102]);
+        assert!(vm.halted());
+
+        let read: Log<6, i64> = log.to_string().parse().unwrap();
+        assert_eq!(read, log);
+        assert_eq!(log.replay(&p, 0), Ok(5));
+        assert_eq!(log.replay(&p, 3), Ok(2));
+        assert_eq!(log.replay(&p, 6), Err(Error::NoSnapshot(6)));
+
+        // Like a VM that got addi wrong from some point on
+        let mut bad = log.clone();
+        for s in &mut bad.snapshots[3..] {
+            s.reg[1] += 1;
+        }
+        assert_eq!(
+            bad.replay(&p, 0).unwrap_err().to_string(),
+            "snapshot 3 differs, r1 is 195, the log has 196"
+        );
+        assert_eq!(bad.replay(&p, 3), Ok(2));
+
+        // Stopping short of the end
+        let mut vm = Vm::new(p.clone(), [0_i64; 6]);
+        let log = Log::record(&mut vm, 100, Some(30)).unwrap();
+        let steps: Vec<u64> = log.snapshots.iter().map(|s| s.steps).collect();
+        assert_eq!(steps, [0, 30]);
+
+        // Resumed, the budget counts from where the snapshot was
+        let mut vm = Vm::restore(p.clone(), log.snapshots.last().unwrap()).unwrap();
+        let log = Log::record(&mut vm, 25, Some(50)).unwrap();
+        let steps: Vec<u64> = log.snapshots.iter().map(|s| s.steps).collect();
+        assert_eq!(steps, [30, 55, 80]);
+    }
+
+    #[test]
+    fn errors() {
+        let snap = Vm::new(assemble(SUM).unwrap(), [0_i64; 6]).snapshot();
+        let text = snap.to_string();
+        let err = |s: &str| s.parse::<Snapshot<6, i64>>().unwrap_err().to_string();
+
+        assert_eq!(
+            err(&text.replace("reg 0 0 0 0 0 0", "reg 0 0 0 0 0")),
+            "line 7, column 14: missing register"
+        );
+        assert_eq!(
+            err(&text.replace("reg 0 0 0 0 0 0", "reg 0 0 0 0 0 0 0")),
+            "line 7, column 17: expected end of line at '0'"
+        );
+        assert_eq!(
+            err(&text.replace("overflow wrapping", "overflow never")),
+            "line 5, column 10: expected wrapping, checked or saturating at 'never'"
+        );
+        assert_eq!(
+            err(&text.replace("steps 0\n", "")),
+            "line 4, column 1: expected steps at 'overflow'"
+        );
+        assert_eq!(
+            err(&text.replace("reg 0 0 0 0 0 0\n", "")),
+            "line 7, column 1: missing reg"
+        );
+        assert_eq!(
+            "program 0\nip none\npc 0\nsteps 0\noverflow wrapping\noptimized no\nreg 0 0 0 99999999999"
+                .parse::<Snapshot<4, i32>>()
+                .unwrap_err()
+                .to_string(),
+            "line 7, column 11: expected a value that fits a register at '99999999999'"
+        );
+        assert_eq!(
+            err(&text[text.find("ip").unwrap()..]),
+            "line 1, column 1: expected program at 'ip'"
+        );
+        assert_eq!(
+            err(&text.replace("reg 0 0 0 0 0 0", "reg 0 0 0 0 0 0\npc 3")),
+            "line 8, column 1: expected a blank line after the snapshot at 'pc'"
+        );
+    }
+}
@@ -0,0 +1,314 @@
+//! Running programs, `N` is how many registers there are
+
+use std::error::Error;
+use std::fmt;
+
+use crate::op::Inst;
+use crate::optimize::{self, Shortcut};
+use crate::program::Program;
+use crate::snapshot::{self, Snapshot};
+use crate::word::{Overflow, Word};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FaultKind {
+    /// It or the ip binding names a register that isn't there
+    BadRegister,
+    /// Only in checked mode
+    Overflow,
+}
+
+/// An instruction that couldn't run
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Fault {
+    pub pc: usize,
+    pub inst: Inst,
+    pub kind: FaultKind,
+}
+
+impl fmt::Display for Fault {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let what = match self.kind {
+            FaultKind::BadRegister => "uses a register that isn't there",
+            FaultKind::Overflow => "overflowed",
+        };
+        write!(f, "pc {}: '{}' {}", self.pc, self.inst, what)
+    }
+}
+
+impl Error for Fault {}
+
+#[derive(Debug, Clone)]
+pub struct Vm<const N: usize, W: Word = i32> {
+    program: Program,
+    pub reg: [W; N],
+    /// Off the end when it's past the program or went negative
+    pc: usize,
+    steps: u64,
+    overflow: Overflow,
+    /// By the pc they start at, empty unless optimized
+    shortcuts: Vec<Option<Shortcut>>,
+    optimized: bool,
+}
+
+impl<const N: usize, W: Word> Vm<N, W> {
+    /// With the ip bound the program starts wherever that register points
+    pub fn new(program: Program, reg: [W; N]) -> Vm<N, W> {
+        let pc = match program.ip.and_then(|r| reg.get(r)) {
+            Some(&v) => to_pc(v),
+            None => 0,
+        };
+
+        Vm {
+            program,
+            reg,
+            pc,
+            steps: 0,
+            overflow: Overflow::default(),
+            shortcuts: vec![],
+            optimized: false,
+        }
+    }
+
+    /// Wrapping unless told otherwise
+    pub fn with_overflow(mut self, mode: Overflow) -> Vm<N, W> {
+        self.overflow = mode;
+        self
+    }
+
+    /// Loops with a shortcut run in one step, which still counts all the instructions.
+    /// Ones that would overflow on the way run as they are.
+    pub fn optimized(mut self) -> Vm<N, W> {
+        self.shortcuts = vec![None; self.program.len()];
+        for s in optimize::shortcuts(&self.program) {
+            self.shortcuts[s.start] = Some(s);
+        }
+        self.optimized = true;
+        self
+    }
+
+    /// Even when there weren't any shortcuts to find
+    pub fn is_optimized(&self) -> bool {
+        self.optimized
+    }
+
+    pub fn shortcuts(&self) -> impl Iterator<Item = &Shortcut> {
+        self.shortcuts.iter().flatten()
+    }
+
+    /// The one `step` tries instead of the instruction at `pc`
+    pub fn shortcut_at(&self, pc: usize) -> Option<&Shortcut> {
+        self.shortcuts.get(pc)?.as_ref()
+    }
+
+    pub fn overflow(&self) -> Overflow {
+        self.overflow
+    }
+
+    pub fn program(&self) -> &Program {
+        &self.program
+    }
+
+    pub fn pc(&self) -> usize {
+        self.pc
+    }
+
+    pub fn set_pc(&mut self, pc: usize) {
+        self.pc = pc;
+    }
+
+    /// Instructions run so far, with shortcuts counting the ones they stand in for
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    /// What runs next, None once it's halted
+    pub fn current(&self) -> Option<&Inst> {
+        self.program.insts.get(self.pc)
+    }
+
+    pub fn halted(&self) -> bool {
+        self.current().is_none()
+    }
+
+    /// Run one instruction, false if it had already halted. With the ip bound the pc
+    /// goes into its register first and comes back out plus one after.
+    pub fn step(&mut self) -> Result<bool, Fault> {
+        let inst = match self.current() {
+            Some(&i) => i,
+            None => return Ok(false),
+        };
+        let pc = self.pc;
+        let fault = |kind| Fault { pc, inst, kind };
+
+        if let Some(&Some(s)) = self.shortcuts.get(pc) {
+            self.write_ip().map_err(fault)?;
+            if let Some(steps) = s.idiom.apply(&mut self.reg).map_err(fault)? {
+                // As if the loop's last jump out had run
+                if let Some(r) = self.program.ip {
+                    self.reg[r] =
+                        W::from_index(s.exit - 1).ok_or_else(|| fault(FaultKind::Overflow))?;
+                }
+                self.pc = s.exit;
+                self.steps += steps;
+                return Ok(true);
+            }
+        }
+
+        self.exec(inst)?;
+        Ok(true)
+    }
+
+    /// Run `inst` as if it were the one at the pc, whatever the program has there
+    pub fn exec(&mut self, inst: Inst) -> Result<(), Fault> {
+        let pc = self.pc;
+        let fault = |kind| Fault { pc, inst, kind };
+
+        self.write_ip().map_err(fault)?;
+        inst.op
+            .exec(&mut self.reg, inst.args, self.overflow)
+            .map_err(fault)?;
+
+        self.pc = match self.program.ip {
+            // Saturating can't fail, and -1 has to come out as 0
+            Some(r) => to_pc(self.reg[r].add(W::ONE, Overflow::Saturating).unwrap()),
+            None => pc + 1,
+        };
+        self.steps += 1;
+
+        Ok(())
+    }
+
+    /// The pc into the ip register, if there is one
+    fn write_ip(&mut self) -> Result<(), FaultKind> {
+        if let Some(r) = self.program.ip {
+            let v = W::from_index(self.pc).ok_or(FaultKind::Overflow)?;
+            *self.reg.get_mut(r).ok_or(FaultKind::BadRegister)? = v;
+        }
+        Ok(())
+    }
+
+    /// Until it halts
+    pub fn run(&mut self) -> Result<(), Fault> {
+        while self.step()? {}
+        Ok(())
+    }
+
+    /// Everything needed to carry on from here later, the program only by its
+    /// fingerprint
+    pub fn snapshot(&self) -> Snapshot<N, W> {
+        Snapshot {
+            program: self.program.fingerprint(),
+            ip: self.program.ip,
+            pc: self.pc,
+            steps: self.steps,
+            overflow: self.overflow,
+            optimized: self.optimized,
+            reg: self.reg,
+        }
+    }
+
+    /// Back where `snap` was taken, as long as it was taken running `program`
+    pub fn restore(program: Program, snap: &Snapshot<N, W>) -> Result<Vm<N, W>, snapshot::Error> {
+        if program.ip != snap.ip {
+            return Err(snapshot::Error::Ip {
+                snapshot: snap.ip,
+                program: program.ip,
+            });
+        }
+        if program.fingerprint() != snap.program {
+            return Err(snapshot::Error::Program {
+                snapshot: snap.program,
+                program: program.fingerprint(),
+            });
+        }
+
+        let mut vm = Vm::new(program, snap.reg).with_overflow(snap.overflow);
+        if snap.optimized {
+            vm = vm.optimized();
+        }
+        vm.pc = snap.pc;
+        vm.steps = snap.steps;
+        Ok(vm)
+    }
+}
+
+/// Negative addresses are off the end too
+pub(crate) fn to_pc<W: Word>(v: W) -> usize {
+    v.to_index().unwrap_or(usize::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "#ip 0
+seti 5 0 1
+seti 6 0 2
+addi 0 1 0
+addr 1 2 3
+setr 1 0 0
+seti 8 0 4
+seti 9 0 5";
+
+    #[test]
+    fn ip_binding() {
+        let mut vm = Vm::new(EXAMPLE.parse().unwrap(), [0; 6]);
+        vm.run().unwrap();
+
+        // The ip register keeps the last address that ran
+        assert_eq!(vm.reg, [6, 5, 6, 0, 0, 9]);
+        assert_eq!(vm.steps(), 5);
+        assert_eq!(vm.pc(), 7);
+        assert!(!vm.step().unwrap());
+    }
+
+    #[test]
+    fn unbound() {
+        let p: Program = "seti 1 0 0\naddi 0 2 1\nmulr 1 1 2".parse().unwrap();
+        let mut vm = Vm::new(p, [0; 4]);
+        vm.run().unwrap();
+
+        assert_eq!(vm.reg, [1, 3, 9, 0]);
+        assert_eq!(vm.pc(), 3);
+    }
+
+    #[test]
+    fn faults() {
+        let mut vm = Vm::new("addr 0 4 0".parse().unwrap(), [0; 4]);
+        let f = vm.step().unwrap_err();
+        assert_eq!(f.kind, FaultKind::BadRegister);
+        assert_eq!(
+            f.to_string(),
+            "pc 0: 'addr 0 4 0' uses a register that isn't there"
+        );
+
+        // Bound to a register that's missing
+        let mut vm = Vm::new("#ip 5\nseti 0 0 0".parse().unwrap(), [0; 4]);
+        assert!(vm.step().is_err());
+
+        // Checked mode stops at the overflow, wrapping just keeps going
+        let p: Program = "seti 65899 0 1\nmulr 1 1 1".parse().unwrap();
+        let mut vm = Vm::new(p.clone(), [0_i32; 2]).with_overflow(Overflow::Checked);
+        let f = vm.run().unwrap_err();
+        assert_eq!(f.to_string(), "pc 1: 'mulr 1 1 1' overflowed");
+        assert_eq!(vm.reg[1], 65899);
+
+        let mut vm = Vm::new(p.clone(), [0_i32; 2]);
+        vm.run().unwrap();
+        assert_eq!(vm.reg[1], 65899_i32.wrapping_pow(2));
+
+        let mut vm = Vm::new(p, [0_i64; 2]).with_overflow(Overflow::Checked);
+        vm.run().unwrap();
+        assert_eq!(vm.reg[1], 65899_i64.pow(2));
+
+        // Jumping backwards off the start halts, -1 goes back to the top
+        let mut vm = Vm::new("#ip 0\nseti -5 0 0".parse().unwrap(), [0; 2]);
+        vm.run().unwrap();
+        assert!(vm.halted());
+
+        let mut vm = Vm::new("#ip 0\naddi 1 1 1\nseti -1 0 0".parse().unwrap(), [0; 2]);
+        vm.step().unwrap();
+        vm.step().unwrap();
+        assert_eq!((vm.pc(), vm.reg[1]), (0, 1));
+    }
+}
@@ -0,0 +1,159 @@
+//! Register widths and what to do when arithmetic doesn't fit
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::hash::Hash;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Overflow {
+    #[default]
+    Wrapping,
+    /// Stop with a fault naming the instruction
+    Checked,
+    Saturating,
+}
+
+impl FromStr for Overflow {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "wrapping" => Ok(Overflow::Wrapping),
+            "checked" => Ok(Overflow::Checked),
+            "saturating" => Ok(Overflow::Saturating),
+            _ => Err(format!(
+                "unknown overflow mode '{}', try wrapping, checked or saturating",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Overflow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Overflow::Wrapping => "wrapping",
+            Overflow::Checked => "checked",
+            Overflow::Saturating => "saturating",
+        };
+        f.write_str(name)
+    }
+}
+
+/// What registers can hold. The ops give None when they overflow in checked mode.
+pub trait Word: Copy + Eq + Ord + Hash + Default + fmt::Debug + fmt::Display + FromStr {
+    const ZERO: Self;
+    const ONE: Self;
+    /// The Rust type, for code that gets generated
+    const NAME: &'static str;
+
+    /// Immediates are written as i32, negative ones don't fit in unsigned words
+    fn from_imm(v: i32, mode: Overflow) -> Option<Self>;
+    fn from_index(i: usize) -> Option<Self>;
+    /// As a register number or an address
+    fn to_index(self) -> Option<usize>;
+
+    fn add(self, other: Self, mode: Overflow) -> Option<Self>;
+    fn mul(self, other: Self, mode: Overflow) -> Option<Self>;
+    fn and(self, other: Self) -> Self;
+    fn or(self, other: Self) -> Self;
+
+    /// Wide enough for any of them, for working things out natively
+    fn to_i128(self) -> i128;
+    fn from_i128(v: i128, mode: Overflow) -> Option<Self>;
+}
+
+macro_rules! word {
+    ($($t:ty),*) => {$(
+        impl Word for $t {
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+            const NAME: &'static str = stringify!($t);
+
+            fn from_imm(v: i32, mode: Overflow) -> Option<Self> {
+                match mode {
+                    Overflow::Wrapping => Some(v as $t),
+                    Overflow::Checked => <$t>::try_from(v).ok(),
+                    Overflow::Saturating => Some(<$t>::try_from(v).unwrap_or(<$t>::MIN)),
+                }
+            }
+
+            fn from_index(i: usize) -> Option<Self> {
+                <$t>::try_from(i).ok()
+            }
+
+            fn to_index(self) -> Option<usize> {
+                usize::try_from(self).ok()
+            }
+
+            fn add(self, other: Self, mode: Overflow) -> Option<Self> {
+                match mode {
+                    Overflow::Wrapping => Some(self.wrapping_add(other)),
+                    Overflow::Checked => self.checked_add(other),
+                    Overflow::Saturating => Some(self.saturating_add(other)),
+                }
+            }
+
+            fn mul(self, other: Self, mode: Overflow) -> Option<Self> {
+                match mode {
+                    Overflow::Wrapping => Some(self.wrapping_mul(other)),
+                    Overflow::Checked => self.checked_mul(other),
+                    Overflow::Saturating => Some(self.saturating_mul(other)),
+                }
+            }
+
+            fn and(self, other: Self) -> Self {
+                self & other
+            }
+
+            fn or(self, other: Self) -> Self {
+                self | other
+            }
+
+            fn to_i128(self) -> i128 {
+                self as i128
+            }
+
+            fn from_i128(v: i128, mode: Overflow) -> Option<Self> {
+                match mode {
+                    Overflow::Wrapping => Some(v as $t),
+                    Overflow::Checked => <$t>::try_from(v).ok(),
+                    Overflow::Saturating => Some(v.clamp(<$t>::MIN as i128, <$t>::MAX as i128) as $t),
+                }
+            }
+        }
+    )*};
+}
+
+word!(i32, i64, u64);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn modes() {
+        let big = i32::MAX;
+
+        assert_eq!(big.add(1, Overflow::Wrapping), Some(i32::MIN));
+        assert_eq!(big.add(1, Overflow::Checked), None);
+        assert_eq!(big.add(1, Overflow::Saturating), Some(i32::MAX));
+        assert_eq!(i64::from(big).add(1, Overflow::Checked), Some(1 << 31));
+
+        assert_eq!(u64::MAX.mul(2, Overflow::Saturating), Some(u64::MAX));
+        assert_eq!(3_u64.mul(5, Overflow::Checked), Some(15));
+    }
+
+    #[test]
+    fn immediates() {
+        assert_eq!(u64::from_imm(-1, Overflow::Wrapping), Some(u64::MAX));
+        assert_eq!(u64::from_imm(-1, Overflow::Checked), None);
+        assert_eq!(u64::from_imm(-1, Overflow::Saturating), Some(0));
+        assert_eq!(i64::from_imm(-1, Overflow::Checked), Some(-1));
+
+        assert_eq!(i32::from_i128(1 << 40, Overflow::Checked), None);
+        assert_eq!(u64::from_i128(-5, Overflow::Saturating), Some(0));
+        assert_eq!(i32::from_i128(1 << 32 | 7, Overflow::Wrapping), Some(7));
+    }
+}